@@ -1,6 +1,7 @@
 #![no_std]
 
 pub static ERROR_NOT_ACTIVE: &[u8] = b"Not active";
+pub static ERROR_NOT_PAUSED: &[u8] = b"Not paused";
 pub static ERROR_EMPTY_PAYMENTS: &[u8] = b"Empty payments";
 pub static ERROR_BAD_INPUT_TOKEN: &[u8] = b"Bad input token";
 pub static ERROR_NO_FARM_TOKEN: &[u8] = b"No farm token";
@@ -13,6 +14,12 @@ pub static ERROR_BAD_PAYMENTS: &[u8] = b"Bad payments";
 pub static ERROR_NOT_ENOUGH_SUPPLY: &[u8] = b"Not enough supply";
 pub static ERROR_NOT_A_FARM_TOKEN: &[u8] = b"Not a farm token";
 pub static ERROR_NO_TOKEN_TO_MERGE: &[u8] = b"No token to merge";
+pub static ERROR_BAD_SPLIT_AMOUNTS: &[u8] = b"Bad split amounts";
 pub static ERROR_PAYMENT_FAILED: &[u8] = b"Payment failed";
 pub static ERROR_PERMISSIONS: &[u8] = b"Permission denied";
 pub static ERROR_PARAMETERS: &[u8] = b"Bad parameters";
+pub static ERROR_ACCOUNT_MODE_DISABLED: &[u8] = b"Account mode disabled";
+pub static ERROR_NO_ACCOUNT_POSITION: &[u8] = b"No account position";
+pub static ERROR_NOT_FUNDED_MODE: &[u8] = b"Not in funded mode";
+pub static ERROR_NOT_ENOUGH_REWARD_FUNDS: &[u8] = b"Not enough reward funds";
+pub static ERROR_REWARDS_NOT_STOPPED: &[u8] = b"Rewards not stopped";
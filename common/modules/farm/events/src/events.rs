@@ -87,6 +87,21 @@ pub struct CompoundRewardsEvent<M: ManagedTypeApi> {
     timestamp: u64,
 }
 
+#[derive(TypeAbi, TopEncode)]
+pub struct EmergencyExitEvent<M: ManagedTypeApi> {
+    caller: ManagedAddress<M>,
+    farming_token_id: TokenIdentifier<M>,
+    farming_token_amount: BigUint<M>,
+    farm_token_id: TokenIdentifier<M>,
+    farm_token_nonce: u64,
+    farm_token_amount: BigUint<M>,
+    farm_supply: BigUint<M>,
+    farm_attributes: FarmTokenAttributes<M>,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
 #[elrond_wasm::module]
 pub trait EventsModule {
     fn emit_enter_farm_event(&self, ctx: &GenericContext<Self::Api>) {
@@ -231,6 +246,29 @@ pub trait EventsModule {
         )
     }
 
+    fn emit_emergency_exit_event(&self, ctx: &GenericContext<Self::Api>) {
+        let first_pay = &ctx.get_tx_input().first_payment;
+
+        self.emergency_exit_event(
+            ctx.get_caller(),
+            ctx.get_farm_token_id(),
+            ctx.get_block_epoch(),
+            &EmergencyExitEvent {
+                caller: ctx.get_caller().clone(),
+                farming_token_id: ctx.get_farming_token_id().clone(),
+                farming_token_amount: ctx.get_initial_farming_amount().clone(),
+                farm_token_id: ctx.get_farm_token_id().clone(),
+                farm_token_nonce: first_pay.token_nonce,
+                farm_token_amount: first_pay.amount.clone(),
+                farm_supply: ctx.get_farm_token_supply().clone(),
+                farm_attributes: ctx.get_input_attributes().clone(),
+                block: ctx.get_block_nonce(),
+                epoch: ctx.get_block_epoch(),
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
     #[event("enter_farm")]
     fn enter_farm_event(
         &self,
@@ -266,4 +304,13 @@ pub trait EventsModule {
         #[indexed] epoch: u64,
         compound_rewards_event: &CompoundRewardsEvent<Self::Api>,
     );
+
+    #[event("emergency_exit")]
+    fn emergency_exit_event(
+        &self,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] farm_token: &TokenIdentifier,
+        #[indexed] epoch: u64,
+        emergency_exit_event: &EmergencyExitEvent<Self::Api>,
+    );
 }
@@ -0,0 +1,196 @@
+#![no_std]
+#![feature(generic_associated_types)]
+
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use common_structs::FarmTokenAttributes;
+use elrond_wasm::elrond_codec::{TopDecode, TopEncode};
+
+pub mod destination_farm_proxy {
+    elrond_wasm::imports!();
+
+    #[elrond_wasm::proxy]
+    pub trait DestinationFarm {
+        #[payable("*")]
+        #[endpoint(migratePosition)]
+        fn migrate_position(
+            &self,
+            source_attributes: ManagedBuffer,
+            orig_caller: ManagedAddress,
+        ) -> EsdtTokenPayment<Self::Api>;
+    }
+}
+
+#[elrond_wasm::module]
+pub trait FarmMigrationModule:
+    config::ConfigModule
+    + token_send::TokenSendModule
+    + farm_token::FarmTokenModule
+    + rewards::RewardsModule
+    + pausable::PausableModule
+    + elrond_wasm_modules::default_issue_callbacks::DefaultIssueCallbacksModule
+{
+    /// Allows the given farm to migrate its positions into this one.
+    #[only_owner]
+    #[endpoint(addMigrationSource)]
+    fn add_migration_source(&self, source_farm_address: ManagedAddress) {
+        require!(
+            source_farm_address != self.blockchain().get_sc_address(),
+            "Cannot migrate from self"
+        );
+
+        let _ = self.migration_sources().insert(source_farm_address);
+    }
+
+    #[only_owner]
+    #[endpoint(removeMigrationSource)]
+    fn remove_migration_source(&self, source_farm_address: ManagedAddress) {
+        let _ = self.migration_sources().swap_remove(&source_farm_address);
+    }
+
+    /// The farm to which the positions of this farm can be migrated.
+    /// This farm must be added as a migration source in the destination farm.
+    #[only_owner]
+    #[endpoint(setMigrationDestination)]
+    fn set_migration_destination(&self, destination_farm_address: ManagedAddress) {
+        require!(
+            destination_farm_address != self.blockchain().get_sc_address(),
+            "Cannot migrate to self"
+        );
+
+        self.migration_destination().set(&destination_farm_address);
+    }
+
+    /// Checks the caller is a migration source and returns the attributes of the new position.
+    /// The source farm pays out the rewards of the position, so the new position
+    /// starts from the current reward per share of this farm.
+    fn process_incoming_migration(
+        &self,
+        source_attributes: ManagedBuffer,
+        farming_amount: &BigUint,
+        reward_per_share: &BigUint,
+    ) -> FarmTokenAttributes<Self::Api> {
+        let caller = self.blockchain().get_caller();
+        require!(
+            self.migration_sources().contains(&caller),
+            "Caller is not a migration source"
+        );
+
+        let old_attributes = FarmTokenAttributes::<Self::Api>::top_decode(source_attributes)
+            .unwrap_or_else(|_| sc_panic!("Failed to decode farm token attributes"));
+
+        self.incoming_migrated_positions(&caller)
+            .update(|x| *x += 1);
+        self.incoming_migrated_farming_amount(&caller)
+            .update(|x| *x += farming_amount);
+
+        FarmTokenAttributes {
+            reward_per_share: reward_per_share.clone(),
+            original_entering_epoch: old_attributes.original_entering_epoch,
+            entering_epoch: old_attributes.entering_epoch,
+            initial_farming_amount: farming_amount.clone(),
+            compounded_reward: BigUint::zero(),
+            current_farm_amount: farming_amount.clone(),
+        }
+    }
+
+    /// Sends the farming tokens and the attributes of a position of this farm
+    /// to the migration destination, which creates the new position for `orig_caller`.
+    fn forward_position_to_destination(
+        &self,
+        farming_tokens: EsdtTokenPayment<Self::Api>,
+        attributes: &FarmTokenAttributes<Self::Api>,
+        orig_caller: ManagedAddress,
+    ) -> EsdtTokenPayment<Self::Api> {
+        require!(
+            !self.migration_destination().is_empty(),
+            "No migration destination"
+        );
+
+        let mut encoded_attributes = ManagedBuffer::new();
+        attributes
+            .top_encode(&mut encoded_attributes)
+            .unwrap_or_else(|_| sc_panic!("Failed to encode farm token attributes"));
+
+        self.outgoing_migrated_positions().update(|x| *x += 1);
+        self.outgoing_migrated_farming_amount()
+            .update(|x| *x += &farming_tokens.amount);
+
+        let destination = self.migration_destination().get();
+        self.destination_farm_proxy(destination)
+            .migrate_position(encoded_attributes, orig_caller)
+            .add_esdt_token_transfer(
+                farming_tokens.token_identifier,
+                farming_tokens.token_nonce,
+                farming_tokens.amount,
+            )
+            .execute_on_dest_context()
+    }
+
+    #[view(getMigrationSources)]
+    fn get_migration_sources(&self) -> MultiValueEncoded<ManagedAddress> {
+        let mut result = MultiValueEncoded::new();
+        for source in self.migration_sources().iter() {
+            result.push(source);
+        }
+        result
+    }
+
+    /// Returns the number of positions and the amount of farming tokens
+    /// received from the given source farm.
+    #[view(getIncomingMigrationProgress)]
+    fn get_incoming_migration_progress(
+        &self,
+        source_farm_address: ManagedAddress,
+    ) -> MultiValue2<u64, BigUint> {
+        (
+            self.incoming_migrated_positions(&source_farm_address).get(),
+            self.incoming_migrated_farming_amount(&source_farm_address)
+                .get(),
+        )
+            .into()
+    }
+
+    /// Returns the number of positions and the amount of farming tokens
+    /// sent to the migration destination.
+    #[view(getOutgoingMigrationProgress)]
+    fn get_outgoing_migration_progress(&self) -> MultiValue2<u64, BigUint> {
+        (
+            self.outgoing_migrated_positions().get(),
+            self.outgoing_migrated_farming_amount().get(),
+        )
+            .into()
+    }
+
+    #[proxy]
+    fn destination_farm_proxy(
+        &self,
+        to: ManagedAddress,
+    ) -> destination_farm_proxy::Proxy<Self::Api>;
+
+    #[storage_mapper("migration_sources")]
+    fn migration_sources(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[view(getMigrationDestination)]
+    #[storage_mapper("migration_destination")]
+    fn migration_destination(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[storage_mapper("incoming_migrated_positions")]
+    fn incoming_migrated_positions(
+        &self,
+        source_farm_address: &ManagedAddress,
+    ) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("incoming_migrated_farming_amount")]
+    fn incoming_migrated_farming_amount(
+        &self,
+        source_farm_address: &ManagedAddress,
+    ) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("outgoing_migrated_positions")]
+    fn outgoing_migrated_positions(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("outgoing_migrated_farming_amount")]
+    fn outgoing_migrated_farming_amount(&self) -> SingleValueMapper<BigUint>;
+}
@@ -0,0 +1,281 @@
+#![no_std]
+
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use pausable::State;
+
+mod farm_proxy {
+    elrond_wasm::imports!();
+
+    use pausable::State;
+
+    pub type ClaimRewardsResultType<BigUint> =
+        MultiValue2<EsdtTokenPayment<BigUint>, EsdtTokenPayment<BigUint>>;
+    pub type CompoundRewardsResultType<BigUint> = EsdtTokenPayment<BigUint>;
+
+    #[elrond_wasm::proxy]
+    pub trait FarmContract {
+        #[payable("*")]
+        #[endpoint(claimRewards)]
+        fn claim_rewards(&self) -> ClaimRewardsResultType<Self::Api>;
+
+        #[payable("*")]
+        #[endpoint(compoundRewards)]
+        fn compound_rewards(&self) -> CompoundRewardsResultType<Self::Api>;
+
+        #[view(getState)]
+        fn state(&self) -> State;
+
+        #[view(getFarmTokenId)]
+        fn farm_token_id(&self) -> TokenIdentifier;
+
+        #[view(getFarmingTokenId)]
+        fn farming_token_id(&self) -> TokenIdentifier;
+
+        #[view(getRewardTokenId)]
+        fn reward_token_id(&self) -> TokenIdentifier;
+    }
+}
+
+/// The farm calls are synchronous, so a farm call that fails reverts the whole batch.
+/// Only the checks made before calling a farm can skip a farm token.
+#[derive(
+    ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, TypeAbi, Debug,
+)]
+pub enum FarmCallStatus {
+    Processed,
+    SkippedUnknownFarmToken,
+    SkippedFarmNotActive,
+    SkippedCompoundNotSupported,
+}
+
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi)]
+pub struct FarmCallResult<M: ManagedTypeApi> {
+    pub farm_address: ManagedAddress<M>,
+    pub status: FarmCallStatus,
+    pub farm_token: EsdtTokenPayment<M>,
+}
+
+#[derive(TopEncode, TopDecode, TypeAbi)]
+pub struct BatchResult<M: ManagedTypeApi> {
+    pub farm_results: ManagedVec<M, FarmCallResult<M>>,
+    pub rewards: ManagedVec<M, EsdtTokenPayment<M>>,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum BatchAction {
+    Claim,
+    Compound,
+}
+
+#[elrond_wasm::contract]
+pub trait FarmAggregator: token_send::TokenSendModule {
+    #[init]
+    fn init(&self) {}
+
+    #[only_owner]
+    #[endpoint(addFarms)]
+    fn add_farms(&self, farm_addresses: MultiValueEncoded<ManagedAddress>) {
+        for farm_address in farm_addresses {
+            let farm_token_id: TokenIdentifier = self
+                .farm_proxy(farm_address.clone())
+                .farm_token_id()
+                .execute_on_dest_context();
+            require!(
+                farm_token_id.is_valid_esdt_identifier(),
+                "Farm token not issued"
+            );
+
+            self.farm_for_token(&farm_token_id).set(&farm_address);
+            let _ = self.farms().insert(farm_address);
+        }
+    }
+
+    #[only_owner]
+    #[endpoint(removeFarms)]
+    fn remove_farms(&self, farm_addresses: MultiValueEncoded<ManagedAddress>) {
+        for farm_address in farm_addresses {
+            let farm_token_id: TokenIdentifier = self
+                .farm_proxy(farm_address.clone())
+                .farm_token_id()
+                .execute_on_dest_context();
+
+            self.farm_for_token(&farm_token_id).clear();
+            let _ = self.farms().swap_remove(&farm_address);
+        }
+    }
+
+    /// Claims the rewards for all the farm tokens received, each from its own farm.
+    /// Farm tokens skipped by the checks made before calling their farm are sent back
+    /// as they were, with the reason reported in their farm result. If a farm call fails
+    /// anyway, the whole batch is reverted.
+    /// Rewards with the same token ID and nonce are merged into a single payment.
+    #[payable("*")]
+    #[endpoint(claimRewardsMulti)]
+    fn claim_rewards_multi(&self) -> BatchResult<Self::Api> {
+        self.process_batch(BatchAction::Claim)
+    }
+
+    /// Same as claimRewardsMulti, but compounds the rewards instead.
+    /// Only works for farms where the farming token is the same as the reward token.
+    #[payable("*")]
+    #[endpoint(compoundRewardsMulti)]
+    fn compound_rewards_multi(&self) -> BatchResult<Self::Api> {
+        self.process_batch(BatchAction::Compound)
+    }
+
+    fn process_batch(&self, action: BatchAction) -> BatchResult<Self::Api> {
+        let payments = self.call_value().all_esdt_transfers();
+        require!(!payments.is_empty(), "Empty payments");
+
+        let mut farm_results = ManagedVec::new();
+        let mut rewards = ManagedVec::new();
+        let mut output_payments = ManagedVec::new();
+        for payment in payments.iter() {
+            require!(payment.amount > 0u64, "Payment amount cannot be zero");
+
+            let farm_mapper = self.farm_for_token(&payment.token_identifier);
+            if farm_mapper.is_empty() {
+                output_payments.push(payment.clone());
+                farm_results.push(FarmCallResult {
+                    farm_address: ManagedAddress::zero(),
+                    status: FarmCallStatus::SkippedUnknownFarmToken,
+                    farm_token: payment,
+                });
+                continue;
+            }
+
+            let farm_address = farm_mapper.get();
+            let status = self.check_farm_can_process(&farm_address, action);
+            if status != FarmCallStatus::Processed {
+                output_payments.push(payment.clone());
+                farm_results.push(FarmCallResult {
+                    farm_address,
+                    status,
+                    farm_token: payment,
+                });
+                continue;
+            }
+
+            let new_farm_token = match action {
+                BatchAction::Claim => {
+                    let (new_farm_token, reward) = self
+                        .farm_proxy(farm_address.clone())
+                        .claim_rewards()
+                        .add_esdt_token_transfer(
+                            payment.token_identifier.clone(),
+                            payment.token_nonce,
+                            payment.amount.clone(),
+                        )
+                        .execute_on_dest_context::<farm_proxy::ClaimRewardsResultType<Self::Api>>()
+                        .into_tuple();
+                    self.merge_into_payments(&mut rewards, reward);
+
+                    new_farm_token
+                }
+                BatchAction::Compound => self
+                    .farm_proxy(farm_address.clone())
+                    .compound_rewards()
+                    .add_esdt_token_transfer(
+                        payment.token_identifier.clone(),
+                        payment.token_nonce,
+                        payment.amount.clone(),
+                    )
+                    .execute_on_dest_context(),
+            };
+
+            output_payments.push(new_farm_token.clone());
+            farm_results.push(FarmCallResult {
+                farm_address,
+                status: FarmCallStatus::Processed,
+                farm_token: new_farm_token,
+            });
+        }
+
+        output_payments.append_vec(rewards.clone());
+        let caller = self.blockchain().get_caller();
+        self.send_multiple_tokens_if_not_zero(&caller, &output_payments);
+
+        BatchResult {
+            farm_results,
+            rewards,
+        }
+    }
+
+    fn check_farm_can_process(
+        &self,
+        farm_address: &ManagedAddress,
+        action: BatchAction,
+    ) -> FarmCallStatus {
+        let state: State = self
+            .farm_proxy(farm_address.clone())
+            .state()
+            .execute_on_dest_context();
+        if state != State::Active {
+            return FarmCallStatus::SkippedFarmNotActive;
+        }
+
+        if action == BatchAction::Compound {
+            let farming_token_id: TokenIdentifier = self
+                .farm_proxy(farm_address.clone())
+                .farming_token_id()
+                .execute_on_dest_context();
+            let reward_token_id: TokenIdentifier = self
+                .farm_proxy(farm_address.clone())
+                .reward_token_id()
+                .execute_on_dest_context();
+            if farming_token_id != reward_token_id {
+                return FarmCallStatus::SkippedCompoundNotSupported;
+            }
+        }
+
+        FarmCallStatus::Processed
+    }
+
+    fn merge_into_payments(
+        &self,
+        payments: &mut ManagedVec<EsdtTokenPayment<Self::Api>>,
+        new_payment: EsdtTokenPayment<Self::Api>,
+    ) {
+        if new_payment.amount == 0u64 {
+            return;
+        }
+
+        for i in 0..payments.len() {
+            let existing = payments.get(i);
+            if existing.token_identifier == new_payment.token_identifier
+                && existing.token_nonce == new_payment.token_nonce
+            {
+                let merged = EsdtTokenPayment::new(
+                    existing.token_identifier,
+                    existing.token_nonce,
+                    existing.amount + new_payment.amount,
+                );
+                let _ = payments.set(i, &merged);
+                return;
+            }
+        }
+
+        payments.push(new_payment);
+    }
+
+    #[view(getFarms)]
+    fn get_farms(&self) -> MultiValueEncoded<ManagedAddress> {
+        let mut result = MultiValueEncoded::new();
+        for farm_address in self.farms().iter() {
+            result.push(farm_address);
+        }
+        result
+    }
+
+    #[proxy]
+    fn farm_proxy(&self, to: ManagedAddress) -> farm_proxy::Proxy<Self::Api>;
+
+    #[storage_mapper("farms")]
+    fn farms(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[view(getFarmForToken)]
+    #[storage_mapper("farm_for_token")]
+    fn farm_for_token(&self, farm_token_id: &TokenIdentifier) -> SingleValueMapper<ManagedAddress>;
+}
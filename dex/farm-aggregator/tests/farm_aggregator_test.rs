@@ -0,0 +1,393 @@
+use common_structs::FarmTokenAttributes;
+use elrond_wasm::storage::mappers::StorageTokenWrapper;
+use elrond_wasm::types::{Address, EsdtLocalRole, MultiValueEncoded};
+use elrond_wasm_debug::tx_mock::TxInputESDT;
+use elrond_wasm_debug::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint,
+    testing_framework::{BlockchainStateWrapper, ContractObjWrapper},
+    DebugApi,
+};
+
+use config::ConfigModule;
+use farm::Farm;
+use farm_aggregator::*;
+use farm_token::FarmTokenModule;
+use pausable::{PausableModule, State};
+
+const AGGREGATOR_WASM_PATH: &'static str = "farm-aggregator/output/farm-aggregator.wasm";
+const FARM_WASM_PATH: &'static str = "farm/output/farm.wasm";
+
+const MEX_TOKEN_ID: &[u8] = b"MEX-abcdef";
+const FIRST_LP_TOKEN_ID: &[u8] = b"LPA-abcdef";
+const FIRST_FARM_TOKEN_ID: &[u8] = b"FARMA-abcdef";
+const SECOND_LP_TOKEN_ID: &[u8] = b"LPB-abcdef";
+const SECOND_FARM_TOKEN_ID: &[u8] = b"FARMB-abcdef";
+const UNKNOWN_TOKEN_ID: &[u8] = b"UNKNOWN-abcdef";
+const DIVISION_SAFETY_CONSTANT: u64 = 1_000_000_000_000;
+const PER_BLOCK_REWARD_AMOUNT: u64 = 5_000;
+const FARM_IN_AMOUNT: u64 = 1_000_000_000;
+const UNKNOWN_TOKEN_AMOUNT: u64 = 1_000;
+
+type FarmObjBuilder = fn() -> farm::ContractObj<DebugApi>;
+
+struct AggregatorSetup<AggregatorObjBuilder>
+where
+    AggregatorObjBuilder: 'static + Copy + Fn() -> farm_aggregator::ContractObj<DebugApi>,
+{
+    pub b_mock: BlockchainStateWrapper,
+    pub owner_address: Address,
+    pub user_address: Address,
+    pub first_farm_wrapper: ContractObjWrapper<farm::ContractObj<DebugApi>, FarmObjBuilder>,
+    pub second_farm_wrapper: ContractObjWrapper<farm::ContractObj<DebugApi>, FarmObjBuilder>,
+    pub aggregator_wrapper:
+        ContractObjWrapper<farm_aggregator::ContractObj<DebugApi>, AggregatorObjBuilder>,
+}
+
+fn setup_farm(
+    b_mock: &mut BlockchainStateWrapper,
+    owner_address: &Address,
+    farming_token_id: &[u8],
+    farm_token_id: &[u8],
+) -> ContractObjWrapper<farm::ContractObj<DebugApi>, FarmObjBuilder> {
+    let rust_zero = rust_biguint!(0u64);
+    let farm_wrapper = b_mock.create_sc_account(
+        &rust_zero,
+        Some(owner_address),
+        farm::contract_obj::<DebugApi> as FarmObjBuilder,
+        FARM_WASM_PATH,
+    );
+
+    b_mock
+        .execute_tx(owner_address, &farm_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_token_id!(MEX_TOKEN_ID),
+                managed_token_id!(farming_token_id),
+                managed_biguint!(DIVISION_SAFETY_CONSTANT),
+                managed_address!(&Address::zero()),
+            );
+
+            sc.farm_token()
+                .set_token_id(&managed_token_id!(farm_token_id));
+            sc.per_block_reward_amount()
+                .set(&managed_biguint!(PER_BLOCK_REWARD_AMOUNT));
+
+            sc.state().set(&State::Active);
+            sc.produce_rewards_enabled().set(&true);
+        })
+        .assert_ok();
+
+    b_mock.set_esdt_local_roles(
+        farm_wrapper.address_ref(),
+        farm_token_id,
+        &[
+            EsdtLocalRole::NftCreate,
+            EsdtLocalRole::NftAddQuantity,
+            EsdtLocalRole::NftBurn,
+        ][..],
+    );
+    b_mock.set_esdt_local_roles(
+        farm_wrapper.address_ref(),
+        farming_token_id,
+        &[EsdtLocalRole::Burn][..],
+    );
+    b_mock.set_esdt_local_roles(
+        farm_wrapper.address_ref(),
+        MEX_TOKEN_ID,
+        &[EsdtLocalRole::Mint][..],
+    );
+
+    farm_wrapper
+}
+
+fn setup_aggregator<AggregatorObjBuilder>(
+    aggregator_builder: AggregatorObjBuilder,
+) -> AggregatorSetup<AggregatorObjBuilder>
+where
+    AggregatorObjBuilder: 'static + Copy + Fn() -> farm_aggregator::ContractObj<DebugApi>,
+{
+    let rust_zero = rust_biguint!(0u64);
+    let mut b_mock = BlockchainStateWrapper::new();
+    let owner_address = b_mock.create_user_account(&rust_zero);
+    let user_address = b_mock.create_user_account(&rust_zero);
+
+    let first_farm_wrapper = setup_farm(
+        &mut b_mock,
+        &owner_address,
+        FIRST_LP_TOKEN_ID,
+        FIRST_FARM_TOKEN_ID,
+    );
+    let second_farm_wrapper = setup_farm(
+        &mut b_mock,
+        &owner_address,
+        SECOND_LP_TOKEN_ID,
+        SECOND_FARM_TOKEN_ID,
+    );
+
+    let aggregator_wrapper = b_mock.create_sc_account(
+        &rust_zero,
+        Some(&owner_address),
+        aggregator_builder,
+        AGGREGATOR_WASM_PATH,
+    );
+    b_mock
+        .execute_tx(&owner_address, &aggregator_wrapper, &rust_zero, |sc| {
+            sc.init();
+
+            let mut farm_addresses = MultiValueEncoded::new();
+            farm_addresses.push(managed_address!(first_farm_wrapper.address_ref()));
+            farm_addresses.push(managed_address!(second_farm_wrapper.address_ref()));
+            sc.add_farms(farm_addresses);
+        })
+        .assert_ok();
+
+    // the user enters both farms directly, at block 0
+    b_mock.set_esdt_balance(
+        &user_address,
+        FIRST_LP_TOKEN_ID,
+        &rust_biguint!(FARM_IN_AMOUNT),
+    );
+    b_mock.set_esdt_balance(
+        &user_address,
+        SECOND_LP_TOKEN_ID,
+        &rust_biguint!(FARM_IN_AMOUNT),
+    );
+    b_mock.set_esdt_balance(
+        &user_address,
+        UNKNOWN_TOKEN_ID,
+        &rust_biguint!(UNKNOWN_TOKEN_AMOUNT),
+    );
+    for (farm_wrapper, farming_token_id) in [
+        (&first_farm_wrapper, FIRST_LP_TOKEN_ID),
+        (&second_farm_wrapper, SECOND_LP_TOKEN_ID),
+    ]
+    .iter()
+    {
+        b_mock
+            .execute_esdt_transfer(
+                &user_address,
+                *farm_wrapper,
+                *farming_token_id,
+                0,
+                &rust_biguint!(FARM_IN_AMOUNT),
+                |sc| {
+                    sc.enter_farm();
+                },
+            )
+            .assert_ok();
+    }
+
+    AggregatorSetup {
+        b_mock,
+        owner_address,
+        user_address,
+        first_farm_wrapper,
+        second_farm_wrapper,
+        aggregator_wrapper,
+    }
+}
+
+fn farm_token_payments() -> Vec<TxInputESDT> {
+    vec![
+        TxInputESDT {
+            token_identifier: FIRST_FARM_TOKEN_ID.to_vec(),
+            nonce: 1,
+            value: rust_biguint!(FARM_IN_AMOUNT),
+        },
+        TxInputESDT {
+            token_identifier: SECOND_FARM_TOKEN_ID.to_vec(),
+            nonce: 1,
+            value: rust_biguint!(FARM_IN_AMOUNT),
+        },
+    ]
+}
+
+#[test]
+fn test_claim_rewards_multi() {
+    let mut setup = setup_aggregator(farm_aggregator::contract_obj);
+    setup.b_mock.set_block_nonce(10);
+
+    let mut payments = farm_token_payments();
+    payments.push(TxInputESDT {
+        token_identifier: UNKNOWN_TOKEN_ID.to_vec(),
+        nonce: 0,
+        value: rust_biguint!(UNKNOWN_TOKEN_AMOUNT),
+    });
+
+    setup
+        .b_mock
+        .execute_esdt_multi_transfer(
+            &setup.user_address,
+            &setup.aggregator_wrapper,
+            &payments,
+            |sc| {
+                let result = sc.claim_rewards_multi();
+
+                assert_eq!(3, result.farm_results.len());
+                assert_eq!(FarmCallStatus::Processed, result.farm_results.get(0).status);
+                assert_eq!(FarmCallStatus::Processed, result.farm_results.get(1).status);
+                assert_eq!(
+                    FarmCallStatus::SkippedUnknownFarmToken,
+                    result.farm_results.get(2).status
+                );
+
+                // the rewards of both farms are merged into a single payment
+                assert_eq!(1, result.rewards.len());
+                assert_eq!(
+                    managed_biguint!(2 * 10 * PER_BLOCK_REWARD_AMOUNT),
+                    result.rewards.get(0).amount
+                );
+            },
+        )
+        .assert_ok();
+
+    setup.b_mock.check_esdt_balance(
+        &setup.user_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(2 * 10 * PER_BLOCK_REWARD_AMOUNT),
+    );
+    setup.b_mock.check_esdt_balance(
+        &setup.user_address,
+        UNKNOWN_TOKEN_ID,
+        &rust_biguint!(UNKNOWN_TOKEN_AMOUNT),
+    );
+    for farm_token_id in [FIRST_FARM_TOKEN_ID, SECOND_FARM_TOKEN_ID].iter() {
+        setup
+            .b_mock
+            .check_nft_balance::<FarmTokenAttributes<DebugApi>>(
+                &setup.user_address,
+                farm_token_id,
+                2,
+                &rust_biguint!(FARM_IN_AMOUNT),
+                None,
+            );
+    }
+}
+
+#[test]
+fn test_skipped_farms() {
+    let mut setup = setup_aggregator(farm_aggregator::contract_obj);
+    setup.b_mock.set_block_nonce(10);
+
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_address,
+            &setup.second_farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.state().set(&State::Inactive);
+            },
+        )
+        .assert_ok();
+
+    setup
+        .b_mock
+        .execute_esdt_multi_transfer(
+            &setup.user_address,
+            &setup.aggregator_wrapper,
+            &farm_token_payments(),
+            |sc| {
+                let result = sc.claim_rewards_multi();
+
+                assert_eq!(FarmCallStatus::Processed, result.farm_results.get(0).status);
+                assert_eq!(
+                    FarmCallStatus::SkippedFarmNotActive,
+                    result.farm_results.get(1).status
+                );
+            },
+        )
+        .assert_ok();
+
+    // the farm token of the inactive farm is sent back unchanged
+    setup
+        .b_mock
+        .check_nft_balance::<FarmTokenAttributes<DebugApi>>(
+            &setup.user_address,
+            SECOND_FARM_TOKEN_ID,
+            1,
+            &rust_biguint!(FARM_IN_AMOUNT),
+            None,
+        );
+    setup.b_mock.check_esdt_balance(
+        &setup.user_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(10 * PER_BLOCK_REWARD_AMOUNT),
+    );
+
+    // the farming token differs from the reward token, so the rewards cannot be compounded
+    setup
+        .b_mock
+        .execute_esdt_transfer(
+            &setup.user_address,
+            &setup.aggregator_wrapper,
+            FIRST_FARM_TOKEN_ID,
+            2,
+            &rust_biguint!(FARM_IN_AMOUNT),
+            |sc| {
+                let result = sc.compound_rewards_multi();
+
+                assert_eq!(
+                    FarmCallStatus::SkippedCompoundNotSupported,
+                    result.farm_results.get(0).status
+                );
+                assert_eq!(0, result.rewards.len());
+            },
+        )
+        .assert_ok();
+
+    setup
+        .b_mock
+        .check_nft_balance::<FarmTokenAttributes<DebugApi>>(
+            &setup.user_address,
+            FIRST_FARM_TOKEN_ID,
+            2,
+            &rust_biguint!(FARM_IN_AMOUNT),
+            None,
+        );
+}
+
+#[test]
+fn test_failed_farm_call_reverts_batch() {
+    let mut setup = setup_aggregator(farm_aggregator::contract_obj);
+    setup.b_mock.set_block_nonce(10);
+
+    // the second farm no longer accepts the registered farm token, so its claim fails
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_address,
+            &setup.second_farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.farm_token()
+                    .set_token_id(&managed_token_id!(UNKNOWN_TOKEN_ID));
+            },
+        )
+        .assert_ok();
+
+    setup
+        .b_mock
+        .execute_esdt_multi_transfer(
+            &setup.user_address,
+            &setup.aggregator_wrapper,
+            &farm_token_payments(),
+            |sc| {
+                sc.claim_rewards_multi();
+            },
+        )
+        .assert_user_error("Bad payments");
+
+    // nothing was claimed, not even from the first farm
+    setup
+        .b_mock
+        .check_esdt_balance(&setup.user_address, MEX_TOKEN_ID, &rust_biguint!(0));
+    setup
+        .b_mock
+        .check_nft_balance::<FarmTokenAttributes<DebugApi>>(
+            &setup.user_address,
+            FIRST_FARM_TOKEN_ID,
+            1,
+            &rust_biguint!(FARM_IN_AMOUNT),
+            None,
+        );
+}
@@ -0,0 +1,19 @@
+////////////////////////////////////////////////////
+////////////////// AUTO-GENERATED //////////////////
+////////////////////////////////////////////////////
+
+#![no_std]
+
+elrond_wasm_node::wasm_endpoints! {
+    farm_aggregator
+    (
+        addFarms
+        claimRewardsMulti
+        compoundRewardsMulti
+        getFarmForToken
+        getFarms
+        removeFarms
+    )
+}
+
+elrond_wasm_node::wasm_empty_callback! {}
@@ -0,0 +1,56 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use crate::factory::FarmType;
+
+#[derive(TypeAbi, TopEncode)]
+pub struct DeployFarmEvent<M: ManagedTypeApi> {
+    caller: ManagedAddress<M>,
+    farm_type: FarmType,
+    farming_token_id: TokenIdentifier<M>,
+    reward_token_id: TokenIdentifier<M>,
+    farm_address: ManagedAddress<M>,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
+#[elrond_wasm::module]
+pub trait EventsModule {
+    fn emit_deploy_farm_event(
+        &self,
+        caller: ManagedAddress,
+        farm_type: FarmType,
+        farming_token_id: TokenIdentifier,
+        reward_token_id: TokenIdentifier,
+        farm_address: ManagedAddress,
+    ) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.deploy_farm_event(
+            farming_token_id.clone(),
+            farm_type,
+            caller.clone(),
+            epoch,
+            DeployFarmEvent {
+                caller,
+                farm_type,
+                farming_token_id,
+                reward_token_id,
+                farm_address,
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
+    #[event("deploy_farm")]
+    fn deploy_farm_event(
+        &self,
+        #[indexed] farming_token_id: TokenIdentifier,
+        #[indexed] farm_type: FarmType,
+        #[indexed] caller: ManagedAddress,
+        #[indexed] epoch: u64,
+        deploy_farm_event: DeployFarmEvent<Self::Api>,
+    );
+}
@@ -0,0 +1,183 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+/// Gas kept by the factory after a deploy or an upgrade, for the calls that follow it
+/// (the pause whitelist setup, or saving the progress of `upgradeAllFarms`).
+pub const GAS_RESERVED_AFTER_DEPLOY: u64 = 20_000_000;
+
+#[derive(
+    TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Copy, TypeAbi, Debug,
+)]
+pub enum FarmType {
+    Farm,
+    FarmWithLock,
+    FarmStaking,
+}
+
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, TypeAbi)]
+pub struct FarmKey<M: ManagedTypeApi> {
+    pub farming_token_id: TokenIdentifier<M>,
+    pub farm_type: FarmType,
+}
+
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi)]
+pub struct FarmContractMetadata<M: ManagedTypeApi> {
+    pub farm_type: FarmType,
+    pub farming_token_id: TokenIdentifier<M>,
+    pub reward_token_id: TokenIdentifier<M>,
+    pub address: ManagedAddress<M>,
+}
+
+#[elrond_wasm::module]
+pub trait FactoryModule {
+    fn deploy_farm_from_template(
+        &self,
+        farm_type: FarmType,
+        farming_token_id: &TokenIdentifier,
+        reward_token_id: &TokenIdentifier,
+        init_args: ManagedArgBuffer<Self::Api>,
+    ) -> ManagedAddress {
+        require!(
+            !self.farm_template_address(farm_type).is_empty(),
+            "farm contract template is empty"
+        );
+
+        let key = FarmKey {
+            farming_token_id: farming_token_id.clone(),
+            farm_type,
+        };
+        require!(self.farm_map().get(&key).is_none(), "Farm already exists");
+
+        let (new_address, _) = Self::Api::send_api_impl().deploy_from_source_contract(
+            self.get_gas_for_deploy(),
+            &BigUint::zero(),
+            &self.farm_template_address(farm_type).get(),
+            CodeMetadata::UPGRADEABLE | CodeMetadata::READABLE | CodeMetadata::PAYABLE_BY_SC,
+            &init_args,
+        );
+
+        self.farm_map().insert(key, new_address.clone());
+        let _ = self.farm_addresses().insert(new_address.clone());
+        self.farm_metadata(&new_address).set(&FarmContractMetadata {
+            farm_type,
+            farming_token_id: farming_token_id.clone(),
+            reward_token_id: reward_token_id.clone(),
+            address: new_address.clone(),
+        });
+        self.farm_init_args(&new_address)
+            .set(&init_args.into_vec_of_buffers());
+
+        new_address
+    }
+
+    fn upgrade_farm_from_template(&self, farm_address: &ManagedAddress) {
+        let metadata = self.farm_metadata(farm_address).get();
+        let template_mapper = self.farm_template_address(metadata.farm_type);
+        require!(
+            !template_mapper.is_empty(),
+            "farm contract template is empty"
+        );
+
+        let init_args = ManagedArgBuffer::from(self.farm_init_args(farm_address).get());
+        Self::Api::send_api_impl().upgrade_from_source_contract(
+            farm_address,
+            self.get_gas_for_deploy(),
+            &BigUint::zero(),
+            &template_mapper.get(),
+            CodeMetadata::UPGRADEABLE | CodeMetadata::READABLE | CodeMetadata::PAYABLE_BY_SC,
+            &init_args,
+        );
+    }
+
+    fn get_gas_for_deploy(&self) -> u64 {
+        let gas_left = self.blockchain().get_gas_left();
+        require!(gas_left > GAS_RESERVED_AFTER_DEPLOY, "Not enough gas");
+
+        gas_left - GAS_RESERVED_AFTER_DEPLOY
+    }
+
+    #[view(getAllFarmsManagedAddresses)]
+    fn get_all_farms_addresses(&self) -> MultiValueEncoded<ManagedAddress> {
+        let mut result = MultiValueEncoded::new();
+        for farm in self.farm_addresses().iter() {
+            result.push(farm);
+        }
+        result
+    }
+
+    #[view(getAllFarmContractMetadata)]
+    fn get_all_farm_contract_metadata(&self) -> MultiValueEncoded<FarmContractMetadata<Self::Api>> {
+        let mut result = MultiValueEncoded::new();
+        for farm in self.farm_addresses().iter() {
+            result.push(self.farm_metadata(&farm).get());
+        }
+        result
+    }
+
+    #[view(getFarmsForFarmingToken)]
+    fn get_farms_for_farming_token(
+        &self,
+        farming_token_id: TokenIdentifier,
+    ) -> MultiValueEncoded<FarmContractMetadata<Self::Api>> {
+        let mut result = MultiValueEncoded::new();
+        for farm_type in [
+            FarmType::Farm,
+            FarmType::FarmWithLock,
+            FarmType::FarmStaking,
+        ] {
+            let key = FarmKey {
+                farming_token_id: farming_token_id.clone(),
+                farm_type,
+            };
+            if let Some(address) = self.farm_map().get(&key) {
+                result.push(self.farm_metadata(&address).get());
+            }
+        }
+        result
+    }
+
+    #[view(getFarm)]
+    fn get_farm(&self, farming_token_id: TokenIdentifier, farm_type: FarmType) -> ManagedAddress {
+        self.farm_map()
+            .get(&FarmKey {
+                farming_token_id,
+                farm_type,
+            })
+            .unwrap_or_else(ManagedAddress::zero)
+    }
+
+    fn require_is_farm_sc(&self, farm_address: &ManagedAddress) {
+        require!(
+            self.farm_addresses().contains(farm_address),
+            "Not a farm SC"
+        );
+    }
+
+    #[only_owner]
+    #[endpoint(setFarmTemplateAddress)]
+    fn set_farm_template_address(&self, farm_type: FarmType, address: ManagedAddress) {
+        self.farm_template_address(farm_type).set(&address);
+    }
+
+    #[storage_mapper("farm_map")]
+    fn farm_map(&self) -> MapMapper<FarmKey<Self::Api>, ManagedAddress>;
+
+    #[storage_mapper("farm_addresses")]
+    fn farm_addresses(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[storage_mapper("farm_metadata")]
+    fn farm_metadata(
+        &self,
+        farm_address: &ManagedAddress,
+    ) -> SingleValueMapper<FarmContractMetadata<Self::Api>>;
+
+    #[storage_mapper("farm_init_args")]
+    fn farm_init_args(
+        &self,
+        farm_address: &ManagedAddress,
+    ) -> SingleValueMapper<ManagedVec<ManagedBuffer>>;
+
+    #[view(getFarmTemplateAddress)]
+    #[storage_mapper("farm_template_address")]
+    fn farm_template_address(&self, farm_type: FarmType) -> SingleValueMapper<ManagedAddress>;
+}
@@ -0,0 +1,308 @@
+#![no_std]
+
+elrond_wasm::imports!();
+
+pub mod events;
+pub mod factory;
+pub mod ongoing_upgrade_operation;
+
+use factory::FarmType;
+use ongoing_upgrade_operation::{OngoingOperation, CONTINUE_OP, STOP_OP};
+
+mod farm_proxy {
+    elrond_wasm::imports!();
+
+    #[elrond_wasm::proxy]
+    pub trait FarmContract {
+        #[payable("EGLD")]
+        #[endpoint(registerFarmToken)]
+        fn register_farm_token(
+            &self,
+            token_display_name: ManagedBuffer,
+            token_ticker: ManagedBuffer,
+            num_decimals: usize,
+        );
+
+        #[endpoint(setPerBlockRewardAmount)]
+        fn set_per_block_rewards(&self, per_block_amount: BigUint);
+
+        #[endpoint(startProduceRewards)]
+        fn start_produce_rewards(&self);
+
+        #[endpoint(addToPauseWhitelist)]
+        fn add_to_pause_whitelist(&self, addr_list: MultiValueEncoded<ManagedAddress>);
+
+        #[endpoint]
+        fn pause(&self);
+
+        #[endpoint]
+        fn resume(&self);
+    }
+}
+
+#[elrond_wasm::contract]
+pub trait FarmFactory:
+    factory::FactoryModule
+    + events::EventsModule
+    + ongoing_upgrade_operation::OngoingUpgradeOperationModule
+{
+    #[init]
+    fn init(&self) {}
+
+    #[only_owner]
+    #[endpoint(deployFarm)]
+    fn deploy_farm(
+        &self,
+        reward_token_id: TokenIdentifier,
+        farming_token_id: TokenIdentifier,
+        division_safety_constant: BigUint,
+        pair_contract_address: ManagedAddress,
+    ) -> ManagedAddress {
+        let mut arg_buffer = ManagedArgBuffer::new();
+        arg_buffer.push_arg(&reward_token_id);
+        arg_buffer.push_arg(&farming_token_id);
+        arg_buffer.push_arg(&division_safety_constant);
+        arg_buffer.push_arg(&pair_contract_address);
+
+        self.deploy_and_wire_farm(
+            FarmType::Farm,
+            farming_token_id,
+            reward_token_id,
+            arg_buffer,
+        )
+    }
+
+    #[only_owner]
+    #[endpoint(deployFarmWithLock)]
+    fn deploy_farm_with_lock(
+        &self,
+        reward_token_id: TokenIdentifier,
+        farming_token_id: TokenIdentifier,
+        locked_asset_factory_address: ManagedAddress,
+        division_safety_constant: BigUint,
+        pair_contract_address: ManagedAddress,
+    ) -> ManagedAddress {
+        let mut arg_buffer = ManagedArgBuffer::new();
+        arg_buffer.push_arg(&reward_token_id);
+        arg_buffer.push_arg(&farming_token_id);
+        arg_buffer.push_arg(&locked_asset_factory_address);
+        arg_buffer.push_arg(&division_safety_constant);
+        arg_buffer.push_arg(&pair_contract_address);
+
+        self.deploy_and_wire_farm(
+            FarmType::FarmWithLock,
+            farming_token_id,
+            reward_token_id,
+            arg_buffer,
+        )
+    }
+
+    #[only_owner]
+    #[endpoint(deployFarmStaking)]
+    fn deploy_farm_staking(
+        &self,
+        farming_token_id: TokenIdentifier,
+        division_safety_constant: BigUint,
+        max_apr: BigUint,
+        min_unbond_epochs: u64,
+    ) -> ManagedAddress {
+        let mut arg_buffer = ManagedArgBuffer::new();
+        arg_buffer.push_arg(&farming_token_id);
+        arg_buffer.push_arg(&division_safety_constant);
+        arg_buffer.push_arg(&max_apr);
+        arg_buffer.push_arg(min_unbond_epochs);
+
+        // farming and reward token are the same
+        self.deploy_and_wire_farm(
+            FarmType::FarmStaking,
+            farming_token_id.clone(),
+            farming_token_id,
+            arg_buffer,
+        )
+    }
+
+    fn deploy_and_wire_farm(
+        &self,
+        farm_type: FarmType,
+        farming_token_id: TokenIdentifier,
+        reward_token_id: TokenIdentifier,
+        init_args: ManagedArgBuffer<Self::Api>,
+    ) -> ManagedAddress {
+        require!(
+            farming_token_id.is_valid_esdt_identifier(),
+            "Farming token ID is not a valid esdt token ID"
+        );
+        require!(
+            reward_token_id.is_valid_esdt_identifier(),
+            "Reward token ID is not a valid esdt token ID"
+        );
+
+        let farm_address = self.deploy_farm_from_template(
+            farm_type,
+            &farming_token_id,
+            &reward_token_id,
+            init_args,
+        );
+
+        let default_whitelist = self.default_pause_whitelist();
+        if !default_whitelist.is_empty() {
+            let mut addr_list = MultiValueEncoded::new();
+            for addr in default_whitelist.iter() {
+                addr_list.push(addr);
+            }
+
+            self.farm_proxy(farm_address.clone())
+                .add_to_pause_whitelist(addr_list)
+                .execute_on_dest_context_ignore_result();
+        }
+
+        self.emit_deploy_farm_event(
+            self.blockchain().get_caller(),
+            farm_type,
+            farming_token_id,
+            reward_token_id,
+            farm_address.clone(),
+        );
+
+        farm_address
+    }
+
+    #[only_owner]
+    #[payable("EGLD")]
+    #[endpoint(registerFarmToken)]
+    fn register_farm_token(
+        &self,
+        farm_address: ManagedAddress,
+        token_display_name: ManagedBuffer,
+        token_ticker: ManagedBuffer,
+        num_decimals: usize,
+    ) {
+        self.require_is_farm_sc(&farm_address);
+
+        let issue_cost = self.call_value().egld_value();
+        self.farm_proxy(farm_address)
+            .register_farm_token(token_display_name, token_ticker, num_decimals)
+            .with_egld_transfer(issue_cost)
+            .async_call()
+            .call_and_exit()
+    }
+
+    #[only_owner]
+    #[endpoint(setPerBlockRewardAmount)]
+    fn set_per_block_reward_amount(&self, farm_address: ManagedAddress, per_block_amount: BigUint) {
+        self.require_is_farm_sc(&farm_address);
+
+        self.farm_proxy(farm_address)
+            .set_per_block_rewards(per_block_amount)
+            .execute_on_dest_context_ignore_result();
+    }
+
+    #[only_owner]
+    #[endpoint(startProduceRewards)]
+    fn start_produce_rewards(&self, farm_address: ManagedAddress) {
+        self.require_is_farm_sc(&farm_address);
+
+        self.farm_proxy(farm_address)
+            .start_produce_rewards()
+            .execute_on_dest_context_ignore_result();
+    }
+
+    #[only_owner]
+    #[endpoint]
+    fn pause(&self, farm_address: ManagedAddress) {
+        self.require_is_farm_sc(&farm_address);
+
+        self.farm_proxy(farm_address)
+            .pause()
+            .execute_on_dest_context_ignore_result();
+    }
+
+    #[only_owner]
+    #[endpoint]
+    fn resume(&self, farm_address: ManagedAddress) {
+        self.require_is_farm_sc(&farm_address);
+
+        self.farm_proxy(farm_address)
+            .resume()
+            .execute_on_dest_context_ignore_result();
+    }
+
+    /// Addresses added here (e.g. the pause-all contract) are added
+    /// to the pause whitelist of every farm deployed afterwards.
+    #[only_owner]
+    #[endpoint(addDefaultPauseWhitelist)]
+    fn add_default_pause_whitelist(&self, addr_list: MultiValueEncoded<ManagedAddress>) {
+        let mut whitelist = self.default_pause_whitelist();
+        for addr in addr_list {
+            let _ = whitelist.insert(addr);
+        }
+    }
+
+    #[only_owner]
+    #[endpoint(removeDefaultPauseWhitelist)]
+    fn remove_default_pause_whitelist(&self, addr_list: MultiValueEncoded<ManagedAddress>) {
+        let mut whitelist = self.default_pause_whitelist();
+        for addr in addr_list {
+            let _ = whitelist.swap_remove(&addr);
+        }
+    }
+
+    #[only_owner]
+    #[endpoint(addToFarmPauseWhitelist)]
+    fn add_to_farm_pause_whitelist(
+        &self,
+        farm_address: ManagedAddress,
+        addr_list: MultiValueEncoded<ManagedAddress>,
+    ) {
+        self.require_is_farm_sc(&farm_address);
+
+        self.farm_proxy(farm_address)
+            .add_to_pause_whitelist(addr_list)
+            .execute_on_dest_context_ignore_result();
+    }
+
+    #[only_owner]
+    #[endpoint(upgradeFarm)]
+    fn upgrade_farm(&self, farm_address: ManagedAddress) {
+        self.require_is_farm_sc(&farm_address);
+        self.upgrade_farm_from_template(&farm_address);
+    }
+
+    /// Will attempt to upgrade all the registered farms from their templates.
+    /// Returns "completed" if all were upgraded.
+    /// Otherwise, it will save progress and return "interrupted",
+    /// and will require more calls to complete
+    #[only_owner]
+    #[endpoint(upgradeAllFarms)]
+    fn upgrade_all_farms(&self) -> OperationCompletionStatus {
+        let mut current_index = self.load_upgrade_all_operation();
+        let farms = self.farm_addresses();
+        let farms_len = farms.len();
+
+        let run_result = self.run_while_it_has_gas(|| {
+            if current_index > farms_len {
+                return STOP_OP;
+            }
+
+            let farm_address = farms.get_by_index(current_index);
+            self.upgrade_farm_from_template(&farm_address);
+            current_index += 1;
+
+            CONTINUE_OP
+        });
+        if run_result == OperationCompletionStatus::InterruptedBeforeOutOfGas {
+            self.save_progress(&OngoingOperation::UpgradeAll {
+                farm_index: current_index,
+            });
+        }
+
+        run_result
+    }
+
+    #[proxy]
+    fn farm_proxy(&self, to: ManagedAddress) -> farm_proxy::Proxy<Self::Api>;
+
+    #[view(getDefaultPauseWhitelist)]
+    #[storage_mapper("default_pause_whitelist")]
+    fn default_pause_whitelist(&self) -> UnorderedSetMapper<ManagedAddress>;
+}
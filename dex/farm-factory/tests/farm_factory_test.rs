@@ -0,0 +1,408 @@
+use elrond_wasm::types::{Address, ManagedAddress, MultiValueEncoded, OperationCompletionStatus};
+use elrond_wasm_debug::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint,
+    testing_framework::{BlockchainStateWrapper, ContractObjWrapper},
+    DebugApi,
+};
+
+use farm_factory::factory::{FactoryModule, FarmType};
+use farm_factory::ongoing_upgrade_operation::{OngoingOperation, OngoingUpgradeOperationModule};
+use farm_factory::*;
+use pausable::{PausableModule, State};
+
+const FACTORY_WASM_PATH: &'static str = "farm-factory/output/farm-factory.wasm";
+const FARM_WASM_PATH: &'static str = "farm/output/farm.wasm";
+const FARM_WITH_LOCK_WASM_PATH: &'static str = "farm_with_lock/output/farm_with_lock.wasm";
+const FARM_STAKING_WASM_PATH: &'static str = "farm-staking/output/farm-staking.wasm";
+
+const MEX_TOKEN_ID: &[u8] = b"MEX-abcdef";
+const LKMEX_TOKEN_ID: &[u8] = b"LKMEX-abcdef";
+const LP_TOKEN_ID: &[u8] = b"LPTOK-abcdef";
+const OTHER_LP_TOKEN_ID: &[u8] = b"LPTOKB-abcdef";
+const DIVISION_SAFETY_CONSTANT: u64 = 1_000_000_000_000;
+const MAX_APR: u64 = 2_500;
+const MIN_UNBOND_EPOCHS: u64 = 5;
+
+struct FarmFactorySetup<FactoryObjBuilder>
+where
+    FactoryObjBuilder: 'static + Copy + Fn() -> farm_factory::ContractObj<DebugApi>,
+{
+    pub b_mock: BlockchainStateWrapper,
+    pub owner_address: Address,
+    pub pause_all_address: Address,
+    pub factory_wrapper: ContractObjWrapper<farm_factory::ContractObj<DebugApi>, FactoryObjBuilder>,
+}
+
+fn setup_factory<FactoryObjBuilder>(
+    factory_builder: FactoryObjBuilder,
+) -> FarmFactorySetup<FactoryObjBuilder>
+where
+    FactoryObjBuilder: 'static + Copy + Fn() -> farm_factory::ContractObj<DebugApi>,
+{
+    let rust_zero = rust_biguint!(0u64);
+    let mut b_mock = BlockchainStateWrapper::new();
+    let owner_address = b_mock.create_user_account(&rust_zero);
+    let pause_all_address = b_mock.create_user_account(&rust_zero);
+
+    let factory_wrapper = b_mock.create_sc_account(
+        &rust_zero,
+        Some(&owner_address),
+        factory_builder,
+        FACTORY_WASM_PATH,
+    );
+    let farm_template = b_mock.create_sc_account(
+        &rust_zero,
+        Some(&owner_address),
+        farm::contract_obj::<DebugApi>,
+        FARM_WASM_PATH,
+    );
+    let farm_with_lock_template = b_mock.create_sc_account(
+        &rust_zero,
+        Some(&owner_address),
+        farm_with_lock::contract_obj::<DebugApi>,
+        FARM_WITH_LOCK_WASM_PATH,
+    );
+    let farm_staking_template = b_mock.create_sc_account(
+        &rust_zero,
+        Some(&owner_address),
+        farm_staking::contract_obj::<DebugApi>,
+        FARM_STAKING_WASM_PATH,
+    );
+
+    b_mock
+        .execute_tx(&owner_address, &factory_wrapper, &rust_zero, |sc| {
+            sc.init();
+
+            sc.set_farm_template_address(
+                FarmType::Farm,
+                managed_address!(farm_template.address_ref()),
+            );
+            sc.set_farm_template_address(
+                FarmType::FarmWithLock,
+                managed_address!(farm_with_lock_template.address_ref()),
+            );
+            sc.set_farm_template_address(
+                FarmType::FarmStaking,
+                managed_address!(farm_staking_template.address_ref()),
+            );
+        })
+        .assert_ok();
+
+    FarmFactorySetup {
+        b_mock,
+        owner_address,
+        pause_all_address,
+        factory_wrapper,
+    }
+}
+
+fn deploy_farm<FactoryObjBuilder>(
+    setup: &mut FarmFactorySetup<FactoryObjBuilder>,
+    farming_token_id: &[u8],
+) -> ContractObjWrapper<farm::ContractObj<DebugApi>, fn() -> farm::ContractObj<DebugApi>>
+where
+    FactoryObjBuilder: 'static + Copy + Fn() -> farm_factory::ContractObj<DebugApi>,
+{
+    let farm_wrapper = setup.b_mock.prepare_deploy_from_sc(
+        setup.factory_wrapper.address_ref(),
+        farm::contract_obj::<DebugApi> as fn() -> farm::ContractObj<DebugApi>,
+    );
+
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_address,
+            &setup.factory_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let farm_address = sc.deploy_farm(
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_token_id!(farming_token_id),
+                    managed_biguint!(DIVISION_SAFETY_CONSTANT),
+                    ManagedAddress::zero(),
+                );
+                assert_eq!(managed_address!(farm_wrapper.address_ref()), farm_address);
+            },
+        )
+        .assert_ok();
+
+    farm_wrapper
+}
+
+#[test]
+fn test_deploy_farms() {
+    let mut setup = setup_factory(farm_factory::contract_obj);
+    let farm_wrapper = deploy_farm(&mut setup, LP_TOKEN_ID);
+
+    // a farming token has at most one farm of each type
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_address,
+            &setup.factory_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.deploy_farm(
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_token_id!(LP_TOKEN_ID),
+                    managed_biguint!(DIVISION_SAFETY_CONSTANT),
+                    ManagedAddress::zero(),
+                );
+            },
+        )
+        .assert_user_error("Farm already exists");
+
+    let farm_with_lock_wrapper = setup.b_mock.prepare_deploy_from_sc(
+        setup.factory_wrapper.address_ref(),
+        farm_with_lock::contract_obj::<DebugApi>,
+    );
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_address,
+            &setup.factory_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let farm_address = sc.deploy_farm_with_lock(
+                    managed_token_id!(LKMEX_TOKEN_ID),
+                    managed_token_id!(LP_TOKEN_ID),
+                    ManagedAddress::zero(),
+                    managed_biguint!(DIVISION_SAFETY_CONSTANT),
+                    ManagedAddress::zero(),
+                );
+                assert_eq!(
+                    managed_address!(farm_with_lock_wrapper.address_ref()),
+                    farm_address
+                );
+            },
+        )
+        .assert_ok();
+
+    let farm_staking_wrapper = setup.b_mock.prepare_deploy_from_sc(
+        setup.factory_wrapper.address_ref(),
+        farm_staking::contract_obj::<DebugApi>,
+    );
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_address,
+            &setup.factory_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let farm_address = sc.deploy_farm_staking(
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_biguint!(DIVISION_SAFETY_CONSTANT),
+                    managed_biguint!(MAX_APR),
+                    MIN_UNBOND_EPOCHS,
+                );
+                assert_eq!(
+                    managed_address!(farm_staking_wrapper.address_ref()),
+                    farm_address
+                );
+            },
+        )
+        .assert_ok();
+
+    setup
+        .b_mock
+        .execute_query(&setup.factory_wrapper, |sc| {
+            assert_eq!(
+                managed_address!(farm_wrapper.address_ref()),
+                sc.get_farm(managed_token_id!(LP_TOKEN_ID), FarmType::Farm)
+            );
+            assert_eq!(
+                managed_address!(farm_with_lock_wrapper.address_ref()),
+                sc.get_farm(managed_token_id!(LP_TOKEN_ID), FarmType::FarmWithLock)
+            );
+            assert_eq!(
+                managed_address!(farm_staking_wrapper.address_ref()),
+                sc.get_farm(managed_token_id!(MEX_TOKEN_ID), FarmType::FarmStaking)
+            );
+            assert_eq!(
+                2,
+                sc.get_farms_for_farming_token(managed_token_id!(LP_TOKEN_ID))
+                    .into_iter()
+                    .count()
+            );
+        })
+        .assert_ok();
+
+    // the factory owns the deployed farms
+    let factory_address = setup.factory_wrapper.address_ref().clone();
+    setup
+        .b_mock
+        .execute_query(&farm_wrapper, |sc| {
+            assert!(sc
+                .pause_whitelist()
+                .contains(&managed_address!(&factory_address)));
+            assert_eq!(State::Inactive, sc.state().get());
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_default_pause_whitelist() {
+    let mut setup = setup_factory(farm_factory::contract_obj);
+    let pause_all_address = setup.pause_all_address.clone();
+
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_address,
+            &setup.factory_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut addr_list = MultiValueEncoded::new();
+                addr_list.push(managed_address!(&pause_all_address));
+                sc.add_default_pause_whitelist(addr_list);
+            },
+        )
+        .assert_ok();
+
+    let whitelisted_farm = deploy_farm(&mut setup, LP_TOKEN_ID);
+    setup
+        .b_mock
+        .execute_query(&whitelisted_farm, |sc| {
+            assert!(sc
+                .pause_whitelist()
+                .contains(&managed_address!(&pause_all_address)));
+        })
+        .assert_ok();
+
+    // the pause-all address can pause the farm directly
+    setup
+        .b_mock
+        .execute_tx(
+            &pause_all_address,
+            &whitelisted_farm,
+            &rust_biguint!(0),
+            |sc| {
+                sc.resume();
+            },
+        )
+        .assert_ok();
+
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_address,
+            &setup.factory_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut addr_list = MultiValueEncoded::new();
+                addr_list.push(managed_address!(&pause_all_address));
+                sc.remove_default_pause_whitelist(addr_list);
+            },
+        )
+        .assert_ok();
+
+    let other_farm = deploy_farm(&mut setup, OTHER_LP_TOKEN_ID);
+    setup
+        .b_mock
+        .execute_query(&other_farm, |sc| {
+            assert!(!sc
+                .pause_whitelist()
+                .contains(&managed_address!(&pause_all_address)));
+        })
+        .assert_ok();
+
+    // farms deployed earlier can still be whitelisted one by one
+    let other_farm_address = other_farm.address_ref().clone();
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_address,
+            &setup.factory_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut addr_list = MultiValueEncoded::new();
+                addr_list.push(managed_address!(&pause_all_address));
+                sc.add_to_farm_pause_whitelist(managed_address!(&other_farm_address), addr_list);
+            },
+        )
+        .assert_ok();
+
+    setup
+        .b_mock
+        .execute_query(&other_farm, |sc| {
+            assert!(sc
+                .pause_whitelist()
+                .contains(&managed_address!(&pause_all_address)));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_resume_upgrade_all_farms() {
+    let mut setup = setup_factory(farm_factory::contract_obj);
+    let first_farm = deploy_farm(&mut setup, LP_TOKEN_ID);
+    let second_farm = deploy_farm(&mut setup, OTHER_LP_TOKEN_ID);
+
+    // upgrading runs the farm's init again, which sets it back to inactive
+    let first_farm_address = first_farm.address_ref().clone();
+    let second_farm_address = second_farm.address_ref().clone();
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_address,
+            &setup.factory_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.resume(managed_address!(&first_farm_address));
+                sc.resume(managed_address!(&second_farm_address));
+
+                // a previous call was interrupted after upgrading the first farm
+                sc.current_ongoing_operation()
+                    .set(&OngoingOperation::UpgradeAll { farm_index: 2 });
+            },
+        )
+        .assert_ok();
+
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_address,
+            &setup.factory_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let result = sc.upgrade_all_farms();
+                assert!(result == OperationCompletionStatus::Completed);
+                assert!(sc.current_ongoing_operation().is_empty());
+            },
+        )
+        .assert_ok();
+
+    setup
+        .b_mock
+        .execute_query(&first_farm, |sc| {
+            assert_eq!(State::Active, sc.state().get());
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_query(&second_farm, |sc| {
+            assert_eq!(State::Inactive, sc.state().get());
+        })
+        .assert_ok();
+
+    // a new call starts over from the first farm
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_address,
+            &setup.factory_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let result = sc.upgrade_all_farms();
+                assert!(result == OperationCompletionStatus::Completed);
+            },
+        )
+        .assert_ok();
+
+    setup
+        .b_mock
+        .execute_query(&first_farm, |sc| {
+            assert_eq!(State::Inactive, sc.state().get());
+        })
+        .assert_ok();
+}
@@ -0,0 +1,33 @@
+////////////////////////////////////////////////////
+////////////////// AUTO-GENERATED //////////////////
+////////////////////////////////////////////////////
+
+#![no_std]
+
+elrond_wasm_node::wasm_endpoints! {
+    farm_factory
+    (
+        addDefaultPauseWhitelist
+        addToFarmPauseWhitelist
+        deployFarm
+        deployFarmStaking
+        deployFarmWithLock
+        getAllFarmContractMetadata
+        getAllFarmsManagedAddresses
+        getDefaultPauseWhitelist
+        getFarm
+        getFarmTemplateAddress
+        getFarmsForFarmingToken
+        pause
+        registerFarmToken
+        removeDefaultPauseWhitelist
+        resume
+        setFarmTemplateAddress
+        setPerBlockRewardAmount
+        startProduceRewards
+        upgradeAllFarms
+        upgradeFarm
+    )
+}
+
+elrond_wasm_node::wasm_empty_callback! {}
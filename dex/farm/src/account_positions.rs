@@ -0,0 +1,199 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use common_errors::*;
+
+use common_structs::FarmTokenAttributes;
+use contexts::generic::StorageCache;
+use farm_token::FarmToken;
+use pausable::State;
+
+use crate::custom_rewards;
+use crate::farm_token_merge;
+
+#[elrond_wasm::module]
+pub trait AccountPositionsModule:
+    custom_rewards::CustomRewardsModule
+    + farm_token_merge::FarmTokenMergeModule
+    + rewards::RewardsModule
+    + config::ConfigModule
+    + token_send::TokenSendModule
+    + token_merge::TokenMergeModule
+    + farm_token::FarmTokenModule
+    + pausable::PausableModule
+    + elrond_wasm_modules::default_issue_callbacks::DefaultIssueCallbacksModule
+{
+    #[only_owner]
+    #[endpoint(setAccountModeEnabled)]
+    fn set_account_mode_enabled(&self, enabled: bool) {
+        self.account_mode_enabled().set(enabled);
+    }
+
+    /// Moves the received farm tokens into the caller's account position,
+    /// merging them with the existing account position, if any.
+    /// The farm token supply is not changed, as the position stays in the farm.
+    #[payable("*")]
+    #[endpoint(convertToAccountPosition)]
+    fn convert_to_account_position(&self) -> BigUint {
+        require!(
+            self.account_mode_enabled().get(),
+            ERROR_ACCOUNT_MODE_DISABLED
+        );
+
+        let caller = self.blockchain().get_caller();
+        let payments = self.call_value().all_esdt_transfers();
+        require!(!payments.is_empty(), ERROR_EMPTY_PAYMENTS);
+
+        let position_mapper = self.account_position(&caller);
+        let existing_position = if position_mapper.is_empty() {
+            None
+        } else {
+            let attributes = position_mapper.get();
+            Some(FarmToken {
+                payment: EsdtTokenPayment::new(
+                    self.farm_token().get_token_id(),
+                    0,
+                    attributes.current_farm_amount.clone(),
+                ),
+                attributes,
+            })
+        };
+
+        let mut total_amount = match &existing_position {
+            Some(position) => position.payment.amount.clone(),
+            None => BigUint::zero(),
+        };
+        for payment in payments.iter() {
+            total_amount += &payment.amount;
+        }
+
+        let merged_attributes =
+            self.get_merged_farm_token_attributes(&payments, existing_position.as_ref());
+        for payment in payments.iter() {
+            self.send().esdt_local_burn(
+                &payment.token_identifier,
+                payment.token_nonce,
+                &payment.amount,
+            );
+        }
+
+        // a single partially sent token keeps the attributes of its whole nonce
+        let new_position = FarmTokenAttributes {
+            reward_per_share: merged_attributes.reward_per_share.clone(),
+            original_entering_epoch: merged_attributes.original_entering_epoch,
+            entering_epoch: merged_attributes.entering_epoch,
+            initial_farming_amount: self.rule_of_three_non_zero_result(
+                &total_amount,
+                &merged_attributes.current_farm_amount,
+                &merged_attributes.initial_farming_amount,
+            ),
+            compounded_reward: self.rule_of_three(
+                &total_amount,
+                &merged_attributes.current_farm_amount,
+                &merged_attributes.compounded_reward,
+            ),
+            current_farm_amount: total_amount.clone(),
+        };
+        position_mapper.set(&new_position);
+
+        total_amount
+    }
+
+    /// Takes the given amount out of the caller's account position
+    /// and sends it back as a farm token.
+    #[endpoint(convertToFarmToken)]
+    fn convert_to_farm_token(&self, amount: BigUint) -> EsdtTokenPayment<Self::Api> {
+        let caller = self.blockchain().get_caller();
+        let position_mapper = self.account_position(&caller);
+        require!(!position_mapper.is_empty(), ERROR_NO_ACCOUNT_POSITION);
+
+        let mut position = position_mapper.get();
+        require!(amount != 0u64, ERROR_ZERO_AMOUNT);
+        require!(
+            amount <= position.current_farm_amount,
+            ERROR_BAD_INPUT_TOKEN
+        );
+
+        let new_attributes = FarmTokenAttributes {
+            reward_per_share: position.reward_per_share.clone(),
+            original_entering_epoch: position.original_entering_epoch,
+            entering_epoch: position.entering_epoch,
+            initial_farming_amount: self.rule_of_three_non_zero_result(
+                &amount,
+                &position.current_farm_amount,
+                &position.initial_farming_amount,
+            ),
+            compounded_reward: self.rule_of_three(
+                &amount,
+                &position.current_farm_amount,
+                &position.compounded_reward,
+            ),
+            current_farm_amount: amount.clone(),
+        };
+
+        if amount == position.current_farm_amount {
+            position_mapper.clear();
+        } else {
+            position.initial_farming_amount -= &new_attributes.initial_farming_amount;
+            position.compounded_reward -= &new_attributes.compounded_reward;
+            position.current_farm_amount -= &amount;
+            position_mapper.set(&position);
+        }
+
+        let farm_token_id = self.farm_token().get_token_id();
+        let new_nonce =
+            self.send()
+                .esdt_nft_create_compact(&farm_token_id, &amount, &new_attributes);
+        self.send()
+            .direct_esdt(&caller, &farm_token_id, new_nonce, &amount);
+
+        EsdtTokenPayment::new(farm_token_id, new_nonce, amount)
+    }
+
+    /// Claims the rewards of the caller's account position.
+    /// Unlike claimRewards, no farm token is burnt or created.
+    #[endpoint(claimRewardsAccount)]
+    fn claim_rewards_account(&self) -> EsdtTokenPayment<Self::Api> {
+        let mut storage_cache = StorageCache::new(self);
+        require!(
+            storage_cache.contract_state == State::Active,
+            ERROR_NOT_ACTIVE
+        );
+
+        let caller = self.blockchain().get_caller();
+        let position_mapper = self.account_position(&caller);
+        require!(!position_mapper.is_empty(), ERROR_NO_ACCOUNT_POSITION);
+
+        self.generate_aggregated_rewards(&mut storage_cache);
+
+        let mut position = position_mapper.get();
+        let reward = if storage_cache.reward_per_share > position.reward_per_share {
+            let rps_diff = &storage_cache.reward_per_share - &position.reward_per_share;
+            &position.current_farm_amount * &rps_diff / &storage_cache.division_safety_constant
+        } else {
+            BigUint::zero()
+        };
+        storage_cache.reward_reserve -= &reward;
+
+        self.reward_per_share().set(&storage_cache.reward_per_share);
+        self.reward_reserve().set(&storage_cache.reward_reserve);
+
+        position.reward_per_share = storage_cache.reward_per_share.clone();
+        position_mapper.set(&position);
+
+        self.send_tokens_non_zero(&caller, &storage_cache.reward_token_id, 0, &reward);
+
+        EsdtTokenPayment::new(storage_cache.reward_token_id, 0, reward)
+    }
+
+    #[view(isAccountModeEnabled)]
+    #[storage_mapper("account_mode_enabled")]
+    fn account_mode_enabled(&self) -> SingleValueMapper<bool>;
+
+    #[view(getAccountPosition)]
+    #[storage_mapper("account_position")]
+    fn account_position(
+        &self,
+        user: &ManagedAddress,
+    ) -> SingleValueMapper<FarmTokenAttributes<Self::Api>>;
+}
@@ -2,6 +2,7 @@ elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
 use common_errors::*;
+use config::BLOCKS_IN_YEAR;
 
 use contexts::generic::StorageCache;
 
@@ -21,9 +22,19 @@ pub trait CustomRewardsModule:
         if current_block_nonce > last_reward_nonce {
             let to_mint = self.calculate_per_block_rewards(current_block_nonce, last_reward_nonce);
 
-            if to_mint != 0 {
-                self.send().esdt_local_mint(token_id, 0, &to_mint);
-            }
+            // in funded mode, rewards are taken from the deposited funds instead of being minted
+            let to_mint = if self.funded_mode().get() {
+                let to_distribute = self.cap_to_remaining_reward_funds(to_mint);
+                self.accumulated_rewards()
+                    .update(|accumulated| *accumulated += &to_distribute);
+                to_distribute
+            } else {
+                if to_mint != 0 {
+                    self.send().esdt_local_mint(token_id, 0, &to_mint);
+                }
+                to_mint
+            };
+
             self.last_reward_block_nonce().set(&current_block_nonce);
             to_mint
         } else {
@@ -31,6 +42,24 @@ pub trait CustomRewardsModule:
         }
     }
 
+    fn calculate_pending_rewards(&self) -> BigUint {
+        let current_block_nonce = self.blockchain().get_block_nonce();
+        let last_reward_nonce = self.last_reward_block_nonce().get();
+        let pending_rewards =
+            self.calculate_per_block_rewards(current_block_nonce, last_reward_nonce);
+
+        if self.funded_mode().get() {
+            self.cap_to_remaining_reward_funds(pending_rewards)
+        } else {
+            pending_rewards
+        }
+    }
+
+    fn cap_to_remaining_reward_funds(&self, amount: BigUint) -> BigUint {
+        let remaining_funds = self.reward_capacity().get() - self.accumulated_rewards().get();
+        core::cmp::min(amount, remaining_funds)
+    }
+
     fn generate_aggregated_rewards(&self, storage: &mut StorageCache<Self::Api>) {
         let total_reward = self.mint_per_block_rewards(&storage.reward_token_id);
 
@@ -70,4 +99,116 @@ pub trait CustomRewardsModule:
 
         self.per_block_reward_amount().set(&per_block_amount);
     }
+
+    #[only_owner]
+    #[endpoint(setFundedMode)]
+    fn set_funded_mode(&self, funded_mode: bool) {
+        let mut storage = StorageCache::new(self);
+
+        self.generate_aggregated_rewards(&mut storage);
+        self.reward_per_share().set(&storage.reward_per_share);
+        self.reward_reserve().set(&storage.reward_reserve);
+
+        self.funded_mode().set(funded_mode);
+    }
+
+    /// Deposits reward tokens to be distributed while the farm is in funded mode.
+    /// Anyone can top up the rewards, so partners can fund the farms of their tokens.
+    #[payable("*")]
+    #[endpoint(topUpRewards)]
+    fn top_up_rewards(&self) {
+        require!(self.funded_mode().get(), ERROR_NOT_FUNDED_MODE);
+
+        let (payment_token, payment_amount) = self.call_value().single_fungible_esdt();
+        let reward_token_id = self.reward_token_id().get();
+        require!(payment_token == reward_token_id, ERROR_BAD_INPUT_TOKEN);
+
+        self.reward_capacity().update(|r| *r += payment_amount);
+    }
+
+    /// Withdraws deposited funds that were not distributed yet,
+    /// for example after the farm leaves funded mode.
+    #[only_owner]
+    #[endpoint(withdrawRewardFunds)]
+    fn withdraw_reward_funds(&self, amount: BigUint) {
+        require!(amount != 0u64, ERROR_ZERO_AMOUNT);
+
+        let mut storage = StorageCache::new(self);
+
+        self.generate_aggregated_rewards(&mut storage);
+        self.reward_per_share().set(&storage.reward_per_share);
+        self.reward_reserve().set(&storage.reward_reserve);
+
+        let remaining_funds = self.reward_capacity().get() - self.accumulated_rewards().get();
+        require!(amount <= remaining_funds, ERROR_NOT_ENOUGH_REWARD_FUNDS);
+
+        self.reward_capacity().update(|r| *r -= &amount);
+
+        let caller = self.blockchain().get_caller();
+        self.send_tokens_non_zero(&caller, &storage.reward_token_id, 0, &amount);
+    }
+
+    /// Rewards generated in one block for each farming token,
+    /// multiplied by the division safety constant.
+    #[view(getRewardPerBlockPerFarmingToken)]
+    fn get_reward_per_block_per_farming_token(&self) -> BigUint {
+        let farm_token_supply = self.farm_token_supply().get();
+        if farm_token_supply == 0u64 || !self.produces_per_block_rewards() {
+            return BigUint::zero();
+        }
+        if self.funded_mode().get() && self.get_remaining_reward_funds() == 0u64 {
+            return BigUint::zero();
+        }
+
+        self.per_block_reward_amount().get() * self.division_safety_constant().get()
+            / farm_token_supply
+    }
+
+    /// Rewards generated in one year for each farming token, at the current rate,
+    /// multiplied by the division safety constant.
+    #[view(getAnnualRewardPerFarmingToken)]
+    fn get_annual_reward_per_farming_token(&self) -> BigUint {
+        let farm_token_supply = self.farm_token_supply().get();
+        if farm_token_supply == 0u64 || !self.produces_per_block_rewards() {
+            return BigUint::zero();
+        }
+        if self.funded_mode().get() && self.get_remaining_reward_funds() == 0u64 {
+            return BigUint::zero();
+        }
+
+        self.per_block_reward_amount().get()
+            * BLOCKS_IN_YEAR
+            * self.division_safety_constant().get()
+            / farm_token_supply
+    }
+
+    #[view(getRemainingRewardFunds)]
+    fn get_remaining_reward_funds(&self) -> BigUint {
+        let remaining_funds = self.reward_capacity().get() - self.accumulated_rewards().get();
+        remaining_funds - self.calculate_pending_rewards()
+    }
+
+    /// Number of blocks until the deposited funds are exhausted,
+    /// at the current per block reward amount.
+    #[view(getRewardsDepletionBlocks)]
+    fn get_rewards_depletion_blocks(&self) -> BigUint {
+        require!(self.funded_mode().get(), ERROR_NOT_FUNDED_MODE);
+
+        let per_block_reward = self.per_block_reward_amount().get();
+        require!(per_block_reward != 0u64, ERROR_ZERO_AMOUNT);
+
+        self.get_remaining_reward_funds() / per_block_reward
+    }
+
+    #[view(isFundedMode)]
+    #[storage_mapper("funded_mode")]
+    fn funded_mode(&self) -> SingleValueMapper<bool>;
+
+    #[view(getRewardCapacity)]
+    #[storage_mapper("reward_capacity")]
+    fn reward_capacity(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getAccumulatedRewards)]
+    #[storage_mapper("accumulatedRewards")]
+    fn accumulated_rewards(&self) -> SingleValueMapper<BigUint>;
 }
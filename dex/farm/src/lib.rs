@@ -2,6 +2,7 @@
 #![allow(clippy::too_many_arguments)]
 #![feature(exact_size_is_empty)]
 
+pub mod account_positions;
 pub mod custom_rewards;
 pub mod farm_token_merge;
 
@@ -29,6 +30,7 @@ type ExitFarmResultType<BigUint> =
 #[elrond_wasm::contract]
 pub trait Farm:
     custom_rewards::CustomRewardsModule
+    + account_positions::AccountPositionsModule
     + rewards::RewardsModule
     + config::ConfigModule
     + token_send::TokenSendModule
@@ -39,6 +41,7 @@ pub trait Farm:
     + events::EventsModule
     + contexts::ctx_helper::CtxHelper
     + migration_from_v1_2::MigrationModule
+    + farm_migration::FarmMigrationModule
     + elrond_wasm_modules::default_issue_callbacks::DefaultIssueCallbacksModule
 {
     #[proxy]
@@ -294,6 +297,130 @@ pub trait Farm:
         context.get_output_payments().get(0)
     }
 
+    /// Allows users to withdraw their farming tokens while the farm is paused.
+    /// All the rewards of the position, including the compounded ones, are forfeited.
+    #[payable("*")]
+    #[endpoint(emergencyExit)]
+    fn emergency_exit(&self) -> EsdtTokenPayment<Self::Api> {
+        let mut context = self.new_farm_context();
+
+        require!(
+            context.get_contract_state() == State::Inactive,
+            ERROR_NOT_PAUSED
+        );
+        require!(
+            context.get_farm_token_id().is_valid_esdt_identifier(),
+            ERROR_NO_FARM_TOKEN
+        );
+        require!(context.is_accepted_payment_exit(), ERROR_BAD_PAYMENTS);
+
+        self.generate_aggregated_rewards(context.get_storage_cache_mut());
+        self.calculate_initial_farming_amount(&mut context);
+
+        self.commit_changes(&context);
+        self.burn_position(&context);
+
+        self.construct_output_payments_exit(&mut context);
+        self.execute_output_payments(&context);
+        self.emit_emergency_exit_event(&context);
+
+        context.get_output_payments().get(0)
+    }
+
+    /// Moves the position to the migration destination farm, once this farm stopped producing rewards.
+    /// The rewards of the position are paid out, and the farming tokens are forwarded
+    /// to the destination, which creates the new position.
+    #[payable("*")]
+    #[endpoint(migrateToNextFarm)]
+    fn migrate_to_next_farm(&self) -> ExitFarmResultType<Self::Api> {
+        let mut context = self.new_farm_context();
+
+        require!(
+            context.get_farm_token_id().is_valid_esdt_identifier(),
+            ERROR_NO_FARM_TOKEN
+        );
+        require!(context.is_accepted_payment_exit(), ERROR_BAD_PAYMENTS);
+        require!(
+            !self.produces_per_block_rewards(),
+            ERROR_REWARDS_NOT_STOPPED
+        );
+
+        self.generate_aggregated_rewards(context.get_storage_cache_mut());
+        self.calculate_reward(&mut context);
+        context.decrease_reward_reserve();
+        self.calculate_initial_farming_amount(&mut context);
+        self.increase_reward_with_compounded_rewards(&mut context);
+
+        self.commit_changes(&context);
+        self.burn_position(&context);
+        self.send_rewards(&mut context);
+
+        let farming_tokens = EsdtTokenPayment::new(
+            context.get_farming_token_id().clone(),
+            0,
+            context.get_initial_farming_amount().clone(),
+        );
+        let new_position = self.forward_position_to_destination(
+            farming_tokens,
+            context.get_input_attributes(),
+            context.get_caller().clone(),
+        );
+
+        MultiValue2::from((new_position, context.get_final_reward().unwrap().clone()))
+    }
+
+    /// Called by a migration source farm with the farming tokens of a position.
+    /// The new position is sent to `orig_caller`.
+    #[payable("*")]
+    #[endpoint(migratePosition)]
+    fn migrate_position(
+        &self,
+        source_attributes: ManagedBuffer,
+        orig_caller: ManagedAddress,
+    ) -> EsdtTokenPayment<Self::Api> {
+        let mut storage_cache = StorageCache::new(self);
+
+        require!(
+            storage_cache.contract_state == State::Active,
+            ERROR_NOT_ACTIVE
+        );
+        require!(
+            storage_cache.farm_token_id.is_valid_esdt_identifier(),
+            ERROR_NO_FARM_TOKEN
+        );
+
+        let farming_tokens = self.call_value().single_esdt();
+        require!(
+            farming_tokens.token_identifier == storage_cache.farming_token_id,
+            ERROR_BAD_PAYMENTS
+        );
+        require!(farming_tokens.amount != 0u64, ERROR_ZERO_AMOUNT);
+
+        self.generate_aggregated_rewards(&mut storage_cache);
+        self.reward_per_share().set(&storage_cache.reward_per_share);
+        self.reward_reserve().set(&storage_cache.reward_reserve);
+
+        let new_attributes = self.process_incoming_migration(
+            source_attributes,
+            &farming_tokens.amount,
+            &storage_cache.reward_per_share,
+        );
+
+        let new_farm_token = self.mint_farm_tokens(
+            storage_cache.farm_token_id,
+            farming_tokens.amount,
+            &new_attributes,
+        );
+        self.send().direct_esdt(
+            &orig_caller,
+            &new_farm_token.token_identifier,
+            new_farm_token.token_nonce,
+            &new_farm_token.amount,
+        );
+
+        new_farm_token
+    }
+
     fn burn_farming_tokens(
         &self,
         farming_token_id: &TokenIdentifier,
@@ -379,10 +506,7 @@ pub trait Farm:
         let farm_token_supply = self.farm_token_supply().get();
         require!(farm_token_supply >= amount, ERROR_ZERO_AMOUNT);
 
-        let last_reward_nonce = self.last_reward_block_nonce().get();
-        let current_block_nonce = self.blockchain().get_block_nonce();
-        let reward_increase =
-            self.calculate_per_block_rewards(current_block_nonce, last_reward_nonce);
+        let reward_increase = self.calculate_pending_rewards();
         let reward_per_share_increase = reward_increase * &self.division_safety_constant().get()
             / self.farm_token_supply().get();
 
@@ -396,6 +520,28 @@ pub trait Farm:
         }
     }
 
+    #[view(calculateRewardsForAccount)]
+    fn calculate_rewards_for_account(&self, user: ManagedAddress) -> BigUint {
+        let position_mapper = self.account_position(&user);
+        require!(!position_mapper.is_empty(), ERROR_NO_ACCOUNT_POSITION);
+
+        let position = position_mapper.get();
+        self.calculate_rewards_for_given_position(position.current_farm_amount.clone(), position)
+    }
+
+    #[view(getFarmingTokenValuation)]
+    fn get_farming_token_valuation(
+        &self,
+        farming_token_amount: BigUint,
+    ) -> MultiValue2<EsdtTokenPayment<Self::Api>, EsdtTokenPayment<Self::Api>> {
+        let pair_contract_address = self.pair_contract_address().get();
+        require!(!pair_contract_address.is_zero(), "No pair contract");
+
+        self.pair_contract_proxy(pair_contract_address)
+            .get_tokens_for_given_position(farming_token_amount)
+            .execute_on_dest_context()
+    }
+
     fn should_apply_penalty(&self, entering_epoch: Epoch) -> bool {
         entering_epoch + self.minimum_farming_epochs().get() as u64
             > self.blockchain().get_block_epoch()
@@ -8,42 +8,71 @@ elrond_wasm_node::wasm_endpoints! {
     farm
     (
         callBack
+        addMigrationSource
         addToPauseWhitelist
+        calculateRewardsForAccount
         calculateRewardsForGivenPosition
         claimRewards
+        claimRewardsAccount
         compoundRewards
+        convertToAccountPosition
+        convertToFarmToken
+        emergencyExit
         end_produce_rewards
         enterFarm
         exitFarm
+        getAccountPosition
+        getAccumulatedRewards
+        getAnnualRewardPerFarmingToken
         getBurnGasLimit
         getDivisionSafetyConstant
         getFarmMigrationConfiguration
         getFarmTokenId
         getFarmTokenSupply
         getFarmingTokenId
+        getFarmingTokenValuation
+        getIncomingMigrationProgress
         getLastRewardBlockNonce
         getLockedAssetFactoryManagedAddress
+        getMigrationDestination
+        getMigrationSources
         getMinimumFarmingEpoch
+        getOutgoingMigrationProgress
         getPairContractManagedAddress
         getPenaltyPercent
         getPerBlockRewardAmount
+        getRemainingRewardFunds
+        getRewardCapacity
+        getRewardPerBlockPerFarmingToken
         getRewardPerShare
         getRewardReserve
         getRewardTokenId
+        getRewardsDepletionBlocks
         getState
+        isAccountModeEnabled
+        isFundedMode
         mergeFarmTokens
         migrateFromV1_2Farm
+        migratePosition
+        migrateToNextFarm
         pause
         registerFarmToken
         removeFromPauseWhitelist
+        removeMigrationSource
         resume
+        setAccountModeEnabled
         setFarmMigrationConfig
         setFarmTokenSupply
+        setFundedMode
+        setMigrationDestination
         setPerBlockRewardAmount
         setRpsAndStartRewards
         set_burn_gas_limit
         set_minimum_farming_epochs
         set_penalty_percent
+        splitFarmToken
         startProduceRewards
+        topUpRewards
+        withdrawRewardFunds
     )
 }
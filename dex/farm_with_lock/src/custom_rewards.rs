@@ -2,6 +2,7 @@ elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
 use common_errors::*;
+use config::BLOCKS_IN_YEAR;
 
 use contexts::generic::StorageCache;
 
@@ -69,4 +70,32 @@ pub trait CustomRewardsModule:
 
         self.per_block_reward_amount().set(&per_block_amount);
     }
+
+    /// Rewards generated in one block for each farming token,
+    /// multiplied by the division safety constant.
+    #[view(getRewardPerBlockPerFarmingToken)]
+    fn get_reward_per_block_per_farming_token(&self) -> BigUint {
+        let farm_token_supply = self.farm_token_supply().get();
+        if farm_token_supply == 0u64 || !self.produces_per_block_rewards() {
+            return BigUint::zero();
+        }
+
+        self.per_block_reward_amount().get() * self.division_safety_constant().get()
+            / farm_token_supply
+    }
+
+    /// Rewards generated in one year for each farming token, at the current rate,
+    /// multiplied by the division safety constant.
+    #[view(getAnnualRewardPerFarmingToken)]
+    fn get_annual_reward_per_farming_token(&self) -> BigUint {
+        let farm_token_supply = self.farm_token_supply().get();
+        if farm_token_supply == 0u64 || !self.produces_per_block_rewards() {
+            return BigUint::zero();
+        }
+
+        self.per_block_reward_amount().get()
+            * BLOCKS_IN_YEAR
+            * self.division_safety_constant().get()
+            / farm_token_supply
+    }
 }
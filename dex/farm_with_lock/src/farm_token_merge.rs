@@ -39,6 +39,93 @@ pub trait FarmTokenMergeModule:
         new_tokens
     }
 
+    /// Splits a farm position into several positions with the given amounts.
+    /// The farming amounts of the position are divided proportionally,
+    /// while the reward per share and the entering epochs are kept.
+    #[payable("*")]
+    #[endpoint(splitFarmToken)]
+    fn split_farm_token(
+        &self,
+        amounts: MultiValueEncoded<BigUint>,
+    ) -> MultiValueEncoded<EsdtTokenPayment<Self::Api>> {
+        let caller = self.blockchain().get_caller();
+        let payment = self.call_value().single_esdt();
+        let farm_token_id = self.farm_token().get_token_id();
+        require!(
+            payment.token_identifier == farm_token_id,
+            ERROR_NOT_A_FARM_TOKEN
+        );
+
+        let amounts = amounts.to_vec();
+        require!(amounts.len() > 1, ERROR_BAD_SPLIT_AMOUNTS);
+
+        let mut total_amount = BigUint::zero();
+        for amount in amounts.iter() {
+            require!(amount != 0u64, ERROR_ZERO_AMOUNT);
+            total_amount += &amount;
+        }
+        require!(total_amount == payment.amount, ERROR_BAD_SPLIT_AMOUNTS);
+
+        let attributes: FarmTokenAttributes<Self::Api> =
+            self.get_farm_token_attributes(&payment.token_identifier, payment.token_nonce);
+        let mut remaining_initial_farming_amount = self.rule_of_three_non_zero_result(
+            &payment.amount,
+            &attributes.current_farm_amount,
+            &attributes.initial_farming_amount,
+        );
+        let mut remaining_compounded_reward = self.rule_of_three(
+            &payment.amount,
+            &attributes.current_farm_amount,
+            &attributes.compounded_reward,
+        );
+        let total_initial_farming_amount = remaining_initial_farming_amount.clone();
+        let total_compounded_reward = remaining_compounded_reward.clone();
+
+        self.burn_farm_tokens(
+            &payment.token_identifier,
+            payment.token_nonce,
+            &payment.amount,
+        );
+
+        let last_index = amounts.len() - 1;
+        let mut new_tokens = ManagedVec::new();
+        for (i, amount) in amounts.iter().enumerate() {
+            // the last position gets the remainders, so nothing is lost to rounding
+            let (initial_farming_amount, compounded_reward) = if i == last_index {
+                (
+                    remaining_initial_farming_amount.clone(),
+                    remaining_compounded_reward.clone(),
+                )
+            } else {
+                (
+                    self.rule_of_three_non_zero_result(
+                        &amount,
+                        &payment.amount,
+                        &total_initial_farming_amount,
+                    ),
+                    self.rule_of_three(&amount, &payment.amount, &total_compounded_reward),
+                )
+            };
+            require!(initial_farming_amount != 0u64, ERROR_ZERO_AMOUNT);
+            remaining_initial_farming_amount -= &initial_farming_amount;
+            remaining_compounded_reward -= &compounded_reward;
+
+            let new_attributes = FarmTokenAttributes {
+                reward_per_share: attributes.reward_per_share.clone(),
+                original_entering_epoch: attributes.original_entering_epoch,
+                entering_epoch: attributes.entering_epoch,
+                initial_farming_amount,
+                compounded_reward,
+                current_farm_amount: amount.clone(),
+            };
+            new_tokens.push(self.mint_farm_tokens(farm_token_id.clone(), amount, &new_attributes));
+        }
+
+        self.send().direct_multi(&caller, &new_tokens);
+
+        new_tokens.into()
+    }
+
     fn get_merged_farm_token_attributes(
         &self,
         payments: &ManagedVec<EsdtTokenPayment<Self::Api>>,
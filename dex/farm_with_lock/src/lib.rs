@@ -4,6 +4,7 @@
 
 pub mod custom_rewards;
 pub mod farm_token_merge;
+pub mod reward_unlock_options;
 
 use common_errors::*;
 
@@ -38,7 +39,9 @@ pub trait Farm:
     + pausable::PausableModule
     + events::EventsModule
     + contexts::ctx_helper::CtxHelper
+    + reward_unlock_options::RewardUnlockOptionsModule
     + migration_from_v1_2::MigrationModule
+    + farm_migration::FarmMigrationModule
     + elrond_wasm_modules::default_issue_callbacks::DefaultIssueCallbacksModule
 {
     #[proxy]
@@ -143,8 +146,12 @@ pub trait Farm:
 
     #[payable("*")]
     #[endpoint(exitFarm)]
-    fn exit_farm(&self) -> ExitFarmResultType<Self::Api> {
+    fn exit_farm(
+        &self,
+        opt_orig_caller: OptionalValue<ManagedAddress>,
+    ) -> ExitFarmResultType<Self::Api> {
         let mut context = self.new_farm_context();
+        let reward_option_user = self.get_reward_option_user(context.get_caller(), opt_orig_caller);
 
         require!(
             context.get_contract_state() == State::Active,
@@ -166,7 +173,7 @@ pub trait Farm:
         self.burn_position(&context);
         self.commit_changes(&context);
 
-        self.send_rewards(&mut context);
+        self.send_rewards(&mut context, &reward_option_user);
         self.construct_output_payments_exit(&mut context);
         self.execute_output_payments(&context);
         self.emit_exit_farm_event(&context);
@@ -176,8 +183,12 @@ pub trait Farm:
 
     #[payable("*")]
     #[endpoint(claimRewards)]
-    fn claim_rewards(&self) -> ClaimRewardsResultType<Self::Api> {
+    fn claim_rewards(
+        &self,
+        opt_orig_caller: OptionalValue<ManagedAddress>,
+    ) -> ClaimRewardsResultType<Self::Api> {
         let mut context = self.new_farm_context();
+        let reward_option_user = self.get_reward_option_user(context.get_caller(), opt_orig_caller);
 
         require!(
             context.get_contract_state() == State::Active,
@@ -225,7 +236,7 @@ pub trait Farm:
         self.burn_position(&context);
         self.commit_changes(&context);
 
-        self.send_rewards(&mut context);
+        self.send_rewards(&mut context, &reward_option_user);
         self.execute_output_payments(&context);
         self.emit_claim_rewards_event(&context);
 
@@ -334,6 +345,134 @@ pub trait Farm:
         self.aggregated_original_entering_epoch(&items)
     }
 
+    /// Allows users to withdraw their farming tokens while the farm is paused.
+    /// All the rewards of the position, including the compounded ones, are forfeited.
+    #[payable("*")]
+    #[endpoint(emergencyExit)]
+    fn emergency_exit(&self) -> EsdtTokenPayment<Self::Api> {
+        let mut context = self.new_farm_context();
+
+        require!(
+            context.get_contract_state() == State::Inactive,
+            ERROR_NOT_PAUSED
+        );
+        require!(
+            context.get_farm_token_id().is_valid_esdt_identifier(),
+            ERROR_NO_FARM_TOKEN
+        );
+        require!(context.is_accepted_payment_exit(), ERROR_BAD_PAYMENTS);
+
+        self.generate_aggregated_rewards(context.get_storage_cache_mut());
+        self.calculate_initial_farming_amount(&mut context);
+
+        self.commit_changes(&context);
+        self.burn_position(&context);
+
+        self.construct_output_payments_exit(&mut context);
+        self.execute_output_payments(&context);
+        self.emit_emergency_exit_event(&context);
+
+        context.get_output_payments().get(0)
+    }
+
+    /// Moves the position to the migration destination farm, once this farm stopped producing rewards.
+    /// The rewards of the position are paid out, and the farming tokens are forwarded
+    /// to the destination, which creates the new position.
+    #[payable("*")]
+    #[endpoint(migrateToNextFarm)]
+    fn migrate_to_next_farm(
+        &self,
+        opt_orig_caller: OptionalValue<ManagedAddress>,
+    ) -> ExitFarmResultType<Self::Api> {
+        let mut context = self.new_farm_context();
+        let reward_option_user = self.get_reward_option_user(context.get_caller(), opt_orig_caller);
+
+        require!(
+            context.get_farm_token_id().is_valid_esdt_identifier(),
+            ERROR_NO_FARM_TOKEN
+        );
+        require!(context.is_accepted_payment_exit(), ERROR_BAD_PAYMENTS);
+        require!(
+            !self.produces_per_block_rewards(),
+            ERROR_REWARDS_NOT_STOPPED
+        );
+
+        self.generate_aggregated_rewards(context.get_storage_cache_mut());
+        self.calculate_reward(&mut context);
+        context.decrease_reward_reserve();
+        self.calculate_initial_farming_amount(&mut context);
+        self.increase_reward_with_compounded_rewards(&mut context);
+
+        self.commit_changes(&context);
+        self.burn_position(&context);
+        self.send_rewards(&mut context, &reward_option_user);
+
+        let farming_tokens = EsdtTokenPayment::new(
+            context.get_farming_token_id().clone(),
+            0,
+            context.get_initial_farming_amount().clone(),
+        );
+        let new_position = self.forward_position_to_destination(
+            farming_tokens,
+            context.get_input_attributes(),
+            context.get_caller().clone(),
+        );
+
+        MultiValue2::from((new_position, context.get_final_reward().unwrap().clone()))
+    }
+
+    /// Called by a migration source farm with the farming tokens of a position.
+    /// The new position is sent to `orig_caller`.
+    #[payable("*")]
+    #[endpoint(migratePosition)]
+    fn migrate_position(
+        &self,
+        source_attributes: ManagedBuffer,
+        orig_caller: ManagedAddress,
+    ) -> EsdtTokenPayment<Self::Api> {
+        let mut storage_cache = StorageCache::new(self);
+
+        require!(
+            storage_cache.contract_state == State::Active,
+            ERROR_NOT_ACTIVE
+        );
+        require!(
+            storage_cache.farm_token_id.is_valid_esdt_identifier(),
+            ERROR_NO_FARM_TOKEN
+        );
+
+        let farming_tokens = self.call_value().single_esdt();
+        require!(
+            farming_tokens.token_identifier == storage_cache.farming_token_id,
+            ERROR_BAD_PAYMENTS
+        );
+        require!(farming_tokens.amount != 0u64, ERROR_ZERO_AMOUNT);
+
+        self.generate_aggregated_rewards(&mut storage_cache);
+        self.reward_per_share().set(&storage_cache.reward_per_share);
+        self.reward_reserve().set(&storage_cache.reward_reserve);
+
+        let new_attributes = self.process_incoming_migration(
+            source_attributes,
+            &farming_tokens.amount,
+            &storage_cache.reward_per_share,
+        );
+
+        let new_farm_token = self.mint_farm_tokens(
+            storage_cache.farm_token_id,
+            farming_tokens.amount,
+            &new_attributes,
+        );
+        self.send().direct_esdt(
+            &orig_caller,
+            &new_farm_token.token_identifier,
+            new_farm_token.token_nonce,
+            &new_farm_token.amount,
+        );
+
+        new_farm_token
+    }
+
     fn burn_farming_tokens(
         &self,
         farming_token_id: &TokenIdentifier,
@@ -392,17 +531,35 @@ pub trait Farm:
             .direct_esdt(destination, farming_token_id, 0, farming_amount);
     }
 
-    fn send_rewards(&self, context: &mut GenericContext<Self::Api>) {
+    /// The unlock option is the one of `reward_option_user`,
+    /// which differs from the caller for positions held through a trusted proxy.
+    fn send_rewards(
+        &self,
+        context: &mut GenericContext<Self::Api>,
+        reward_option_user: &ManagedAddress,
+    ) {
         if context.get_position_reward() > &0u64 {
             let locked_asset_factory_address = self.locked_asset_factory_address().get();
-            let result = self
-                .locked_asset_factory(locked_asset_factory_address)
-                .create_and_forward(
-                    context.get_position_reward().clone(),
-                    context.get_caller().clone(),
-                    context.get_input_attributes().entering_epoch,
-                )
-                .execute_on_dest_context();
+            let caller = context.get_caller().clone();
+            let start_epoch = context.get_input_attributes().entering_epoch;
+            let result = match self.get_reward_unlock_option(reward_option_user) {
+                Some(option) => {
+                    let reward_amount =
+                        context.get_position_reward() * option.reward_multiplier / MAX_PERCENT;
+                    self.locked_asset_factory(locked_asset_factory_address)
+                        .create_and_forward_custom_period(
+                            reward_amount,
+                            caller,
+                            start_epoch,
+                            option.unlock_period,
+                        )
+                        .execute_on_dest_context()
+                }
+                None => self
+                    .locked_asset_factory(locked_asset_factory_address)
+                    .create_and_forward(context.get_position_reward().clone(), caller, start_epoch)
+                    .execute_on_dest_context(),
+            };
             context.set_final_reward(result);
         } else {
             context.set_final_reward(EsdtTokenPayment::new(
@@ -440,6 +597,19 @@ pub trait Farm:
         }
     }
 
+    #[view(getFarmingTokenValuation)]
+    fn get_farming_token_valuation(
+        &self,
+        farming_token_amount: BigUint,
+    ) -> MultiValue2<EsdtTokenPayment<Self::Api>, EsdtTokenPayment<Self::Api>> {
+        let pair_contract_address = self.pair_contract_address().get();
+        require!(!pair_contract_address.is_zero(), "No pair contract");
+
+        self.pair_contract_proxy(pair_contract_address)
+            .get_tokens_for_given_position(farming_token_amount)
+            .execute_on_dest_context()
+    }
+
     #[inline]
     fn should_apply_penalty(&self, entering_epoch: u64) -> bool {
         entering_epoch + self.minimum_farming_epochs().get() as u64
@@ -0,0 +1,218 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use common_errors::*;
+use common_structs::{UnlockMilestone, UnlockPeriod};
+use config::MAX_PERCENT;
+
+pub const DEFAULT_UNLOCK_OPTION_ID: usize = 0;
+
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct RewardUnlockOption<M: ManagedTypeApi> {
+    pub unlock_period: UnlockPeriod<M>,
+    pub reward_multiplier: u64,
+}
+
+#[elrond_wasm::module]
+pub trait RewardUnlockOptionsModule: config::ConfigModule {
+    /// Adds an unlock schedule users can choose for their rewards.
+    /// The rewards are multiplied by `reward_multiplier` / MAX_PERCENT,
+    /// so longer schedules can be given a bonus and shorter ones a cut.
+    #[only_owner]
+    #[endpoint(addRewardUnlockOption)]
+    fn add_reward_unlock_option(
+        &self,
+        reward_multiplier: u64,
+        unlock_milestones: MultiValueEncoded<UnlockMilestone>,
+    ) -> usize {
+        require!(
+            reward_multiplier != 0 && reward_multiplier <= self.get_max_reward_multiplier(),
+            ERROR_PARAMETERS
+        );
+
+        let unlock_milestones = unlock_milestones.to_vec();
+        self.validate_unlock_milestones(&unlock_milestones);
+
+        let option_id = self.last_reward_unlock_option_id().get() + 1;
+        self.last_reward_unlock_option_id().set(option_id);
+        self.reward_unlock_options().insert(
+            option_id,
+            RewardUnlockOption {
+                unlock_period: UnlockPeriod::from(unlock_milestones),
+                reward_multiplier,
+            },
+        );
+
+        option_id
+    }
+
+    /// The rewards are created by the locked asset factory, so the multiplier
+    /// of new options is bounded by this value instead of the farm's reserve.
+    #[only_owner]
+    #[endpoint(setMaxRewardMultiplier)]
+    fn set_max_reward_multiplier(&self, max_reward_multiplier: u64) {
+        require!(max_reward_multiplier >= MAX_PERCENT, ERROR_PARAMETERS);
+        self.max_reward_multiplier().set(max_reward_multiplier);
+    }
+
+    /// Users who selected a removed option get the default one.
+    #[only_owner]
+    #[endpoint(removeRewardUnlockOption)]
+    fn remove_reward_unlock_option(&self, option_id: usize) {
+        let removed = self.reward_unlock_options().remove(&option_id);
+        require!(removed.is_some(), ERROR_PARAMETERS);
+
+        if self.default_reward_unlock_option().get() == option_id {
+            self.default_reward_unlock_option().clear();
+        }
+    }
+
+    /// The option used for users who did not select one.
+    /// Option 0 means the default unlock period of the locked asset factory.
+    #[only_owner]
+    #[endpoint(setDefaultRewardUnlockOption)]
+    fn set_default_reward_unlock_option(&self, option_id: usize) {
+        self.require_valid_reward_unlock_option(option_id);
+        self.default_reward_unlock_option().set(option_id);
+    }
+
+    /// Selects the unlock schedule of the caller's future rewards.
+    /// Option 0 means the farm's default option.
+    #[endpoint(selectRewardUnlockOption)]
+    fn select_reward_unlock_option(&self, option_id: usize) {
+        self.require_valid_reward_unlock_option(option_id);
+
+        let caller = self.blockchain().get_caller();
+        self.user_reward_unlock_option(&caller).set(option_id);
+    }
+
+    /// Allows a contract, such as proxy-dex, to pass the original caller
+    /// of its users, so their rewards use the unlock option they selected.
+    #[only_owner]
+    #[endpoint(addTrustedProxy)]
+    fn add_trusted_proxy(&self, address: ManagedAddress) {
+        self.trusted_proxies().insert(address);
+    }
+
+    #[only_owner]
+    #[endpoint(removeTrustedProxy)]
+    fn remove_trusted_proxy(&self, address: ManagedAddress) {
+        let removed = self.trusted_proxies().remove(&address);
+        require!(removed, ERROR_PARAMETERS);
+    }
+
+    /// Returns the user whose unlock option applies to the caller's rewards.
+    fn get_reward_option_user(
+        &self,
+        caller: &ManagedAddress,
+        opt_orig_caller: OptionalValue<ManagedAddress>,
+    ) -> ManagedAddress {
+        match opt_orig_caller {
+            OptionalValue::Some(orig_caller) => {
+                require!(
+                    self.trusted_proxies().contains(caller),
+                    "Caller is not a trusted proxy"
+                );
+                orig_caller
+            }
+            OptionalValue::None => caller.clone(),
+        }
+    }
+
+    /// Returns None if the rewards should use the factory's default unlock period.
+    fn get_reward_unlock_option(
+        &self,
+        user: &ManagedAddress,
+    ) -> Option<RewardUnlockOption<Self::Api>> {
+        let option_id = self.get_user_effective_reward_unlock_option(user.clone());
+        self.reward_unlock_options().get(&option_id)
+    }
+
+    /// Defaults to MAX_PERCENT, which allows no bonus.
+    #[view(getMaxRewardMultiplier)]
+    fn get_max_reward_multiplier(&self) -> u64 {
+        let max_reward_multiplier = self.max_reward_multiplier().get();
+        if max_reward_multiplier == 0 {
+            MAX_PERCENT
+        } else {
+            max_reward_multiplier
+        }
+    }
+
+    fn require_valid_reward_unlock_option(&self, option_id: usize) {
+        require!(
+            option_id == DEFAULT_UNLOCK_OPTION_ID
+                || self.reward_unlock_options().contains_key(&option_id),
+            "Unknown unlock option"
+        );
+    }
+
+    fn validate_unlock_milestones(&self, unlock_milestones: &ManagedVec<UnlockMilestone>) {
+        require!(!unlock_milestones.is_empty(), ERROR_PARAMETERS);
+
+        let mut percents_sum = 0u64;
+        let mut last_milestone_unlock_epoch = 0u64;
+        for milestone in unlock_milestones.iter() {
+            require!(
+                milestone.unlock_epoch >= last_milestone_unlock_epoch,
+                "Unlock epochs not in order"
+            );
+
+            last_milestone_unlock_epoch = milestone.unlock_epoch;
+            percents_sum += milestone.unlock_percent as u64;
+        }
+
+        require!(percents_sum == 100, "Percents do not sum up to 100");
+    }
+
+    #[view(getRewardUnlockOptions)]
+    fn get_reward_unlock_options(
+        &self,
+    ) -> MultiValueEncoded<MultiValue2<usize, RewardUnlockOption<Self::Api>>> {
+        let mut result = MultiValueEncoded::new();
+        for (option_id, option) in self.reward_unlock_options().iter() {
+            result.push((option_id, option).into());
+        }
+        result
+    }
+
+    /// Returns the option that will be used for the user's rewards.
+    #[view(getUserEffectiveRewardUnlockOption)]
+    fn get_user_effective_reward_unlock_option(&self, user: ManagedAddress) -> usize {
+        let user_option_id = self.user_reward_unlock_option(&user).get();
+        if self.reward_unlock_options().contains_key(&user_option_id) {
+            return user_option_id;
+        }
+
+        let default_option_id = self.default_reward_unlock_option().get();
+        if self
+            .reward_unlock_options()
+            .contains_key(&default_option_id)
+        {
+            default_option_id
+        } else {
+            DEFAULT_UNLOCK_OPTION_ID
+        }
+    }
+
+    #[storage_mapper("reward_unlock_options")]
+    fn reward_unlock_options(&self) -> MapMapper<usize, RewardUnlockOption<Self::Api>>;
+
+    #[storage_mapper("last_reward_unlock_option_id")]
+    fn last_reward_unlock_option_id(&self) -> SingleValueMapper<usize>;
+
+    #[storage_mapper("max_reward_multiplier")]
+    fn max_reward_multiplier(&self) -> SingleValueMapper<u64>;
+
+    #[view(getDefaultRewardUnlockOption)]
+    #[storage_mapper("default_reward_unlock_option")]
+    fn default_reward_unlock_option(&self) -> SingleValueMapper<usize>;
+
+    #[view(getTrustedProxies)]
+    #[storage_mapper("trusted_proxies")]
+    fn trusted_proxies(&self) -> SetMapper<ManagedAddress>;
+
+    #[view(getUserRewardUnlockOption)]
+    #[storage_mapper("user_reward_unlock_option")]
+    fn user_reward_unlock_option(&self, user: &ManagedAddress) -> SingleValueMapper<usize>;
+}
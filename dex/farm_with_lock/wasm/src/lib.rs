@@ -8,42 +8,69 @@ elrond_wasm_node::wasm_endpoints! {
     farm_with_lock
     (
         callBack
+        addMigrationSource
+        addRewardUnlockOption
         addToPauseWhitelist
+        addTrustedProxy
         calculateRewardsForGivenPosition
         claimRewards
         compoundRewards
+        emergencyExit
         end_produce_rewards
         enterFarm
         exitFarm
+        getAnnualRewardPerFarmingToken
         getBurnGasLimit
+        getDefaultRewardUnlockOption
         getDivisionSafetyConstant
         getFarmMigrationConfiguration
         getFarmTokenId
         getFarmTokenSupply
         getFarmingTokenId
+        getFarmingTokenValuation
+        getIncomingMigrationProgress
         getLastRewardBlockNonce
         getLockedAssetFactoryManagedAddress
+        getMaxRewardMultiplier
+        getMigrationDestination
+        getMigrationSources
         getMinimumFarmingEpoch
+        getOutgoingMigrationProgress
         getPairContractManagedAddress
         getPenaltyPercent
         getPerBlockRewardAmount
+        getRewardPerBlockPerFarmingToken
         getRewardPerShare
         getRewardReserve
         getRewardTokenId
+        getRewardUnlockOptions
         getState
+        getTrustedProxies
+        getUserEffectiveRewardUnlockOption
+        getUserRewardUnlockOption
         mergeFarmTokens
         migrateFromV1_2Farm
+        migratePosition
+        migrateToNextFarm
         pause
         registerFarmToken
         removeFromPauseWhitelist
+        removeMigrationSource
+        removeRewardUnlockOption
+        removeTrustedProxy
         resume
+        selectRewardUnlockOption
+        setDefaultRewardUnlockOption
         setFarmMigrationConfig
         setFarmTokenSupply
+        setMaxRewardMultiplier
+        setMigrationDestination
         setPerBlockRewardAmount
         setRpsAndStartRewards
         set_burn_gas_limit
         set_minimum_farming_epochs
         set_penalty_percent
+        splitFarmToken
         startProduceRewards
     )
 }
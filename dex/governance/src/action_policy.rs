@@ -0,0 +1,139 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use crate::config;
+use crate::errors::*;
+use crate::proposal::Action;
+
+/// An empty `endpoint_name` marks every endpoint of the destination as critical.
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Debug)]
+pub struct CriticalAction<M: ManagedTypeApi> {
+    pub dest_address: ManagedAddress<M>,
+    pub endpoint_name: ManagedBuffer<M>,
+}
+
+#[elrond_wasm::module]
+pub trait ActionPolicyModule: config::Config {
+    #[endpoint(addCriticalActions)]
+    fn add_critical_actions(
+        &self,
+        critical_actions: MultiValueEncoded<MultiValue2<ManagedAddress, ManagedBuffer>>,
+    ) {
+        self.require_caller_self();
+
+        for critical_action in critical_actions.into_iter() {
+            let (dest_address, endpoint_name) = critical_action.into_tuple();
+            require!(!dest_address.is_zero(), ERROR_ZERO_VALUE);
+
+            self.critical_actions().insert(CriticalAction {
+                dest_address,
+                endpoint_name,
+            });
+        }
+    }
+
+    #[endpoint(removeCriticalActions)]
+    fn remove_critical_actions(
+        &self,
+        critical_actions: MultiValueEncoded<MultiValue2<ManagedAddress, ManagedBuffer>>,
+    ) {
+        self.require_caller_self();
+
+        for critical_action in critical_actions.into_iter() {
+            let (dest_address, endpoint_name) = critical_action.into_tuple();
+            self.critical_actions().remove(&CriticalAction {
+                dest_address,
+                endpoint_name,
+            });
+        }
+    }
+
+    #[endpoint(addAllowedDestinations)]
+    fn add_allowed_destinations(&self, addresses: MultiValueEncoded<ManagedAddress>) {
+        self.require_caller_self();
+
+        for address in addresses.into_iter() {
+            require!(!address.is_zero(), ERROR_ZERO_VALUE);
+
+            self.allowed_destinations().insert(address);
+        }
+    }
+
+    #[endpoint(removeAllowedDestinations)]
+    fn remove_allowed_destinations(&self, addresses: MultiValueEncoded<ManagedAddress>) {
+        self.require_caller_self();
+
+        for address in addresses.into_iter() {
+            self.allowed_destinations().remove(&address);
+        }
+    }
+
+    #[endpoint(setDestinationAllowlistEnabled)]
+    fn set_destination_allowlist_enabled(&self, enabled: bool) {
+        self.require_caller_self();
+
+        self.destination_allowlist_enabled().set(enabled);
+    }
+
+    /// Only contract destinations are checked, so the treasury can still pay user accounts.
+    /// The governance contract itself is always allowed, to keep its configuration reachable.
+    fn require_allowed_destinations(&self, actions: &ManagedVec<Action<Self::Api>>) {
+        if !self.destination_allowlist_enabled().get() {
+            return;
+        }
+
+        let sc_address = self.blockchain().get_sc_address();
+        for action in actions.iter() {
+            if action.dest_address == sc_address
+                || !self.blockchain().is_smart_contract(&action.dest_address)
+            {
+                continue;
+            }
+
+            require!(
+                self.allowed_destinations().contains(&action.dest_address),
+                DESTINATION_NOT_ALLOWED
+            );
+        }
+    }
+
+    fn has_critical_actions(&self, actions: &ManagedVec<Action<Self::Api>>) -> bool {
+        for action in actions.iter() {
+            if self.is_critical_action(&action) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Self-calls change the voting power, the quorums, the timelock, the guardian
+    /// or the action policy itself, so they are always critical.
+    fn is_critical_action(&self, action: &Action<Self::Api>) -> bool {
+        if action.dest_address == self.blockchain().get_sc_address() {
+            return true;
+        }
+
+        let critical_actions = self.critical_actions();
+
+        critical_actions.contains(&CriticalAction {
+            dest_address: action.dest_address.clone(),
+            endpoint_name: action.endpoint_name.clone(),
+        }) || critical_actions.contains(&CriticalAction {
+            dest_address: action.dest_address.clone(),
+            endpoint_name: ManagedBuffer::new(),
+        })
+    }
+
+    #[view(getCriticalActions)]
+    #[storage_mapper("criticalActions")]
+    fn critical_actions(&self) -> SetMapper<CriticalAction<Self::Api>>;
+
+    #[view(getAllowedDestinations)]
+    #[storage_mapper("allowedDestinations")]
+    fn allowed_destinations(&self) -> SetMapper<ManagedAddress>;
+
+    #[view(isDestinationAllowlistEnabled)]
+    #[storage_mapper("destinationAllowlistEnabled")]
+    fn destination_allowlist_enabled(&self) -> SingleValueMapper<bool>;
+}
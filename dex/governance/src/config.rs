@@ -3,6 +3,10 @@ elrond_wasm::imports!();
 use crate::errors::*;
 use crate::proposal::*;
 
+pub const MAX_PERCENTAGE: u64 = 10_000;
+pub const DEFAULT_TIMELOCK_DELAY_IN_BLOCKS: u64 = 14_400; // ~1 day
+pub const DEFAULT_EXECUTION_GRACE_PERIOD_IN_BLOCKS: u64 = 100_800; // ~7 days
+
 #[elrond_wasm::module]
 pub trait Config {
     #[endpoint(changeQuorum)]
@@ -12,6 +16,27 @@ pub trait Config {
         self.try_change_quorum(new_value);
     }
 
+    #[endpoint(changeQuorumPercentage)]
+    fn change_quorum_percentage(&self, new_value: u64) {
+        self.require_caller_self();
+
+        self.try_change_quorum_percentage(new_value);
+    }
+
+    #[endpoint(changeCirculatingVotingSupply)]
+    fn change_circulating_voting_supply(&self, new_value: BigUint) {
+        self.require_caller_self();
+
+        self.try_change_circulating_voting_supply(new_value);
+    }
+
+    #[endpoint(changeMinApprovalRatio)]
+    fn change_min_approval_ratio(&self, new_value: u64) {
+        self.require_caller_self();
+
+        self.try_change_min_approval_ratio(new_value);
+    }
+
     #[endpoint(changeMinTokenBalanceForProposing)]
     fn change_min_weight_for_proposal(&self, new_value: BigUint) {
         self.require_caller_self();
@@ -33,6 +58,55 @@ pub trait Config {
         self.try_change_voting_period_in_blocks(new_value);
     }
 
+    #[endpoint(changeCriticalQuorum)]
+    fn change_critical_quorum(&self, new_value: BigUint) {
+        self.require_caller_self();
+
+        self.try_change_critical_quorum(new_value);
+    }
+
+    #[endpoint(changeCriticalQuorumPercentage)]
+    fn change_critical_quorum_percentage(&self, new_value: u64) {
+        self.require_caller_self();
+
+        self.try_change_critical_quorum_percentage(new_value);
+    }
+
+    #[endpoint(changeCriticalVotingPeriodInBlocks)]
+    fn change_critical_voting_period_in_blocks(&self, new_value: u64) {
+        self.require_caller_self();
+
+        self.try_change_critical_voting_period_in_blocks(new_value);
+    }
+
+    #[endpoint(changeTimelockDelayInBlocks)]
+    fn change_timelock_delay_in_blocks(&self, new_value: u64) {
+        self.require_caller_self();
+
+        self.try_change_timelock_delay_in_blocks(new_value);
+    }
+
+    #[endpoint(changeExecutionGracePeriodInBlocks)]
+    fn change_execution_grace_period_in_blocks(&self, new_value: u64) {
+        self.require_caller_self();
+
+        self.try_change_execution_grace_period_in_blocks(new_value);
+    }
+
+    #[endpoint(changeProposalDepositAmount)]
+    fn change_proposal_deposit_amount(&self, new_value: BigUint) {
+        self.require_caller_self();
+
+        self.proposal_deposit_amount().set(&new_value);
+    }
+
+    #[endpoint(changeGuardian)]
+    fn change_guardian(&self, new_guardian: ManagedAddress) {
+        self.require_caller_self();
+
+        self.guardian().set(&new_guardian);
+    }
+
     #[endpoint(changeGovernanceTokenIds)]
     fn change_governance_token_ids(&self, token_ids: ManagedVec<TokenIdentifier>) {
         self.require_caller_self();
@@ -57,6 +131,26 @@ pub trait Config {
         require!(caller == sc_address, INVALID_CALLER_NOT_SELF);
     }
 
+    fn require_caller_owner_or_self(&self) {
+        let caller = self.blockchain().get_caller();
+        require!(
+            caller == self.blockchain().get_owner_address()
+                || caller == self.blockchain().get_sc_address(),
+            INVALID_CALLER_NOT_OWNER_OR_SELF
+        );
+    }
+
+    fn require_caller_guardian(&self) {
+        let caller = self.blockchain().get_caller();
+        require!(self.is_guardian(&caller), INVALID_CALLER_NOT_GUARDIAN);
+    }
+
+    fn is_guardian(&self, address: &ManagedAddress) -> bool {
+        let guardian_mapper = self.guardian();
+
+        !guardian_mapper.is_empty() && address == &guardian_mapper.get()
+    }
+
     fn try_change_mex_token_id(&self, token_id: TokenIdentifier) {
         require!(token_id.is_valid_esdt_identifier(), INVALID_ESDT);
 
@@ -69,6 +163,7 @@ pub trait Config {
         self.vote_nft_id().set(&token_id);
     }
 
+    /// Tokens with a weight provider stay governance tokens until their provider is removed.
     fn try_change_governance_token_ids(&self, token_ids: ManagedVec<TokenIdentifier>) {
         self.governance_token_ids().clear();
 
@@ -77,6 +172,10 @@ pub trait Config {
 
             self.governance_token_ids().insert(token_id);
         }
+
+        for token_id in self.weight_provider_token_ids().iter() {
+            self.governance_token_ids().insert(token_id);
+        }
     }
 
     fn try_change_price_providers(
@@ -100,6 +199,34 @@ pub trait Config {
         self.quorum().set(&new_value);
     }
 
+    fn try_change_quorum_percentage(&self, new_value: u64) {
+        require!(new_value <= MAX_PERCENTAGE, INVALID_PERCENTAGE);
+        self.require_circulating_voting_supply_set(new_value);
+
+        self.quorum_percentage().set(&new_value);
+    }
+
+    /// A percentage quorum of an unset supply would let any proposal pass,
+    /// so the supply must be set before, e.g. by an earlier action of the same proposal.
+    fn require_circulating_voting_supply_set(&self, quorum_percentage: u64) {
+        require!(
+            quorum_percentage == 0 || !self.circulating_voting_supply().is_empty(),
+            CIRCULATING_SUPPLY_NOT_SET
+        );
+    }
+
+    fn try_change_circulating_voting_supply(&self, new_value: BigUint) {
+        require!(new_value != 0u64, ERROR_ZERO_VALUE);
+
+        self.circulating_voting_supply().set(&new_value);
+    }
+
+    fn try_change_min_approval_ratio(&self, new_value: u64) {
+        require!(new_value <= MAX_PERCENTAGE, INVALID_PERCENTAGE);
+
+        self.min_approval_ratio().set(&new_value);
+    }
+
     fn try_change_min_weight_for_proposal(&self, new_value: BigUint) {
         require!(new_value != 0u64, ERROR_ZERO_VALUE);
 
@@ -118,14 +245,60 @@ pub trait Config {
         self.voting_period_in_blocks().set(&new_value);
     }
 
+    fn try_change_critical_quorum(&self, new_value: BigUint) {
+        require!(new_value != 0u64, ERROR_ZERO_VALUE);
+
+        self.critical_quorum().set(&new_value);
+    }
+
+    fn try_change_critical_quorum_percentage(&self, new_value: u64) {
+        require!(new_value <= MAX_PERCENTAGE, INVALID_PERCENTAGE);
+        self.require_circulating_voting_supply_set(new_value);
+
+        self.critical_quorum_percentage().set(&new_value);
+    }
+
+    fn try_change_critical_voting_period_in_blocks(&self, new_value: u64) {
+        require!(new_value != 0, ERROR_ZERO_VALUE);
+
+        self.critical_voting_period_in_blocks().set(&new_value);
+    }
+
+    fn try_change_timelock_delay_in_blocks(&self, new_value: u64) {
+        require!(new_value != 0, ERROR_ZERO_VALUE);
+
+        self.timelock_delay_in_blocks().set(&new_value);
+    }
+
+    fn try_change_execution_grace_period_in_blocks(&self, new_value: u64) {
+        require!(new_value != 0, ERROR_ZERO_VALUE);
+
+        self.execution_grace_period_in_blocks().set(&new_value);
+    }
+
     #[view(getGovernanceTokenId)]
     #[storage_mapper("governanceTokenIds")]
     fn governance_token_ids(&self) -> SetMapper<TokenIdentifier>;
 
+    #[storage_mapper("weightProviderTokenIds")]
+    fn weight_provider_token_ids(&self) -> SetMapper<TokenIdentifier>;
+
     #[view(getQuorum)]
     #[storage_mapper("quorum")]
     fn quorum(&self) -> SingleValueMapper<BigUint>;
 
+    #[view(getQuorumPercentage)]
+    #[storage_mapper("quorumPercentage")]
+    fn quorum_percentage(&self) -> SingleValueMapper<u64>;
+
+    #[view(getCirculatingVotingSupply)]
+    #[storage_mapper("circulatingVotingSupply")]
+    fn circulating_voting_supply(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getMinApprovalRatio)]
+    #[storage_mapper("minApprovalRatio")]
+    fn min_approval_ratio(&self) -> SingleValueMapper<u64>;
+
     #[view(getMinWeightForProposal)]
     #[storage_mapper("minWeightForProposal")]
     fn min_weight_for_proposal(&self) -> SingleValueMapper<BigUint>;
@@ -138,6 +311,34 @@ pub trait Config {
     #[storage_mapper("votingPeriodInBlocks")]
     fn voting_period_in_blocks(&self) -> SingleValueMapper<u64>;
 
+    #[view(getCriticalQuorum)]
+    #[storage_mapper("criticalQuorum")]
+    fn critical_quorum(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getCriticalQuorumPercentage)]
+    #[storage_mapper("criticalQuorumPercentage")]
+    fn critical_quorum_percentage(&self) -> SingleValueMapper<u64>;
+
+    #[view(getCriticalVotingPeriodInBlocks)]
+    #[storage_mapper("criticalVotingPeriodInBlocks")]
+    fn critical_voting_period_in_blocks(&self) -> SingleValueMapper<u64>;
+
+    #[view(getTimelockDelayInBlocks)]
+    #[storage_mapper("timelockDelayInBlocks")]
+    fn timelock_delay_in_blocks(&self) -> SingleValueMapper<u64>;
+
+    #[view(getExecutionGracePeriodInBlocks)]
+    #[storage_mapper("executionGracePeriodInBlocks")]
+    fn execution_grace_period_in_blocks(&self) -> SingleValueMapper<u64>;
+
+    #[view(getProposalDepositAmount)]
+    #[storage_mapper("proposalDepositAmount")]
+    fn proposal_deposit_amount(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getGuardian)]
+    #[storage_mapper("guardian")]
+    fn guardian(&self) -> SingleValueMapper<ManagedAddress>;
+
     #[view(getProposal)]
     #[storage_mapper("proposal")]
     fn proposal(&self, id: u64) -> SingleValueMapper<Proposal<Self::Api>>;
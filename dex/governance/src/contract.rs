@@ -7,14 +7,21 @@ use proposal::ProposalCreationArgs;
 elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
+pub mod action_policy;
 pub mod config;
+pub mod delegation;
+pub mod deposit;
 pub mod errors;
 mod events;
 mod lib;
 pub mod proposal;
+pub mod snapshot_voting;
+pub mod treasury;
 mod validation;
 pub mod vote;
+pub mod weight_providers;
 
+use crate::config::{DEFAULT_EXECUTION_GRACE_PERIOD_IN_BLOCKS, DEFAULT_TIMELOCK_DELAY_IN_BLOCKS};
 use crate::errors::*;
 use crate::proposal::*;
 use crate::vote::*;
@@ -22,9 +29,17 @@ use crate::vote::*;
 #[elrond_wasm::contract]
 pub trait Governance:
     config::Config
+    + action_policy::ActionPolicyModule
+    + delegation::DelegationModule
+    + deposit::DepositModule
+    + treasury::TreasuryModule
     + validation::Validation
     + proposal::ProposalHelper
+    + snapshot_voting::SnapshotVotingModule
     + lib::Lib
+    + weight_providers::WeightProviderModule
+    + factory::attr_ex_helper::AttrExHelper
+    + factory::lock_time_weight::LockTimeWeightModule
     + vote::VoteHelper
     + events::Events
 {
@@ -48,22 +63,40 @@ pub trait Governance:
         self.try_change_voting_period_in_blocks(voting_period_in_blocks);
         self.try_change_min_weight_for_proposal(min_weight_for_proposal);
         self.try_change_price_providers(price_providers);
+
+        if self.timelock_delay_in_blocks().is_empty() {
+            self.try_change_timelock_delay_in_blocks(DEFAULT_TIMELOCK_DELAY_IN_BLOCKS);
+        }
+        if self.execution_grace_period_in_blocks().is_empty() {
+            self.try_change_execution_grace_period_in_blocks(
+                DEFAULT_EXECUTION_GRACE_PERIOD_IN_BLOCKS,
+            );
+        }
     }
 
     #[payable("*")]
     #[endpoint]
     fn propose(&self, args: ProposalCreationArgs<Self::Api>) -> u64 {
-        let payment = self.call_value().single_esdt();
+        let payments = self.call_value().all_esdt_transfers();
+        require!(!payments.is_empty(), INVALID_PROPOSAL_DEPOSIT);
+
+        let payment = payments.get(0);
         self.require_is_accepted_payment(&payment);
+        let deposit_amount = self.take_proposal_deposit(&payments);
 
         let vote_weight = self.get_vote_weight(&payment);
         let min_weight = self.min_weight_for_proposal().get();
         require!(vote_weight >= min_weight, NOT_ENOUGH_FUNDS_TO_PROPOSE);
 
+        self.require_treasury_covers_actions(&args.actions);
+        self.require_allowed_destinations(&args.actions);
+
         let mut proposal = self.new_proposal_from_args(args);
+        proposal.is_critical = self.has_critical_actions(&proposal.actions);
         self.proposal_id_counter().set(proposal.id + 1);
 
         proposal.num_upvotes = vote_weight.clone();
+        proposal.deposit_amount = deposit_amount;
         self.proposal(proposal.id).set(&proposal);
 
         let vote_nft = self.create_vote_nft(
@@ -92,14 +125,39 @@ pub trait Governance:
         self.vote(proposal_id, VoteType::DownVote)
     }
 
+    #[payable("*")]
     #[endpoint]
-    fn execute(&self, proposal_id: u64) {
+    fn abstain(&self, proposal_id: u64) {
+        self.vote(proposal_id, VoteType::Abstain)
+    }
+
+    #[endpoint]
+    fn queue(&self, proposal_id: u64) {
         require!(!self.proposal(proposal_id).is_empty(), PROPOSAL_NOT_FOUND);
         let mut proposal = self.proposal(proposal_id).get();
 
         let pstat = self.get_proposal_status(&proposal);
         require!(pstat == ProposalStatus::Succeeded, PROPOSAL_NOT_SUCCEEDED);
 
+        let current_block = self.blockchain().get_block_nonce();
+        proposal.eta_block = current_block + self.timelock_delay_in_blocks().get();
+        self.proposal(proposal_id).set(&proposal);
+
+        self.emit_queue_event(proposal);
+    }
+
+    #[endpoint]
+    fn execute(&self, proposal_id: u64) {
+        require!(!self.proposal(proposal_id).is_empty(), PROPOSAL_NOT_FOUND);
+        let mut proposal = self.proposal(proposal_id).get();
+
+        let pstat = self.get_proposal_status(&proposal);
+        require!(pstat == ProposalStatus::Queued, PROPOSAL_NOT_QUEUED);
+
+        let current_block = self.blockchain().get_block_nonce();
+        require!(current_block >= proposal.eta_block, TIMELOCK_NOT_ELAPSED);
+
+        self.spend_from_treasury(&proposal.actions);
         self.execute_proposal(&proposal);
         proposal.was_executed = true;
         self.proposal(proposal_id).set(&proposal);
@@ -107,6 +165,39 @@ pub trait Governance:
         self.emit_execute_event(proposal);
     }
 
+    /// The proposer or the guardian can cancel a pending proposal, and the guardian can also
+    /// cancel a queued one. A pending proposal cancelled by the guardian is treated as spam.
+    #[endpoint]
+    fn cancel(&self, proposal_id: u64) {
+        require!(!self.proposal(proposal_id).is_empty(), PROPOSAL_NOT_FOUND);
+        let mut proposal = self.proposal(proposal_id).get();
+
+        let caller = self.blockchain().get_caller();
+        let is_guardian = self.is_guardian(&caller);
+        require!(
+            is_guardian || caller == proposal.proposer,
+            INVALID_CALLER_NOT_PROPOSER_OR_GUARDIAN
+        );
+
+        let pstat = self.get_proposal_status(&proposal);
+        match pstat {
+            ProposalStatus::Pending => {
+                if is_guardian {
+                    self.slash_deposit(&mut proposal);
+                } else {
+                    self.return_deposit(&mut proposal);
+                }
+            }
+            ProposalStatus::Queued => self.require_caller_guardian(),
+            _ => sc_panic!(PROPOSAL_NOT_CANCELLABLE),
+        }
+
+        proposal.was_cancelled = true;
+        self.proposal(proposal_id).set(&proposal);
+
+        self.emit_cancel_event(proposal);
+    }
+
     fn vote(&self, proposal_id: u64, vote_type: VoteType) {
         require!(!self.proposal(proposal_id).is_empty(), PROPOSAL_NOT_FOUND);
         let mut proposal = self.proposal(proposal_id).get();
@@ -120,10 +211,7 @@ pub trait Governance:
         let vote_weight = self.get_vote_weight(&payment);
         require!(vote_weight != 0u64, ERROR_ZERO_VALUE);
 
-        match vote_type {
-            VoteType::Upvote => proposal.num_upvotes += &vote_weight,
-            VoteType::DownVote => proposal.num_downvotes += &vote_weight,
-        }
+        self.add_vote_weight(&mut proposal, &vote_type, &vote_weight);
 
         let vote_nft = self.create_vote_nft(
             proposal_id,
@@ -150,7 +238,12 @@ pub trait Governance:
         let pstat = self.get_proposal_status(&proposal);
 
         match pstat {
-            ProposalStatus::Succeeded | ProposalStatus::Defeated | ProposalStatus::Executed => {
+            ProposalStatus::Succeeded
+            | ProposalStatus::Defeated
+            | ProposalStatus::Executed
+            | ProposalStatus::Queued
+            | ProposalStatus::Expired
+            | ProposalStatus::Cancelled => {
                 self.send_back(attr.payment.clone());
                 self.burn_vote_nft(payment.clone());
             }
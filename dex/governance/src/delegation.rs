@@ -0,0 +1,274 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use crate::errors::*;
+use crate::proposal::ProposalStatus;
+use crate::vote::VoteType;
+use crate::{config, events, lib, proposal, validation};
+
+#[derive(TypeAbi, TopEncode, TopDecode, PartialEq, Debug)]
+pub struct Delegation<M: ManagedTypeApi> {
+    pub delegate: ManagedAddress<M>,
+    pub payment: EsdtTokenPayment<M>,
+    pub weight: BigUint<M>,
+    pub delegation_block: u64,
+    pub delegate_since_block: u64,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Debug, Clone)]
+pub struct DelegatedWeightCheckpoint<M: ManagedTypeApi> {
+    pub block: u64,
+    pub weight: BigUint<M>,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, PartialEq, Debug)]
+pub struct DelegateVote<M: ManagedTypeApi> {
+    pub vote_type: VoteType,
+    pub weight: BigUint<M>,
+}
+
+#[elrond_wasm::module]
+pub trait DelegationModule:
+    config::Config + validation::Validation + proposal::ProposalHelper + lib::Lib + events::Events
+{
+    #[payable("*")]
+    #[endpoint]
+    fn delegate(&self, delegate: ManagedAddress) {
+        let caller = self.blockchain().get_caller();
+        require!(caller != delegate, CANNOT_DELEGATE_TO_SELF);
+        require!(self.delegation(&caller).is_empty(), ALREADY_DELEGATING);
+
+        let payment = self.call_value().single_esdt();
+        self.require_is_accepted_payment(&payment);
+
+        let weight = self.get_vote_weight(&payment);
+        require!(weight != 0u64, ERROR_ZERO_VALUE);
+
+        let current_block = self.blockchain().get_block_nonce();
+        self.increase_delegated_weight(&delegate, &weight);
+        self.delegation(&caller).set(&Delegation {
+            delegate: delegate.clone(),
+            payment,
+            weight: weight.clone(),
+            delegation_block: current_block,
+            delegate_since_block: current_block,
+        });
+
+        self.delegate_event(caller, delegate, weight, current_block);
+    }
+
+    #[endpoint(changeDelegate)]
+    fn change_delegate(&self, new_delegate: ManagedAddress) {
+        let caller = self.blockchain().get_caller();
+        require!(caller != new_delegate, CANNOT_DELEGATE_TO_SELF);
+        require!(!self.delegation(&caller).is_empty(), NOT_DELEGATING);
+
+        let mut delegation = self.delegation(&caller).get();
+        require!(delegation.delegate != new_delegate, ALREADY_DELEGATING);
+
+        self.decrease_delegated_weight(&delegation.delegate, &delegation.weight);
+        self.increase_delegated_weight(&new_delegate, &delegation.weight);
+
+        let current_block = self.blockchain().get_block_nonce();
+        delegation.delegate = new_delegate.clone();
+        delegation.delegate_since_block = current_block;
+        self.delegation(&caller).set(&delegation);
+
+        self.delegate_event(caller, new_delegate, delegation.weight, current_block);
+    }
+
+    #[endpoint]
+    fn undelegate(&self) {
+        let caller = self.blockchain().get_caller();
+        require!(!self.delegation(&caller).is_empty(), NOT_DELEGATING);
+
+        let delegation = self.delegation(&caller).get();
+        self.require_delegation_not_in_use(&delegation);
+
+        self.decrease_delegated_weight(&delegation.delegate, &delegation.weight);
+        self.delegation(&caller).clear();
+        self.send_back(delegation.payment);
+
+        self.undelegate_event(
+            caller,
+            delegation.delegate,
+            delegation.weight,
+            self.blockchain().get_block_nonce(),
+        );
+    }
+
+    #[endpoint(voteAsDelegate)]
+    fn vote_as_delegate(&self, proposal_id: u64, vote_type: VoteType) {
+        let mut proposal = self.get_active_proposal(proposal_id);
+
+        let caller = self.blockchain().get_caller();
+        let delegate_vote_mapper = self.delegate_vote(proposal_id, &caller);
+        require!(delegate_vote_mapper.is_empty(), ALREADY_VOTED);
+
+        let snapshot_weight = self.get_delegated_weight_at(&caller, proposal.creation_block);
+        let overridden_weight = self.overridden_weight(proposal_id, &caller).get();
+        require!(snapshot_weight > overridden_weight, ERROR_ZERO_VALUE);
+
+        let weight = snapshot_weight - overridden_weight;
+        self.add_vote_weight(&mut proposal, &vote_type, &weight);
+        self.proposal(proposal_id).set(&proposal);
+
+        delegate_vote_mapper.set(&DelegateVote {
+            vote_type: vote_type.clone(),
+            weight: weight.clone(),
+        });
+
+        self.delegated_vote_event(caller, proposal_id, vote_type, weight);
+    }
+
+    #[endpoint(voteAsDelegator)]
+    fn vote_as_delegator(&self, proposal_id: u64, vote_type: VoteType) {
+        let mut proposal = self.get_active_proposal(proposal_id);
+
+        let caller = self.blockchain().get_caller();
+        require!(!self.delegation(&caller).is_empty(), NOT_DELEGATING);
+        require!(
+            !self.delegator_voted(proposal_id, &caller).get(),
+            ALREADY_VOTED
+        );
+
+        let delegation = self.delegation(&caller).get();
+        require!(
+            delegation.delegate_since_block < proposal.creation_block,
+            DELEGATION_CHANGED_AFTER_PROPOSAL
+        );
+
+        let delegate_vote_mapper = self.delegate_vote(proposal_id, &delegation.delegate);
+        if delegate_vote_mapper.is_empty() {
+            self.overridden_weight(proposal_id, &delegation.delegate)
+                .update(|weight| *weight += &delegation.weight);
+        } else {
+            let mut delegate_vote = delegate_vote_mapper.get();
+            self.remove_vote_weight(&mut proposal, &delegate_vote.vote_type, &delegation.weight);
+            delegate_vote.weight -= &delegation.weight;
+            delegate_vote_mapper.set(&delegate_vote);
+        }
+
+        self.add_vote_weight(&mut proposal, &vote_type, &delegation.weight);
+        self.proposal(proposal_id).set(&proposal);
+        self.delegator_voted(proposal_id, &caller).set(true);
+
+        self.delegated_vote_event(caller, proposal_id, vote_type, delegation.weight);
+    }
+
+    // Proposals are created in increasing block order, so walking back from the latest one
+    // until the longest voting period has passed covers every proposal that is not yet finished.
+    fn require_delegation_not_in_use(&self, delegation: &Delegation<Self::Api>) {
+        let voting_delay = self.voting_delay_in_blocks().get();
+        let longest_voting_period = core::cmp::max(
+            self.voting_period_in_blocks().get(),
+            self.critical_voting_period_in_blocks().get(),
+        );
+        let current_block = self.blockchain().get_block_nonce();
+
+        let proposals_count = self.proposal_id_counter().get();
+        for proposal_id in (0..proposals_count).rev() {
+            let proposal = self.proposal(proposal_id).get();
+            if proposal.creation_block + voting_delay + longest_voting_period <= current_block {
+                break;
+            }
+
+            let pstat = self.get_proposal_status(&proposal);
+            if pstat != ProposalStatus::Pending && pstat != ProposalStatus::Active {
+                continue;
+            }
+
+            require!(
+                delegation.delegation_block >= proposal.creation_block,
+                DELEGATION_IN_USE
+            );
+        }
+    }
+
+    fn increase_delegated_weight(&self, delegate: &ManagedAddress, amount: &BigUint) {
+        let new_weight = self.get_current_delegated_weight(delegate) + amount;
+        self.push_delegated_weight_checkpoint(delegate, new_weight);
+    }
+
+    fn decrease_delegated_weight(&self, delegate: &ManagedAddress, amount: &BigUint) {
+        let new_weight = self.get_current_delegated_weight(delegate) - amount;
+        self.push_delegated_weight_checkpoint(delegate, new_weight);
+    }
+
+    fn push_delegated_weight_checkpoint(&self, delegate: &ManagedAddress, weight: BigUint) {
+        let block = self.blockchain().get_block_nonce();
+        let mut checkpoints = self.delegated_weight_checkpoints(delegate);
+        let checkpoint = DelegatedWeightCheckpoint { block, weight };
+
+        let len = checkpoints.len();
+        if len > 0 && checkpoints.get(len).block == block {
+            checkpoints.set(len, &checkpoint);
+        } else {
+            checkpoints.push(&checkpoint);
+        }
+    }
+
+    #[view(getDelegatedWeight)]
+    fn get_current_delegated_weight(&self, delegate: &ManagedAddress) -> BigUint {
+        let checkpoints = self.delegated_weight_checkpoints(delegate);
+        if checkpoints.is_empty() {
+            return BigUint::zero();
+        }
+
+        checkpoints.get(checkpoints.len()).weight
+    }
+
+    /// Weight delegated to `delegate` before `block` started.
+    #[view(getDelegatedWeightAt)]
+    fn get_delegated_weight_at(&self, delegate: &ManagedAddress, block: u64) -> BigUint {
+        let checkpoints = self.delegated_weight_checkpoints(delegate);
+
+        let mut low = 1;
+        let mut high = checkpoints.len();
+        let mut result = BigUint::zero();
+        while low <= high {
+            let mid = (low + high) / 2;
+            let checkpoint = checkpoints.get(mid);
+            if checkpoint.block < block {
+                result = checkpoint.weight;
+                low = mid + 1;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        result
+    }
+
+    #[view(getDelegation)]
+    #[storage_mapper("delegation")]
+    fn delegation(&self, delegator: &ManagedAddress) -> SingleValueMapper<Delegation<Self::Api>>;
+
+    #[storage_mapper("delegatedWeightCheckpoints")]
+    fn delegated_weight_checkpoints(
+        &self,
+        delegate: &ManagedAddress,
+    ) -> VecMapper<DelegatedWeightCheckpoint<Self::Api>>;
+
+    #[view(getDelegateVote)]
+    #[storage_mapper("delegateVote")]
+    fn delegate_vote(
+        &self,
+        proposal_id: u64,
+        delegate: &ManagedAddress,
+    ) -> SingleValueMapper<DelegateVote<Self::Api>>;
+
+    #[storage_mapper("overriddenWeight")]
+    fn overridden_weight(
+        &self,
+        proposal_id: u64,
+        delegate: &ManagedAddress,
+    ) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("delegatorVoted")]
+    fn delegator_voted(
+        &self,
+        proposal_id: u64,
+        delegator: &ManagedAddress,
+    ) -> SingleValueMapper<bool>;
+}
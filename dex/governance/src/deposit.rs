@@ -0,0 +1,79 @@
+elrond_wasm::imports!();
+
+use crate::errors::*;
+use crate::proposal::{Proposal, ProposalStatus};
+use crate::{config, events, proposal, treasury};
+
+#[elrond_wasm::module]
+pub trait DepositModule:
+    config::Config + proposal::ProposalHelper + treasury::TreasuryModule + events::Events
+{
+    /// Returns the deposit to the proposer if the proposal reached quorum, slashes it otherwise.
+    #[endpoint(settleDeposit)]
+    fn settle_deposit(&self, proposal_id: u64) {
+        require!(!self.proposal(proposal_id).is_empty(), PROPOSAL_NOT_FOUND);
+        let mut proposal = self.proposal(proposal_id).get();
+        require!(proposal.deposit_amount != 0u64, NO_DEPOSIT_TO_SETTLE);
+
+        let pstat = self.get_proposal_status(&proposal);
+        require!(
+            pstat != ProposalStatus::Pending && pstat != ProposalStatus::Active,
+            VOTING_PERIOD_NOT_ENDED
+        );
+
+        if self.is_quorum_reached(&proposal) {
+            self.return_deposit(&mut proposal);
+        } else {
+            self.slash_deposit(&mut proposal);
+        }
+        self.proposal(proposal_id).set(&proposal);
+    }
+
+    /// The deposit is paid in MEX, as the second payment of `propose`.
+    fn take_proposal_deposit(&self, payments: &ManagedVec<EsdtTokenPayment<Self::Api>>) -> BigUint {
+        let deposit_amount = self.proposal_deposit_amount().get();
+        if deposit_amount == 0u64 {
+            require!(payments.len() == 1, INVALID_PROPOSAL_DEPOSIT);
+            return deposit_amount;
+        }
+
+        require!(payments.len() == 2, INVALID_PROPOSAL_DEPOSIT);
+        let deposit = payments.get(1);
+        require!(
+            deposit.token_identifier == self.mex_token_id().get()
+                && deposit.token_nonce == 0
+                && deposit.amount == deposit_amount,
+            INVALID_PROPOSAL_DEPOSIT
+        );
+
+        deposit_amount
+    }
+
+    fn return_deposit(&self, proposal: &mut Proposal<Self::Api>) {
+        let amount = core::mem::replace(&mut proposal.deposit_amount, BigUint::zero());
+        if amount == 0u64 {
+            return;
+        }
+
+        self.send()
+            .direct_esdt(&proposal.proposer, &self.mex_token_id().get(), 0, &amount);
+        self.deposit_returned_event(proposal.id, proposal.proposer.clone(), amount);
+    }
+
+    fn slash_deposit(&self, proposal: &mut Proposal<Self::Api>) {
+        let amount = core::mem::replace(&mut proposal.deposit_amount, BigUint::zero());
+        if amount == 0u64 {
+            return;
+        }
+
+        self.slashed_deposits()
+            .update(|slashed| *slashed += &amount);
+        self.treasury_balance(&self.mex_token_id().get(), 0)
+            .update(|balance| *balance += &amount);
+        self.deposit_slashed_event(proposal.id, proposal.proposer.clone(), amount);
+    }
+
+    #[view(getSlashedDeposits)]
+    #[storage_mapper("slashedDeposits")]
+    fn slashed_deposits(&self) -> SingleValueMapper<BigUint>;
+}
@@ -8,3 +8,24 @@ pub const VOTING_PERIOD_NOT_ENDED: &[u8] = b"voting period not ended";
 pub const ERROR_ZERO_VALUE: &[u8] = b"value cannot be zero";
 pub const INVALID_ESDT: &[u8] = b"invalid esdt";
 pub const INVALID_CALLER_NOT_SELF: &[u8] = b"caller should be self";
+pub const CANNOT_DELEGATE_TO_SELF: &[u8] = b"cannot delegate to self";
+pub const ALREADY_DELEGATING: &[u8] = b"already delegating";
+pub const NOT_DELEGATING: &[u8] = b"no active delegation";
+pub const DELEGATION_IN_USE: &[u8] = b"delegation used by an unfinished proposal";
+pub const DELEGATION_CHANGED_AFTER_PROPOSAL: &[u8] = b"delegation changed after proposal creation";
+pub const ALREADY_VOTED: &[u8] = b"already voted";
+pub const INVALID_PERCENTAGE: &[u8] = b"invalid percentage";
+pub const INVALID_CALLER_NOT_GUARDIAN: &[u8] = b"caller should be guardian";
+pub const PROPOSAL_NOT_QUEUED: &[u8] = b"proposal not queued";
+pub const TIMELOCK_NOT_ELAPSED: &[u8] = b"timelock not elapsed";
+pub const INVALID_PROPOSAL_DEPOSIT: &[u8] = b"invalid proposal deposit";
+pub const NO_DEPOSIT_TO_SETTLE: &[u8] = b"no deposit to settle";
+pub const PROPOSAL_NOT_CANCELLABLE: &[u8] = b"proposal cannot be cancelled";
+pub const INVALID_CALLER_NOT_PROPOSER_OR_GUARDIAN: &[u8] = b"caller should be proposer or guardian";
+pub const INVALID_ACTION_PAYMENTS: &[u8] = b"invalid action payments";
+pub const INSUFFICIENT_TREASURY_BALANCE: &[u8] = b"insufficient treasury balance";
+pub const INVALID_CALLER_NOT_OWNER_OR_SELF: &[u8] = b"caller should be owner or self";
+pub const INVALID_SNAPSHOT_SOURCE: &[u8] = b"invalid snapshot source";
+pub const SNAPSHOT_NOT_AVAILABLE: &[u8] = b"snapshot not available for proposal";
+pub const DESTINATION_NOT_ALLOWED: &[u8] = b"action destination not allowed";
+pub const CIRCULATING_SUPPLY_NOT_SET: &[u8] = b"circulating voting supply not set";
@@ -52,9 +52,37 @@ pub trait Events {
                     self.blockchain().get_block_nonce(),
                 );
             }
+            VoteType::Abstain => {
+                self.abstain_event(
+                    self.blockchain().get_caller(),
+                    proposal,
+                    payment,
+                    weight,
+                    self.blockchain().get_block_timestamp(),
+                    self.blockchain().get_block_nonce(),
+                );
+            }
         }
     }
 
+    fn emit_queue_event(&self, proposal: Proposal<Self::Api>) {
+        self.queue_event(
+            self.blockchain().get_caller(),
+            proposal,
+            self.blockchain().get_block_timestamp(),
+            self.blockchain().get_block_nonce(),
+        );
+    }
+
+    fn emit_cancel_event(&self, proposal: Proposal<Self::Api>) {
+        self.cancel_event(
+            self.blockchain().get_caller(),
+            proposal,
+            self.blockchain().get_block_timestamp(),
+            self.blockchain().get_block_nonce(),
+        );
+    }
+
     fn emit_execute_event(&self, proposal: Proposal<Self::Api>) {
         self.execute_event(
             self.blockchain().get_caller(),
@@ -113,6 +141,35 @@ pub trait Events {
         #[indexed] epoch: u64,
     );
 
+    #[event("abstain")]
+    fn abstain_event(
+        &self,
+        #[indexed] caller: ManagedAddress,
+        #[indexed] proposal: Proposal<Self::Api>,
+        #[indexed] payment: EsdtTokenPayment<Self::Api>,
+        #[indexed] weight: BigUint,
+        #[indexed] timestamp: u64,
+        #[indexed] epoch: u64,
+    );
+
+    #[event("queue")]
+    fn queue_event(
+        &self,
+        #[indexed] caller: ManagedAddress,
+        #[indexed] proposal: Proposal<Self::Api>,
+        #[indexed] timestamp: u64,
+        #[indexed] epoch: u64,
+    );
+
+    #[event("cancel")]
+    fn cancel_event(
+        &self,
+        #[indexed] caller: ManagedAddress,
+        #[indexed] proposal: Proposal<Self::Api>,
+        #[indexed] timestamp: u64,
+        #[indexed] epoch: u64,
+    );
+
     #[event("execute")]
     fn execute_event(
         &self,
@@ -132,4 +189,63 @@ pub trait Events {
         #[indexed] timestamp: u64,
         #[indexed] epoch: u64,
     );
+
+    #[event("delegate")]
+    fn delegate_event(
+        &self,
+        #[indexed] delegator: ManagedAddress,
+        #[indexed] delegate: ManagedAddress,
+        #[indexed] weight: BigUint,
+        #[indexed] block: u64,
+    );
+
+    #[event("undelegate")]
+    fn undelegate_event(
+        &self,
+        #[indexed] delegator: ManagedAddress,
+        #[indexed] delegate: ManagedAddress,
+        #[indexed] weight: BigUint,
+        #[indexed] block: u64,
+    );
+
+    #[event("delegatedVote")]
+    fn delegated_vote_event(
+        &self,
+        #[indexed] caller: ManagedAddress,
+        #[indexed] proposal_id: u64,
+        #[indexed] vote_type: VoteType,
+        #[indexed] weight: BigUint,
+    );
+
+    #[event("depositReturned")]
+    fn deposit_returned_event(
+        &self,
+        #[indexed] proposal_id: u64,
+        #[indexed] proposer: ManagedAddress,
+        #[indexed] amount: BigUint,
+    );
+
+    #[event("depositSlashed")]
+    fn deposit_slashed_event(
+        &self,
+        #[indexed] proposal_id: u64,
+        #[indexed] proposer: ManagedAddress,
+        #[indexed] amount: BigUint,
+    );
+
+    #[event("treasuryDeposit")]
+    fn treasury_deposit_event(
+        &self,
+        #[indexed] caller: ManagedAddress,
+        #[indexed] payments: ManagedVec<EsdtTokenPayment<Self::Api>>,
+    );
+
+    #[event("snapshotVote")]
+    fn snapshot_vote_event(
+        &self,
+        #[indexed] caller: ManagedAddress,
+        #[indexed] proposal_id: u64,
+        #[indexed] vote_type: VoteType,
+        #[indexed] weight: BigUint,
+    );
 }
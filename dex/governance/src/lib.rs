@@ -1,6 +1,7 @@
 elrond_wasm::imports!();
 
 use crate::config;
+use crate::weight_providers::{self, WeightProviderType};
 
 mod price_provider_proxy {
     elrond_wasm::imports!();
@@ -16,14 +17,44 @@ mod price_provider_proxy {
 }
 
 #[elrond_wasm::module]
-pub trait Lib: config::Config {
+pub trait Lib: config::Config + weight_providers::WeightProviderModule {
     fn get_vote_weight(&self, payment: &EsdtTokenPayment<Self::Api>) -> BigUint {
+        let sc_address = self.blockchain().get_sc_address();
+        self.get_position_weight(&sc_address, payment)
+    }
+
+    /// `holder` is the account holding the position, whose attributes are read.
+    fn get_position_weight(
+        &self,
+        holder: &ManagedAddress,
+        payment: &EsdtTokenPayment<Self::Api>,
+    ) -> BigUint {
         let mex_token_id = self.mex_token_id().get();
 
         if payment.token_identifier == self.mex_token_id().get() {
             return payment.amount.clone();
         }
 
+        let weight_provider_mapper = self.weight_provider(&payment.token_identifier);
+        if !weight_provider_mapper.is_empty() {
+            let provider = weight_provider_mapper.get();
+            return match provider.provider_type {
+                WeightProviderType::Farm => {
+                    let farming_tokens =
+                        self.get_farm_position_farming_tokens(&provider, holder, payment);
+                    self.get_position_weight(holder, &farming_tokens)
+                }
+                WeightProviderType::LockedAsset => {
+                    self.get_locked_asset_weight(&provider, holder, payment)
+                }
+                WeightProviderType::DualYield => {
+                    let lp_farm_position =
+                        self.get_dual_yield_lp_farm_position(&provider, holder, payment);
+                    self.get_position_weight(&provider.address, &lp_farm_position)
+                }
+            };
+        }
+
         if let Some(provider) = self.price_providers().get(&payment.token_identifier) {
             let call_result: MultiValue2<EsdtTokenPayment<Self::Api>, EsdtTokenPayment<Self::Api>> =
                 self.price_provider_proxy(provider)
@@ -1,8 +1,11 @@
 elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
-use crate::config;
+use elrond_wasm::elrond_codec::{NestedDecodeInput, TopDecodeInput};
+
+use crate::config::{self, MAX_PERCENTAGE};
 use crate::errors::*;
+use crate::vote::VoteType;
 
 #[derive(TypeAbi, TopEncode, TopDecode, PartialEq, Debug)]
 pub enum ProposalStatus {
@@ -11,24 +14,38 @@ pub enum ProposalStatus {
     Defeated,
     Succeeded,
     Executed,
+    Queued,
+    Expired,
+    Cancelled,
 }
 
 #[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem, TypeAbi)]
 pub struct Action<M: ManagedTypeApi> {
     pub gas_limit: u64,
     pub dest_address: ManagedAddress<M>,
-    pub payments: ManagedVec<M, ManagedBuffer<M>>,
+    pub payments: ManagedVec<M, EsdtTokenPayment<M>>,
     pub endpoint_name: ManagedBuffer<M>,
     pub arguments: ManagedVec<M, ManagedBuffer<M>>,
 }
 
+/// An action as shown to voters, with whether its destination is a contract.
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, PartialEq, Debug)]
+pub struct ActionDescription<M: ManagedTypeApi> {
+    pub dest_address: ManagedAddress<M>,
+    pub dest_is_smart_contract: bool,
+    pub endpoint_name: ManagedBuffer<M>,
+    pub arguments: ManagedVec<M, ManagedBuffer<M>>,
+    pub gas_limit: u64,
+    pub payments: ManagedVec<M, EsdtTokenPayment<M>>,
+}
+
 #[derive(TopEncode, TopDecode, TypeAbi)]
 pub struct ProposalCreationArgs<M: ManagedTypeApi> {
     pub description: ManagedBuffer<M>,
     pub actions: ManagedVec<M, Action<M>>,
 }
 
-#[derive(TopEncode, TopDecode, TypeAbi)]
+#[derive(TopEncode, TypeAbi)]
 pub struct Proposal<M: ManagedTypeApi> {
     pub id: u64,
     pub creation_block: u64,
@@ -40,6 +57,72 @@ pub struct Proposal<M: ManagedTypeApi> {
 
     pub num_upvotes: BigUint<M>,
     pub num_downvotes: BigUint<M>,
+    pub num_abstains: BigUint<M>,
+
+    pub eta_block: u64,
+    pub was_cancelled: bool,
+    pub deposit_amount: BigUint<M>,
+    pub creation_epoch: u64,
+    pub is_critical: bool,
+}
+
+/// Proposals stored by older versions of the contract miss the trailing fields,
+/// which are then decoded with their default values.
+impl<M: ManagedTypeApi> TopDecode for Proposal<M> {
+    fn top_decode<I>(input: I) -> Result<Self, DecodeError>
+    where
+        I: TopDecodeInput,
+    {
+        let mut buffer = input.into_nested_buffer();
+        let id = u64::dep_decode(&mut buffer)?;
+        let creation_block = u64::dep_decode(&mut buffer)?;
+        let proposer = ManagedAddress::dep_decode(&mut buffer)?;
+        let description = ManagedBuffer::dep_decode(&mut buffer)?;
+        let was_executed = bool::dep_decode(&mut buffer)?;
+        let actions = ManagedVec::dep_decode(&mut buffer)?;
+        let num_upvotes = BigUint::dep_decode(&mut buffer)?;
+        let num_downvotes = BigUint::dep_decode(&mut buffer)?;
+        let num_abstains = dep_decode_or_default(&mut buffer, BigUint::zero)?;
+        let eta_block = dep_decode_or_default(&mut buffer, u64::default)?;
+        let was_cancelled = dep_decode_or_default(&mut buffer, bool::default)?;
+        let deposit_amount = dep_decode_or_default(&mut buffer, BigUint::zero)?;
+        let creation_epoch = dep_decode_or_default(&mut buffer, u64::default)?;
+        let is_critical = dep_decode_or_default(&mut buffer, bool::default)?;
+
+        if !buffer.is_depleted() {
+            return Err(DecodeError::INPUT_TOO_LONG);
+        }
+
+        Ok(Proposal {
+            id,
+            creation_block,
+            proposer,
+            description,
+            was_executed,
+            actions,
+            num_upvotes,
+            num_downvotes,
+            num_abstains,
+            eta_block,
+            was_cancelled,
+            deposit_amount,
+            creation_epoch,
+            is_critical,
+        })
+    }
+}
+
+fn dep_decode_or_default<T, I, F>(buffer: &mut I, default: F) -> Result<T, DecodeError>
+where
+    T: NestedDecode,
+    I: NestedDecodeInput,
+    F: FnOnce() -> T,
+{
+    if buffer.is_depleted() {
+        Ok(default())
+    } else {
+        T::dep_decode(buffer)
+    }
 }
 
 #[elrond_wasm::module]
@@ -52,15 +135,37 @@ pub trait ProposalHelper: config::Config {
         self.get_proposal_status(&proposal)
     }
 
+    fn get_active_proposal(&self, proposal_id: u64) -> Proposal<Self::Api> {
+        require!(!self.proposal(proposal_id).is_empty(), PROPOSAL_NOT_FOUND);
+        let proposal = self.proposal(proposal_id).get();
+
+        let pstat = self.get_proposal_status(&proposal);
+        require!(pstat == ProposalStatus::Active, PROPOSAL_NOT_ACTIVE);
+
+        proposal
+    }
+
     fn get_proposal_status(&self, proposal: &Proposal<Self::Api>) -> ProposalStatus {
         if proposal.was_executed {
             return ProposalStatus::Executed;
         }
+        if proposal.was_cancelled {
+            return ProposalStatus::Cancelled;
+        }
 
         let current_block = self.blockchain().get_block_nonce();
+        if proposal.eta_block != 0 {
+            let grace_period = self.execution_grace_period_in_blocks().get();
+            if current_block >= proposal.eta_block + grace_period {
+                return ProposalStatus::Expired;
+            }
+
+            return ProposalStatus::Queued;
+        }
+
         let proposal_block = proposal.creation_block;
         let voting_delay = self.voting_delay_in_blocks().get();
-        let voting_period = self.voting_period_in_blocks().get();
+        let voting_period = self.get_voting_period(proposal);
 
         let voting_start = proposal_block + voting_delay;
         let voting_end = voting_start + voting_period;
@@ -72,14 +177,137 @@ pub trait ProposalHelper: config::Config {
             return ProposalStatus::Active;
         }
 
+        if self.is_quorum_reached(proposal) && self.is_approved(proposal) {
+            ProposalStatus::Succeeded
+        } else {
+            ProposalStatus::Defeated
+        }
+    }
+
+    /// Block from which a queued proposal can be executed, or 0 if it was not queued.
+    #[view(getProposalEta)]
+    fn get_proposal_eta(&self, proposal_id: u64) -> u64 {
+        require!(!self.proposal(proposal_id).is_empty(), PROPOSAL_NOT_FOUND);
+
+        self.proposal(proposal_id).get().eta_block
+    }
+
+    #[view(getProposalActions)]
+    fn get_proposal_actions(
+        &self,
+        proposal_id: u64,
+    ) -> MultiValueEncoded<ActionDescription<Self::Api>> {
+        require!(!self.proposal(proposal_id).is_empty(), PROPOSAL_NOT_FOUND);
+        let proposal = self.proposal(proposal_id).get();
+
+        let mut result = MultiValueEncoded::new();
+        for action in proposal.actions.iter() {
+            result.push(ActionDescription {
+                dest_is_smart_contract: self.blockchain().is_smart_contract(&action.dest_address),
+                dest_address: action.dest_address,
+                endpoint_name: action.endpoint_name,
+                arguments: action.arguments,
+                gas_limit: action.gas_limit,
+                payments: action.payments,
+            });
+        }
+
+        result
+    }
+
+    /// Abstain votes count toward the quorum, but not toward the outcome.
+    fn is_quorum_reached(&self, proposal: &Proposal<Self::Api>) -> bool {
+        let participation =
+            &proposal.num_upvotes + &proposal.num_downvotes + &proposal.num_abstains;
+
+        participation >= self.get_proposal_required_quorum(proposal)
+    }
+
+    fn is_approved(&self, proposal: &Proposal<Self::Api>) -> bool {
         let total_upvotes = &proposal.num_upvotes;
         let total_downvotes = &proposal.num_downvotes;
-        let quorum = self.quorum().get();
+        if total_upvotes <= total_downvotes {
+            return false;
+        }
 
-        if total_upvotes > total_downvotes && total_upvotes - total_downvotes >= quorum {
-            ProposalStatus::Succeeded
+        let min_approval_ratio = self.min_approval_ratio().get();
+        let decisive_votes = total_upvotes + total_downvotes;
+
+        BigUint::from(MAX_PERCENTAGE) * total_upvotes >= decisive_votes * min_approval_ratio
+    }
+
+    /// The percentage quorum takes precedence over the absolute one once configured.
+    /// The circulating voting supply is set through proposals, not tracked,
+    /// so it goes stale as tokens are minted, burned or locked, and must be kept up to date.
+    #[view(getRequiredQuorum)]
+    fn get_required_quorum(&self) -> BigUint {
+        let quorum_percentage = self.quorum_percentage().get();
+        let circulating_voting_supply = self.circulating_voting_supply().get();
+        if quorum_percentage == 0 || circulating_voting_supply == 0 {
+            return self.quorum().get();
+        }
+
+        circulating_voting_supply * quorum_percentage / MAX_PERCENTAGE
+    }
+
+    /// Critical proposals are never held to a lower quorum than the regular one.
+    fn get_proposal_required_quorum(&self, proposal: &Proposal<Self::Api>) -> BigUint {
+        let required_quorum = self.get_required_quorum();
+        if !proposal.is_critical {
+            return required_quorum;
+        }
+
+        let critical_quorum = self.get_required_critical_quorum();
+        if critical_quorum > required_quorum {
+            critical_quorum
         } else {
-            ProposalStatus::Defeated
+            required_quorum
+        }
+    }
+
+    #[view(getRequiredCriticalQuorum)]
+    fn get_required_critical_quorum(&self) -> BigUint {
+        let critical_quorum_percentage = self.critical_quorum_percentage().get();
+        let circulating_voting_supply = self.circulating_voting_supply().get();
+        if critical_quorum_percentage == 0 || circulating_voting_supply == 0 {
+            return self.critical_quorum().get();
+        }
+
+        circulating_voting_supply * critical_quorum_percentage / MAX_PERCENTAGE
+    }
+
+    fn get_voting_period(&self, proposal: &Proposal<Self::Api>) -> u64 {
+        let voting_period = self.voting_period_in_blocks().get();
+        if !proposal.is_critical {
+            return voting_period;
+        }
+
+        core::cmp::max(voting_period, self.critical_voting_period_in_blocks().get())
+    }
+
+    fn add_vote_weight(
+        &self,
+        proposal: &mut Proposal<Self::Api>,
+        vote_type: &VoteType,
+        weight: &BigUint,
+    ) {
+        match vote_type {
+            VoteType::Upvote => proposal.num_upvotes += weight,
+            VoteType::DownVote => proposal.num_downvotes += weight,
+            VoteType::Abstain => proposal.num_abstains += weight,
+        }
+    }
+
+    fn remove_vote_weight(
+        &self,
+        proposal: &mut Proposal<Self::Api>,
+        vote_type: &VoteType,
+        weight: &BigUint,
+    ) {
+        match vote_type {
+            VoteType::Upvote => proposal.num_upvotes -= weight,
+            VoteType::DownVote => proposal.num_downvotes -= weight,
+            VoteType::Abstain => proposal.num_abstains -= weight,
         }
     }
 
@@ -93,6 +321,12 @@ pub trait ProposalHelper: config::Config {
             actions: args.actions,
             num_upvotes: BigUint::zero(),
             num_downvotes: BigUint::zero(),
+            num_abstains: BigUint::zero(),
+            eta_block: 0,
+            was_cancelled: false,
+            deposit_amount: BigUint::zero(),
+            creation_epoch: self.blockchain().get_block_epoch(),
+            is_critical: false,
         }
     }
 
@@ -102,13 +336,36 @@ pub trait ProposalHelper: config::Config {
         }
     }
 
+    /// EGLD payments are always alone in an action, as checked at propose time.
     fn execute_action(&self, action: &Action<Self::Api>) -> Result<(), &'static [u8]> {
-        Self::Api::send_api_impl().transfer_value_execute(
+        let arg_buffer = ManagedArgBuffer::from(action.arguments.clone());
+        if action.payments.is_empty() {
+            return Self::Api::send_api_impl().transfer_value_execute(
+                &action.dest_address,
+                &BigUint::zero(),
+                action.gas_limit,
+                &action.endpoint_name,
+                &arg_buffer,
+            );
+        }
+
+        let first_payment = action.payments.get(0);
+        if first_payment.token_identifier.is_egld() {
+            return Self::Api::send_api_impl().transfer_value_execute(
+                &action.dest_address,
+                &first_payment.amount,
+                action.gas_limit,
+                &action.endpoint_name,
+                &arg_buffer,
+            );
+        }
+
+        Self::Api::send_api_impl().multi_transfer_esdt_nft_execute(
             &action.dest_address,
-            &BigUint::zero(),
+            &action.payments,
             action.gas_limit,
             &action.endpoint_name,
-            &ManagedArgBuffer::from(action.arguments.clone()),
+            &arg_buffer,
         )
     }
 }
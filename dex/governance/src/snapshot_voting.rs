@@ -0,0 +1,104 @@
+elrond_wasm::imports!();
+
+use crate::errors::*;
+use crate::proposal::Proposal;
+use crate::vote::VoteType;
+use crate::{config, events, proposal};
+
+mod snapshot_source_proxy {
+    elrond_wasm::imports!();
+
+    #[elrond_wasm::proxy]
+    pub trait SnapshotSource {
+        #[view(getStakeAtEpoch)]
+        fn get_stake_at_epoch(&self, user_address: ManagedAddress, epoch: u64) -> BigUint;
+    }
+}
+
+#[elrond_wasm::module]
+pub trait SnapshotVotingModule: config::Config + proposal::ProposalHelper + events::Events {
+    /// A snapshot source must expose `getStakeAtEpoch`, with the stake denominated in MEX.
+    /// As sources add voting power, they can only be added through proposals.
+    #[endpoint(addSnapshotSource)]
+    fn add_snapshot_source(&self, address: ManagedAddress) {
+        self.require_caller_self();
+        require!(
+            self.blockchain().is_smart_contract(&address),
+            INVALID_SNAPSHOT_SOURCE
+        );
+
+        self.snapshot_sources().insert(address);
+    }
+
+    #[endpoint(removeSnapshotSource)]
+    fn remove_snapshot_source(&self, address: ManagedAddress) {
+        self.require_caller_owner_or_self();
+        require!(
+            self.snapshot_sources().remove(&address),
+            INVALID_SNAPSHOT_SOURCE
+        );
+    }
+
+    /// Votes with the stake held in the snapshot sources, without transferring any tokens.
+    #[endpoint(voteWithSnapshot)]
+    fn vote_with_snapshot(&self, proposal_id: u64, vote_type: VoteType) {
+        let mut proposal = self.get_active_proposal(proposal_id);
+
+        let caller = self.blockchain().get_caller();
+        let snapshot_voted_mapper = self.snapshot_voted(proposal_id, &caller);
+        require!(!snapshot_voted_mapper.get(), ALREADY_VOTED);
+
+        let weight = self.get_snapshot_weight(&caller, &proposal);
+        require!(weight != 0u64, ERROR_ZERO_VALUE);
+
+        self.add_vote_weight(&mut proposal, &vote_type, &weight);
+        self.proposal(proposal_id).set(&proposal);
+        snapshot_voted_mapper.set(true);
+
+        self.snapshot_vote_event(caller, proposal_id, vote_type, weight);
+    }
+
+    #[view(getSnapshotVotingPower)]
+    fn get_snapshot_voting_power(&self, user_address: ManagedAddress, proposal_id: u64) -> BigUint {
+        require!(!self.proposal(proposal_id).is_empty(), PROPOSAL_NOT_FOUND);
+        let proposal = self.proposal(proposal_id).get();
+
+        self.get_snapshot_weight(&user_address, &proposal)
+    }
+
+    /// The snapshot is taken at the end of the epoch before the proposal's creation,
+    /// so stake changes made after the proposal was created are never counted.
+    fn get_snapshot_weight(
+        &self,
+        user_address: &ManagedAddress,
+        proposal: &Proposal<Self::Api>,
+    ) -> BigUint {
+        require!(proposal.creation_epoch > 0, SNAPSHOT_NOT_AVAILABLE);
+        let snapshot_epoch = proposal.creation_epoch - 1;
+
+        let mut weight = BigUint::zero();
+        for source in self.snapshot_sources().iter() {
+            let stake: BigUint = self
+                .snapshot_source_proxy(source)
+                .get_stake_at_epoch(user_address.clone(), snapshot_epoch)
+                .execute_on_dest_context();
+            weight += stake;
+        }
+
+        weight
+    }
+
+    #[proxy]
+    fn snapshot_source_proxy(&self, to: ManagedAddress) -> snapshot_source_proxy::Proxy<Self::Api>;
+
+    #[view(getSnapshotSources)]
+    #[storage_mapper("snapshotSources")]
+    fn snapshot_sources(&self) -> SetMapper<ManagedAddress>;
+
+    #[storage_mapper("snapshotVoted")]
+    fn snapshot_voted(
+        &self,
+        proposal_id: u64,
+        user_address: &ManagedAddress,
+    ) -> SingleValueMapper<bool>;
+}
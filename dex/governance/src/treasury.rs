@@ -0,0 +1,106 @@
+elrond_wasm::imports!();
+
+use crate::errors::*;
+use crate::proposal::Action;
+use crate::{config, events};
+
+#[elrond_wasm::module]
+pub trait TreasuryModule: config::Config + events::Events {
+    #[payable("*")]
+    #[endpoint(depositToTreasury)]
+    fn deposit_to_treasury(&self) {
+        let mut payments = self.call_value().all_esdt_transfers();
+        let egld_value = self.call_value().egld_value();
+        if egld_value > 0u64 {
+            payments.push(EsdtTokenPayment::new(
+                TokenIdentifier::egld(),
+                0,
+                egld_value,
+            ));
+        }
+        require!(!payments.is_empty(), ERROR_ZERO_VALUE);
+
+        for payment in payments.iter() {
+            self.treasury_balance(&payment.token_identifier, payment.token_nonce)
+                .update(|balance| *balance += &payment.amount);
+        }
+
+        self.treasury_deposit_event(self.blockchain().get_caller(), payments);
+    }
+
+    /// EGLD can only be sent alone, as the protocol does not allow mixing it with ESDT transfers.
+    fn require_valid_action_payments(&self, action: &Action<Self::Api>) {
+        for payment in action.payments.iter() {
+            require!(payment.amount != 0u64, ERROR_ZERO_VALUE);
+
+            if payment.token_identifier.is_egld() {
+                require!(
+                    action.payments.len() == 1 && payment.token_nonce == 0,
+                    INVALID_ACTION_PAYMENTS
+                );
+            } else {
+                require!(
+                    payment.token_identifier.is_valid_esdt_identifier(),
+                    INVALID_ESDT
+                );
+            }
+        }
+    }
+
+    fn require_treasury_covers_actions(&self, actions: &ManagedVec<Action<Self::Api>>) {
+        let mut total_payments = ManagedVec::<Self::Api, EsdtTokenPayment<Self::Api>>::new();
+        for action in actions.iter() {
+            self.require_valid_action_payments(&action);
+
+            for payment in action.payments.iter() {
+                self.merge_payment(&mut total_payments, payment);
+            }
+        }
+
+        for payment in total_payments.iter() {
+            let balance = self
+                .treasury_balance(&payment.token_identifier, payment.token_nonce)
+                .get();
+            require!(balance >= payment.amount, INSUFFICIENT_TREASURY_BALANCE);
+        }
+    }
+
+    fn spend_from_treasury(&self, actions: &ManagedVec<Action<Self::Api>>) {
+        for action in actions.iter() {
+            for payment in action.payments.iter() {
+                self.treasury_balance(&payment.token_identifier, payment.token_nonce)
+                    .update(|balance| {
+                        require!(*balance >= payment.amount, INSUFFICIENT_TREASURY_BALANCE);
+                        *balance -= &payment.amount;
+                    });
+            }
+        }
+    }
+
+    fn merge_payment(
+        &self,
+        total_payments: &mut ManagedVec<Self::Api, EsdtTokenPayment<Self::Api>>,
+        payment: EsdtTokenPayment<Self::Api>,
+    ) {
+        for i in 0..total_payments.len() {
+            let mut existing = total_payments.get(i);
+            if existing.token_identifier == payment.token_identifier
+                && existing.token_nonce == payment.token_nonce
+            {
+                existing.amount += &payment.amount;
+                let _ = total_payments.set(i, &existing);
+                return;
+            }
+        }
+
+        total_payments.push(payment);
+    }
+
+    #[view(getTreasuryBalance)]
+    #[storage_mapper("treasuryBalance")]
+    fn treasury_balance(
+        &self,
+        token_id: &TokenIdentifier,
+        token_nonce: u64,
+    ) -> SingleValueMapper<BigUint>;
+}
@@ -3,10 +3,12 @@ elrond_wasm::derive_imports!();
 
 use crate::config;
 
+/// New variants must only be appended, so that existing vote NFT attributes still decode.
 #[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Debug, Clone)]
 pub enum VoteType {
     Upvote = 1,
     DownVote = 2,
+    Abstain = 3,
 }
 
 #[derive(TypeAbi, TopEncode, TopDecode, PartialEq, Debug)]
@@ -0,0 +1,249 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use common_structs::{
+    FarmTokenAttributes, LockedAssetTokenAttributes, LockedAssetTokenAttributesEx,
+    UnlockMilestoneEx,
+};
+use factory::attr_ex_helper::ProxyTrait as _;
+use farm_staking_proxy::dual_yield_token::DualYieldTokenAttributes;
+use farm_staking_proxy::lp_farm_registry::LpFarmEntry;
+
+use crate::config;
+use crate::errors::*;
+
+mod farm_proxy {
+    elrond_wasm::imports!();
+
+    #[elrond_wasm::proxy]
+    pub trait Farm {
+        #[view(getFarmingTokenId)]
+        fn farming_token_id(&self) -> TokenIdentifier;
+    }
+}
+
+mod farm_staking_proxy_proxy {
+    elrond_wasm::imports!();
+
+    use farm_staking_proxy::lp_farm_registry::LpFarmEntry;
+
+    #[elrond_wasm::proxy]
+    pub trait FarmStakingProxy {
+        #[view(getLpFarmEntry)]
+        fn lp_farm_entry(&self, entry_id: u64) -> LpFarmEntry<Self::Api>;
+    }
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Debug, Clone)]
+pub enum WeightProviderType {
+    Farm,
+    LockedAsset,
+    DualYield,
+}
+
+/// `address` is the farm for farm tokens, the locked asset factory for locked asset tokens
+/// and the farm staking proxy for dual yield tokens.
+/// `extended_attributes_activation_nonce` is the factory's, and is 0 for the other providers.
+#[derive(TypeAbi, TopEncode, TopDecode, PartialEq, Debug)]
+pub struct WeightProvider<M: ManagedTypeApi> {
+    pub provider_type: WeightProviderType,
+    pub address: ManagedAddress<M>,
+    pub lock_time_weighting: bool,
+    pub extended_attributes_activation_nonce: u64,
+}
+
+#[elrond_wasm::module]
+pub trait WeightProviderModule:
+    config::Config
+    + factory::attr_ex_helper::AttrExHelper
+    + factory::lock_time_weight::LockTimeWeightModule
+{
+    #[endpoint(addFarmWeightProvider)]
+    fn add_farm_weight_provider(
+        &self,
+        farm_token_id: TokenIdentifier,
+        farm_address: ManagedAddress,
+    ) {
+        self.set_weight_provider(
+            farm_token_id,
+            WeightProvider {
+                provider_type: WeightProviderType::Farm,
+                address: farm_address,
+                lock_time_weighting: false,
+                extended_attributes_activation_nonce: 0,
+            },
+        );
+    }
+
+    /// The factory's extended attributes activation nonce is copied into the provider,
+    /// so the attributes are decoded the same way the factory does.
+    #[endpoint(addLockedAssetWeightProvider)]
+    fn add_locked_asset_weight_provider(
+        &self,
+        locked_asset_token_id: TokenIdentifier,
+        factory_address: ManagedAddress,
+        lock_time_weighting: bool,
+    ) {
+        let extended_attributes_activation_nonce: u64 = self
+            .locked_asset_factory_proxy(factory_address.clone())
+            .extended_attributes_activation_nonce()
+            .execute_on_dest_context();
+        self.set_weight_provider(
+            locked_asset_token_id,
+            WeightProvider {
+                provider_type: WeightProviderType::LockedAsset,
+                address: factory_address,
+                lock_time_weighting,
+                extended_attributes_activation_nonce,
+            },
+        );
+    }
+
+    #[endpoint(addDualYieldWeightProvider)]
+    fn add_dual_yield_weight_provider(
+        &self,
+        dual_yield_token_id: TokenIdentifier,
+        proxy_address: ManagedAddress,
+    ) {
+        self.set_weight_provider(
+            dual_yield_token_id,
+            WeightProvider {
+                provider_type: WeightProviderType::DualYield,
+                address: proxy_address,
+                lock_time_weighting: false,
+                extended_attributes_activation_nonce: 0,
+            },
+        );
+    }
+
+    #[endpoint(removeWeightProvider)]
+    fn remove_weight_provider(&self, token_id: TokenIdentifier) {
+        self.require_caller_owner_or_self();
+        require!(
+            !self.weight_provider(&token_id).is_empty(),
+            UNREGISTERED_TOKEN_ID
+        );
+
+        self.weight_provider(&token_id).clear();
+        self.weight_provider_token_ids().remove(&token_id);
+        self.governance_token_ids().remove(&token_id);
+    }
+
+    fn set_weight_provider(&self, token_id: TokenIdentifier, provider: WeightProvider<Self::Api>) {
+        self.require_caller_owner_or_self();
+        require!(token_id.is_valid_esdt_identifier(), INVALID_ESDT);
+        require!(!provider.address.is_zero(), ERROR_ZERO_VALUE);
+
+        self.weight_provider(&token_id).set(&provider);
+        self.weight_provider_token_ids().insert(token_id.clone());
+        self.governance_token_ids().insert(token_id);
+    }
+
+    /// The farming tokens behind a farm position, including the compounded rewards.
+    fn get_farm_position_farming_tokens(
+        &self,
+        provider: &WeightProvider<Self::Api>,
+        holder: &ManagedAddress,
+        payment: &EsdtTokenPayment<Self::Api>,
+    ) -> EsdtTokenPayment<Self::Api> {
+        let attributes: FarmTokenAttributes<Self::Api> = self
+            .blockchain()
+            .get_esdt_token_data(holder, &payment.token_identifier, payment.token_nonce)
+            .decode_attributes();
+        let farming_token_id: TokenIdentifier = self
+            .farm_proxy(provider.address.clone())
+            .farming_token_id()
+            .execute_on_dest_context();
+
+        let farming_amount = &payment.amount
+            * &(attributes.initial_farming_amount + attributes.compounded_reward)
+            / attributes.current_farm_amount;
+
+        EsdtTokenPayment::new(farming_token_id, 0, farming_amount)
+    }
+
+    /// The LP farm position behind a dual yield position, held by the farm staking proxy.
+    fn get_dual_yield_lp_farm_position(
+        &self,
+        provider: &WeightProvider<Self::Api>,
+        holder: &ManagedAddress,
+        payment: &EsdtTokenPayment<Self::Api>,
+    ) -> EsdtTokenPayment<Self::Api> {
+        let attributes: DualYieldTokenAttributes<Self::Api> = self
+            .blockchain()
+            .get_esdt_token_data(holder, &payment.token_identifier, payment.token_nonce)
+            .decode_attributes();
+        let lp_farm_entry: LpFarmEntry<Self::Api> = self
+            .farm_staking_proxy_proxy(provider.address.clone())
+            .lp_farm_entry(attributes.lp_farm_entry_id)
+            .execute_on_dest_context();
+
+        let lp_farm_token_amount = &payment.amount * &attributes.lp_farm_token_amount
+            / attributes.get_total_dual_yield_tokens_for_position();
+
+        EsdtTokenPayment::new(
+            lp_farm_entry.lp_farm_token_id,
+            attributes.lp_farm_token_nonce,
+            lp_farm_token_amount,
+        )
+    }
+
+    /// Locked MEX counts 1:1, or weighted by the remaining lock duration with lock-time weighting.
+    fn get_locked_asset_weight(
+        &self,
+        provider: &WeightProvider<Self::Api>,
+        holder: &ManagedAddress,
+        payment: &EsdtTokenPayment<Self::Api>,
+    ) -> BigUint {
+        if !provider.lock_time_weighting {
+            return payment.amount.clone();
+        }
+
+        let unlock_milestones = self.get_unlock_milestones(provider, holder, payment);
+        self.get_lock_time_weighted_amount(&payment.amount, &unlock_milestones)
+    }
+
+    /// Tokens created before the factory's extended attributes activation nonce
+    /// have the legacy attributes, with their percents out of 100.
+    fn get_unlock_milestones(
+        &self,
+        provider: &WeightProvider<Self::Api>,
+        holder: &ManagedAddress,
+        payment: &EsdtTokenPayment<Self::Api>,
+    ) -> ManagedVec<UnlockMilestoneEx> {
+        let token_data = self.blockchain().get_esdt_token_data(
+            holder,
+            &payment.token_identifier,
+            payment.token_nonce,
+        );
+
+        let attributes = if payment.token_nonce < provider.extended_attributes_activation_nonce {
+            let legacy_attributes: LockedAssetTokenAttributes<Self::Api> =
+                token_data.decode_attributes();
+            self.convert_attr_to_attr_ex(&legacy_attributes)
+        } else {
+            token_data.decode_attributes::<LockedAssetTokenAttributesEx<Self::Api>>()
+        };
+
+        attributes.unlock_schedule.unlock_milestones
+    }
+
+    #[proxy]
+    fn locked_asset_factory_proxy(&self, to: ManagedAddress) -> factory::Proxy<Self::Api>;
+
+    #[proxy]
+    fn farm_proxy(&self, to: ManagedAddress) -> farm_proxy::Proxy<Self::Api>;
+
+    #[proxy]
+    fn farm_staking_proxy_proxy(
+        &self,
+        to: ManagedAddress,
+    ) -> farm_staking_proxy_proxy::Proxy<Self::Api>;
+
+    #[view(getWeightProvider)]
+    #[storage_mapper("weightProvider")]
+    fn weight_provider(
+        &self,
+        token_id: &TokenIdentifier,
+    ) -> SingleValueMapper<WeightProvider<Self::Api>>;
+}
@@ -0,0 +1,241 @@
+use common_structs::UnlockMilestone;
+use elrond_wasm::api::{ErrorApi, ErrorApiImpl};
+use elrond_wasm::elrond_codec::multi_types::{MultiValue2, OptionalValue};
+use elrond_wasm::types::{Address, EsdtLocalRole, ManagedVec, MultiValueEncoded};
+use elrond_wasm_debug::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, testing_framework::*,
+    DebugApi,
+};
+
+use factory::LockedAssetFactory;
+use governance::config::Config;
+use governance::proposal::ProposalHelper;
+use governance::treasury::TreasuryModule;
+use governance::*;
+use pair_mock::*;
+
+pub const GOVERNANCE_WASM_PATH: &'static str = "governance/output/governance.wasm";
+pub const PAIR_MOCK_WASM_PATH: &'static str = "pair-mock/output/pair-mock.wasm";
+pub const METABONDING_STAKING_WASM_PATH: &'static str =
+    "metabonding-staking/output/metabonding-staking.wasm";
+pub const FACTORY_WASM_PATH: &'static str = "factory/output/factory.wasm";
+pub const VOTE_NFT_ID: &[u8] = b"VOTE-abcdef";
+pub const FAKE_TOKEN_ID: &[u8] = b"FAKE-abcdef";
+pub const MEX_TOKEN_ID: &[u8] = b"MEX-abcdef";
+pub const LPMEX_TOKEN_ID: &[u8] = b"LPMEX-abcdef";
+pub const WUSDC_TOKEN_ID: &[u8] = b"WUSDC-abcdef";
+pub const LKMEX_TOKEN_ID: &[u8] = b"LKMEX-abcdef";
+pub const OTHER_LKMEX_TOKEN_ID: &[u8] = b"LKMEXB-abcdef";
+pub const USER_TOTAL_MEX_TOKENS: u64 = 5_000_000_000;
+pub const QUORUM: u64 = 1_000_000_000;
+pub const VOTING_DELAY_IN_BLOCKS: u64 = 1;
+pub const VOTING_PERIOD_IN_BLOCKS: u64 = 1;
+pub const MIN_WEIGHT_FOR_PROPOSAL: u64 = 1_000_000;
+pub const DRY_RUN_REVERTED: &[u8] = b"dry run reverted";
+
+pub struct GovernanceSetup<GovernanceObjBuilder, PairMockObjBuilder>
+where
+    GovernanceObjBuilder: 'static + Copy + Fn() -> governance::ContractObj<DebugApi>,
+    PairMockObjBuilder: 'static + Copy + Fn() -> pair_mock::ContractObj<DebugApi>,
+{
+    pub blockchain_wrapper: BlockchainStateWrapper,
+    pub owner_address: Address,
+    pub user_address: Address,
+    pub gov_wrapper: ContractObjWrapper<governance::ContractObj<DebugApi>, GovernanceObjBuilder>,
+    pub pair_wrapper: ContractObjWrapper<pair_mock::ContractObj<DebugApi>, PairMockObjBuilder>,
+}
+
+pub fn setup_gov<GovernanceObjBuilder, PairMockObjBuilder>(
+    gov_builder: GovernanceObjBuilder,
+    pair_builder: PairMockObjBuilder,
+) -> GovernanceSetup<GovernanceObjBuilder, PairMockObjBuilder>
+where
+    GovernanceObjBuilder: 'static + Copy + Fn() -> governance::ContractObj<DebugApi>,
+    PairMockObjBuilder: 'static + Copy + Fn() -> pair_mock::ContractObj<DebugApi>,
+{
+    let rust_zero = rust_biguint!(0u64);
+    let mut blockchain_wrapper = BlockchainStateWrapper::new();
+    let owner_addr = blockchain_wrapper.create_user_account(&rust_zero);
+    let gov_wrapper = blockchain_wrapper.create_sc_account(
+        &rust_zero,
+        Some(&owner_addr),
+        gov_builder,
+        GOVERNANCE_WASM_PATH,
+    );
+
+    let pair_wrapper = blockchain_wrapper.create_sc_account(
+        &rust_zero,
+        Some(&owner_addr),
+        pair_builder,
+        PAIR_MOCK_WASM_PATH,
+    );
+
+    // init DEX mock
+    blockchain_wrapper
+        .execute_tx(&owner_addr, &pair_wrapper, &rust_zero, |sc| {
+            sc.init(
+                OptionalValue::Some(managed_token_id!(MEX_TOKEN_ID)),
+                OptionalValue::Some(managed_token_id!(WUSDC_TOKEN_ID)),
+                OptionalValue::None,
+                OptionalValue::None,
+                OptionalValue::None,
+                OptionalValue::None,
+            );
+        })
+        .assert_ok();
+
+    blockchain_wrapper
+        .execute_tx(&owner_addr, &gov_wrapper, &rust_zero, |sc| {
+            let mut price_providers = MultiValueEncoded::new();
+            price_providers.push(MultiValue2::from((
+                managed_token_id!(LPMEX_TOKEN_ID),
+                managed_address!(pair_wrapper.address_ref()),
+            )));
+
+            sc.init(
+                managed_biguint!(QUORUM),
+                VOTING_DELAY_IN_BLOCKS,
+                VOTING_PERIOD_IN_BLOCKS,
+                managed_token_id!(VOTE_NFT_ID),
+                managed_token_id!(MEX_TOKEN_ID),
+                managed_biguint!(MIN_WEIGHT_FOR_PROPOSAL),
+                ManagedVec::from(vec![
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_token_id!(LPMEX_TOKEN_ID),
+                ]),
+                price_providers,
+            );
+        })
+        .assert_ok();
+
+    let vote_nft_roles = [
+        EsdtLocalRole::NftCreate,
+        EsdtLocalRole::NftBurn,
+        EsdtLocalRole::NftUpdateAttributes,
+    ];
+    blockchain_wrapper.set_esdt_local_roles(
+        gov_wrapper.address_ref(),
+        VOTE_NFT_ID,
+        &vote_nft_roles[..],
+    );
+
+    let user_addr = blockchain_wrapper.create_user_account(&rust_biguint!(100_000_000));
+    blockchain_wrapper.set_esdt_balance(
+        &user_addr,
+        MEX_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_MEX_TOKENS),
+    );
+    blockchain_wrapper.set_esdt_balance(
+        &owner_addr,
+        MEX_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_MEX_TOKENS),
+    );
+    blockchain_wrapper.set_esdt_balance(
+        &user_addr,
+        LPMEX_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_MEX_TOKENS),
+    );
+    blockchain_wrapper.set_esdt_balance(
+        &owner_addr,
+        LPMEX_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_MEX_TOKENS),
+    );
+    blockchain_wrapper.set_esdt_balance(
+        &user_addr,
+        FAKE_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_MEX_TOKENS),
+    );
+    blockchain_wrapper.set_esdt_balance(
+        &owner_addr,
+        FAKE_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_MEX_TOKENS),
+    );
+
+    GovernanceSetup {
+        blockchain_wrapper,
+        owner_address: owner_addr,
+        user_address: user_addr,
+        gov_wrapper,
+        pair_wrapper,
+    }
+}
+
+/// Runs the actions of a proposal from the governance contract, the same way `execute` does,
+/// and returns the outcome of each action. The blockchain state cannot be cloned, so every run
+/// ends with a failing transaction instead, whose changes are discarded. Action `i` runs after
+/// the treasury is charged for all the actions, as in `execute`, and after actions `0..i`.
+/// The actions following a failed one are not run, so a proposal the treasury cannot pay for
+/// only reports its first action, as failed.
+pub fn dry_run_proposal_actions<GovernanceObjBuilder>(
+    blockchain_wrapper: &mut BlockchainStateWrapper,
+    gov_wrapper: &ContractObjWrapper<governance::ContractObj<DebugApi>, GovernanceObjBuilder>,
+    proposal_id: u64,
+) -> Vec<Result<(), String>>
+where
+    GovernanceObjBuilder: 'static + Copy + Fn() -> governance::ContractObj<DebugApi>,
+{
+    let mut num_actions = 0;
+    blockchain_wrapper
+        .execute_query(gov_wrapper, |sc| {
+            num_actions = sc.proposal(proposal_id).get().actions.len();
+        })
+        .assert_ok();
+
+    let gov_address = gov_wrapper.address_ref().clone();
+    let mut results = Vec::new();
+    for action_index in 0..num_actions {
+        let tx_result =
+            blockchain_wrapper.execute_tx(&gov_address, gov_wrapper, &rust_biguint!(0), |sc| {
+                let proposal = sc.proposal(proposal_id).get();
+                sc.spend_from_treasury(&proposal.actions);
+                for i in 0..=action_index {
+                    if let Err(message) = sc.execute_action(&proposal.actions.get(i)) {
+                        DebugApi::error_api_impl().signal_error(message);
+                    }
+                }
+
+                DebugApi::error_api_impl().signal_error(DRY_RUN_REVERTED);
+            });
+
+        if tx_result.result_message.as_bytes() == DRY_RUN_REVERTED {
+            results.push(Ok(()));
+        } else {
+            results.push(Err(tx_result.result_message));
+            break;
+        }
+    }
+
+    results
+}
+
+pub fn setup_locked_asset_factory<GovernanceObjBuilder, PairMockObjBuilder>(
+    gov_setup: &mut GovernanceSetup<GovernanceObjBuilder, PairMockObjBuilder>,
+) -> Address
+where
+    GovernanceObjBuilder: 'static + Copy + Fn() -> governance::ContractObj<DebugApi>,
+    PairMockObjBuilder: 'static + Copy + Fn() -> pair_mock::ContractObj<DebugApi>,
+{
+    let factory_wrapper = gov_setup.blockchain_wrapper.create_sc_account(
+        &rust_biguint!(0),
+        Some(&gov_setup.owner_address),
+        factory::contract_obj,
+        FACTORY_WASM_PATH,
+    );
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &factory_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let unlock_milestones = ManagedVec::from_single_item(UnlockMilestone {
+                    unlock_epoch: 5,
+                    unlock_percent: 100,
+                });
+                sc.init(managed_token_id!(MEX_TOKEN_ID), unlock_milestones.into());
+            },
+        )
+        .assert_ok();
+
+    factory_wrapper.address_ref().clone()
+}
@@ -1,158 +1,28 @@
-use elrond_wasm::elrond_codec::multi_types::{MultiValue2, OptionalValue};
-use elrond_wasm::types::MultiValueEncoded;
-use elrond_wasm::types::{Address, EsdtLocalRole, EsdtTokenPayment, ManagedVec};
+pub mod gov_setup;
+
+use common_structs::{
+    LockedAssetTokenAttributes, LockedAssetTokenAttributesEx, UnlockMilestone, UnlockMilestoneEx,
+    UnlockSchedule, UnlockScheduleEx,
+};
+use elrond_wasm::types::{Address, EsdtTokenPayment, ManagedVec};
 use elrond_wasm_debug::{
     managed_address, managed_biguint, managed_buffer, managed_token_id, rust_biguint,
-    testing_framework::*, DebugApi,
+    tx_mock::TxInputESDT, DebugApi,
 };
 
+use governance::action_policy::*;
 use governance::config::*;
+use governance::delegation::*;
 use governance::errors::*;
 use governance::proposal::*;
+use governance::snapshot_voting::*;
+use governance::treasury::*;
 use governance::vote::*;
+use governance::weight_providers::*;
 use governance::*;
 
-use pair_mock::*;
-
-pub const GOVERNANCE_WASM_PATH: &'static str = "governance/output/governance.wasm";
-pub const PAIR_MOCK_WASM_PATH: &'static str = "pair-mock/output/pair-mock.wasm";
-pub const VOTE_NFT_ID: &[u8] = b"VOTE-abcdef";
-pub const FAKE_TOKEN_ID: &[u8] = b"FAKE-abcdef";
-pub const MEX_TOKEN_ID: &[u8] = b"MEX-abcdef";
-pub const LPMEX_TOKEN_ID: &[u8] = b"LPMEX-abcdef";
-pub const WUSDC_TOKEN_ID: &[u8] = b"WUSDC-abcdef";
-pub const USER_TOTAL_MEX_TOKENS: u64 = 5_000_000_000;
-pub const QUORUM: u64 = 1_000_000_000;
-pub const VOTING_DELAY_IN_BLOCKS: u64 = 1;
-pub const VOTING_PERIOD_IN_BLOCKS: u64 = 1;
-pub const MIN_WEIGHT_FOR_PROPOSAL: u64 = 1_000_000;
-
-pub struct GovernanceSetup<GovernanceObjBuilder, PairMockObjBuilder>
-where
-    GovernanceObjBuilder: 'static + Copy + Fn() -> governance::ContractObj<DebugApi>,
-    PairMockObjBuilder: 'static + Copy + Fn() -> pair_mock::ContractObj<DebugApi>,
-{
-    pub blockchain_wrapper: BlockchainStateWrapper,
-    pub owner_address: Address,
-    pub user_address: Address,
-    pub gov_wrapper: ContractObjWrapper<governance::ContractObj<DebugApi>, GovernanceObjBuilder>,
-    pub pair_wrapper: ContractObjWrapper<pair_mock::ContractObj<DebugApi>, PairMockObjBuilder>,
-}
-
-pub fn setup_gov<GovernanceObjBuilder, PairMockObjBuilder>(
-    gov_builder: GovernanceObjBuilder,
-    pair_builder: PairMockObjBuilder,
-) -> GovernanceSetup<GovernanceObjBuilder, PairMockObjBuilder>
-where
-    GovernanceObjBuilder: 'static + Copy + Fn() -> governance::ContractObj<DebugApi>,
-    PairMockObjBuilder: 'static + Copy + Fn() -> pair_mock::ContractObj<DebugApi>,
-{
-    let rust_zero = rust_biguint!(0u64);
-    let mut blockchain_wrapper = BlockchainStateWrapper::new();
-    let owner_addr = blockchain_wrapper.create_user_account(&rust_zero);
-    let gov_wrapper = blockchain_wrapper.create_sc_account(
-        &rust_zero,
-        Some(&owner_addr),
-        gov_builder,
-        GOVERNANCE_WASM_PATH,
-    );
-
-    let pair_wrapper = blockchain_wrapper.create_sc_account(
-        &rust_zero,
-        Some(&owner_addr),
-        pair_builder,
-        PAIR_MOCK_WASM_PATH,
-    );
-
-    // init DEX mock
-    blockchain_wrapper
-        .execute_tx(&owner_addr, &pair_wrapper, &rust_zero, |sc| {
-            sc.init(
-                OptionalValue::Some(managed_token_id!(MEX_TOKEN_ID)),
-                OptionalValue::Some(managed_token_id!(WUSDC_TOKEN_ID)),
-                OptionalValue::None,
-                OptionalValue::None,
-                OptionalValue::None,
-                OptionalValue::None,
-            );
-        })
-        .assert_ok();
-
-    blockchain_wrapper
-        .execute_tx(&owner_addr, &gov_wrapper, &rust_zero, |sc| {
-            let mut price_providers = MultiValueEncoded::new();
-            price_providers.push(MultiValue2::from((
-                managed_token_id!(LPMEX_TOKEN_ID),
-                managed_address!(pair_wrapper.address_ref()),
-            )));
-
-            sc.init(
-                managed_biguint!(QUORUM),
-                VOTING_DELAY_IN_BLOCKS,
-                VOTING_PERIOD_IN_BLOCKS,
-                managed_token_id!(VOTE_NFT_ID),
-                managed_token_id!(MEX_TOKEN_ID),
-                managed_biguint!(MIN_WEIGHT_FOR_PROPOSAL),
-                ManagedVec::from(vec![
-                    managed_token_id!(MEX_TOKEN_ID),
-                    managed_token_id!(LPMEX_TOKEN_ID),
-                ]),
-                price_providers,
-            );
-        })
-        .assert_ok();
-
-    let vote_nft_roles = [
-        EsdtLocalRole::NftCreate,
-        EsdtLocalRole::NftBurn,
-        EsdtLocalRole::NftUpdateAttributes,
-    ];
-    blockchain_wrapper.set_esdt_local_roles(
-        gov_wrapper.address_ref(),
-        VOTE_NFT_ID,
-        &vote_nft_roles[..],
-    );
-
-    let user_addr = blockchain_wrapper.create_user_account(&rust_biguint!(100_000_000));
-    blockchain_wrapper.set_esdt_balance(
-        &user_addr,
-        MEX_TOKEN_ID,
-        &rust_biguint!(USER_TOTAL_MEX_TOKENS),
-    );
-    blockchain_wrapper.set_esdt_balance(
-        &owner_addr,
-        MEX_TOKEN_ID,
-        &rust_biguint!(USER_TOTAL_MEX_TOKENS),
-    );
-    blockchain_wrapper.set_esdt_balance(
-        &user_addr,
-        LPMEX_TOKEN_ID,
-        &rust_biguint!(USER_TOTAL_MEX_TOKENS),
-    );
-    blockchain_wrapper.set_esdt_balance(
-        &owner_addr,
-        LPMEX_TOKEN_ID,
-        &rust_biguint!(USER_TOTAL_MEX_TOKENS),
-    );
-    blockchain_wrapper.set_esdt_balance(
-        &user_addr,
-        FAKE_TOKEN_ID,
-        &rust_biguint!(USER_TOTAL_MEX_TOKENS),
-    );
-    blockchain_wrapper.set_esdt_balance(
-        &owner_addr,
-        FAKE_TOKEN_ID,
-        &rust_biguint!(USER_TOTAL_MEX_TOKENS),
-    );
-
-    GovernanceSetup {
-        blockchain_wrapper,
-        owner_address: owner_addr,
-        user_address: user_addr,
-        gov_wrapper,
-        pair_wrapper,
-    }
-}
+use gov_setup::*;
+use metabonding_staking::MetabondingStaking;
 
 #[test]
 fn test_gov_setup() {
@@ -288,11 +158,17 @@ fn test_proposal_status_change() {
                 let dummy_proposal = Proposal::<DebugApi> {
                     actions: ManagedVec::from(Vec::<Action<DebugApi>>::new()),
                     creation_block: 0,
+                    creation_epoch: 0,
+                    deposit_amount: managed_biguint!(0),
                     description: managed_buffer!(&[]),
+                    eta_block: 0,
                     id: 0,
+                    is_critical: false,
+                    num_abstains: managed_biguint!(0),
                     num_downvotes: managed_biguint!(0),
                     num_upvotes: managed_biguint!(0),
                     proposer: managed_address!(&Address::zero()),
+                    was_cancelled: false,
                     was_executed: false,
                 };
 
@@ -343,11 +219,17 @@ fn test_proposal_status_change() {
                 let dummy_proposal = Proposal::<DebugApi> {
                     actions: ManagedVec::from(Vec::<Action<DebugApi>>::new()),
                     creation_block: 0,
+                    creation_epoch: 0,
+                    deposit_amount: managed_biguint!(0),
                     description: managed_buffer!(&[]),
+                    eta_block: 0,
                     id: 0,
+                    is_critical: false,
+                    num_abstains: managed_biguint!(0),
                     num_downvotes: managed_biguint!(0),
                     num_upvotes: managed_biguint!(QUORUM),
                     proposer: managed_address!(&Address::zero()),
+                    was_cancelled: false,
                     was_executed: false,
                 };
 
@@ -364,6 +246,54 @@ fn test_proposal_status_change() {
         })
         .assert_ok();
 
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.execute(0);
+            },
+        )
+        .assert_user_error(&String::from_utf8(PROPOSAL_NOT_QUEUED.to_vec()).unwrap());
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.queue(0);
+            },
+        )
+        .assert_ok();
+
+    let eta_block =
+        VOTING_DELAY_IN_BLOCKS + VOTING_PERIOD_IN_BLOCKS + DEFAULT_TIMELOCK_DELAY_IN_BLOCKS;
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            assert_eq!(ProposalStatus::Queued, sc.get_proposal_status_view(0));
+            assert_eq!(eta_block, sc.get_proposal_eta(0));
+        })
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.execute(0);
+            },
+        )
+        .assert_user_error(&String::from_utf8(TIMELOCK_NOT_ELAPSED.to_vec()).unwrap());
+
+    gov_setup.blockchain_wrapper.set_block_nonce(eta_block);
+
     gov_setup
         .blockchain_wrapper
         .execute_tx(
@@ -462,11 +392,17 @@ fn test_vote() {
                 let dummy_proposal = Proposal::<DebugApi> {
                     actions: ManagedVec::from(Vec::<Action<DebugApi>>::new()),
                     creation_block: 0,
+                    creation_epoch: 0,
+                    deposit_amount: managed_biguint!(0),
                     description: managed_buffer!(&[]),
+                    eta_block: 0,
                     id: 0,
+                    is_critical: false,
+                    num_abstains: managed_biguint!(0),
                     num_downvotes: managed_biguint!(0),
                     num_upvotes: managed_biguint!(0),
                     proposer: managed_address!(&Address::zero()),
+                    was_cancelled: false,
                     was_executed: false,
                 };
 
@@ -676,3 +612,1573 @@ fn test_basic_propose_with_lpmex() {
         })
         .assert_ok();
 }
+
+#[test]
+fn test_vote_delegation() {
+    let mut gov_setup = setup_gov(governance::contract_obj, pair_mock::contract_obj);
+    let delegate_address = gov_setup
+        .blockchain_wrapper
+        .create_user_account(&rust_biguint!(0));
+    let user_delegated_amount = 2_000_000_000u64;
+    let owner_delegated_amount = 1_000_000_000u64;
+
+    gov_setup.blockchain_wrapper.set_block_nonce(1);
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(user_delegated_amount),
+            |sc| {
+                sc.delegate(managed_address!(&delegate_address));
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(owner_delegated_amount),
+            |sc| {
+                sc.delegate(managed_address!(&delegate_address));
+            },
+        )
+        .assert_ok();
+
+    gov_setup.blockchain_wrapper.set_block_nonce(2);
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(MIN_WEIGHT_FOR_PROPOSAL),
+            |sc| {
+                sc.propose(ProposalCreationArgs {
+                    description: managed_buffer!(&b""[..]),
+                    actions: ManagedVec::from(Vec::<Action<DebugApi>>::new()),
+                });
+            },
+        )
+        .assert_ok();
+
+    // Delegated tokens stay locked while the proposal is not finished
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.undelegate();
+            },
+        )
+        .assert_user_error(&String::from_utf8(DELEGATION_IN_USE.to_vec()).unwrap());
+
+    gov_setup
+        .blockchain_wrapper
+        .set_block_nonce(2 + VOTING_DELAY_IN_BLOCKS);
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &delegate_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.vote_as_delegate(0, VoteType::Upvote);
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &delegate_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.vote_as_delegate(0, VoteType::Upvote);
+            },
+        )
+        .assert_user_error(&String::from_utf8(ALREADY_VOTED.to_vec()).unwrap());
+
+    // User overrides the delegate's vote with its own delegated weight
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.vote_as_delegator(0, VoteType::DownVote);
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.vote_as_delegator(0, VoteType::Upvote);
+            },
+        )
+        .assert_user_error(&String::from_utf8(ALREADY_VOTED.to_vec()).unwrap());
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            let proposal = sc.proposal(0).get();
+            assert_eq!(
+                managed_biguint!(MIN_WEIGHT_FOR_PROPOSAL + owner_delegated_amount),
+                proposal.num_upvotes
+            );
+            assert_eq!(
+                managed_biguint!(user_delegated_amount),
+                proposal.num_downvotes
+            );
+
+            let delegate_vote = sc
+                .delegate_vote(0, &managed_address!(&delegate_address))
+                .get();
+            assert_eq!(VoteType::Upvote, delegate_vote.vote_type);
+            assert_eq!(
+                managed_biguint!(owner_delegated_amount),
+                delegate_vote.weight
+            );
+        })
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .set_block_nonce(2 + VOTING_DELAY_IN_BLOCKS + VOTING_PERIOD_IN_BLOCKS);
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.undelegate();
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            assert_eq!(
+                managed_biguint!(owner_delegated_amount),
+                sc.get_current_delegated_weight(&managed_address!(&delegate_address))
+            );
+        })
+        .assert_ok();
+
+    let user_address = gov_setup.user_address.clone();
+    gov_setup.blockchain_wrapper.check_esdt_balance(
+        &user_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_MEX_TOKENS),
+    );
+}
+
+#[test]
+fn test_abstain_and_percentage_quorum() {
+    let mut gov_setup = setup_gov(governance::contract_obj, pair_mock::contract_obj);
+
+    gov_setup.blockchain_wrapper.set_block_nonce(0);
+
+    // a percentage of an unset supply is rejected
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.try_change_quorum_percentage(2_000);
+            },
+        )
+        .assert_user_error("circulating voting supply not set");
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                // 20% of the circulating supply must vote, 60% of decisive votes must approve
+                sc.try_change_circulating_voting_supply(managed_biguint!(10_000_000_000));
+                sc.try_change_quorum_percentage(2_000);
+                sc.try_change_min_approval_ratio(6_000);
+
+                let dummy_proposal = Proposal::<DebugApi> {
+                    actions: ManagedVec::from(Vec::<Action<DebugApi>>::new()),
+                    creation_block: 0,
+                    creation_epoch: 0,
+                    deposit_amount: managed_biguint!(0),
+                    description: managed_buffer!(&[]),
+                    eta_block: 0,
+                    id: 0,
+                    is_critical: false,
+                    num_abstains: managed_biguint!(0),
+                    num_downvotes: managed_biguint!(0),
+                    num_upvotes: managed_biguint!(0),
+                    proposer: managed_address!(&Address::zero()),
+                    was_cancelled: false,
+                    was_executed: false,
+                };
+
+                sc.proposal(0).set(dummy_proposal);
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .set_block_nonce(VOTING_DELAY_IN_BLOCKS);
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000_000_000),
+            |sc| {
+                sc.upvote(0);
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(500_000_000),
+            |sc| {
+                sc.downvote(0);
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(500_000_000),
+            |sc| {
+                sc.abstain(0);
+            },
+        )
+        .assert_ok();
+
+    let user_address = gov_setup.user_address.clone();
+    gov_setup
+        .blockchain_wrapper
+        .execute_in_managed_environment(|| {
+            gov_setup.blockchain_wrapper.check_nft_balance(
+                &user_address,
+                VOTE_NFT_ID,
+                3,
+                &rust_biguint!(1),
+                Some(&VoteNFTAttributes::<DebugApi> {
+                    proposal_id: 0,
+                    vote_type: VoteType::Abstain,
+                    vote_weight: managed_biguint!(500_000_000),
+                    voter: managed_address!(&user_address),
+                    payment: EsdtTokenPayment::new(
+                        managed_token_id!(MEX_TOKEN_ID),
+                        0,
+                        managed_biguint!(500_000_000),
+                    ),
+                }),
+            );
+        });
+
+    gov_setup
+        .blockchain_wrapper
+        .set_block_nonce(VOTING_DELAY_IN_BLOCKS + VOTING_PERIOD_IN_BLOCKS);
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            let proposal = sc.proposal(0).get();
+            assert_eq!(managed_biguint!(500_000_000), proposal.num_abstains);
+            // abstain votes are needed to reach the 2_000_000_000 quorum
+            assert_eq!(managed_biguint!(2_000_000_000), sc.get_required_quorum());
+            assert_eq!(ProposalStatus::Succeeded, sc.get_proposal_status_view(0));
+        })
+        .assert_ok();
+
+    // 1_000_000_000 / 1_500_000_000 decisive votes is below a 70% approval ratio
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.try_change_min_approval_ratio(7_000);
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            assert_eq!(ProposalStatus::Defeated, sc.get_proposal_status_view(0));
+        })
+        .assert_ok();
+
+    // 2_000_000_000 votes are below 20% of a larger circulating supply
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.try_change_min_approval_ratio(6_000);
+                sc.try_change_circulating_voting_supply(managed_biguint!(12_500_000_000));
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            assert_eq!(ProposalStatus::Defeated, sc.get_proposal_status_view(0));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_guardian_cancel_and_expiry() {
+    let mut gov_setup = setup_gov(governance::contract_obj, pair_mock::contract_obj);
+    let guardian_address = gov_setup
+        .blockchain_wrapper
+        .create_user_account(&rust_biguint!(0));
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.guardian().set(&managed_address!(&guardian_address));
+
+                for id in 0..2 {
+                    let dummy_proposal = Proposal::<DebugApi> {
+                        actions: ManagedVec::from(Vec::<Action<DebugApi>>::new()),
+                        creation_block: 0,
+                        creation_epoch: 0,
+                        deposit_amount: managed_biguint!(0),
+                        description: managed_buffer!(&[]),
+                        eta_block: 0,
+                        id,
+                        is_critical: false,
+                        num_abstains: managed_biguint!(0),
+                        num_downvotes: managed_biguint!(0),
+                        num_upvotes: managed_biguint!(QUORUM),
+                        proposer: managed_address!(&Address::zero()),
+                        was_cancelled: false,
+                        was_executed: false,
+                    };
+
+                    sc.proposal(id).set(dummy_proposal);
+                }
+                sc.proposal_id_counter().set(2);
+            },
+        )
+        .assert_ok();
+
+    let queue_block = VOTING_DELAY_IN_BLOCKS + VOTING_PERIOD_IN_BLOCKS;
+    gov_setup.blockchain_wrapper.set_block_nonce(queue_block);
+
+    for id in 0..2 {
+        gov_setup
+            .blockchain_wrapper
+            .execute_tx(
+                &gov_setup.user_address,
+                &gov_setup.gov_wrapper,
+                &rust_biguint!(0),
+                |sc| {
+                    sc.queue(id);
+                },
+            )
+            .assert_ok();
+    }
+
+    // Only the guardian can cancel a queued proposal
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.cancel(0);
+            },
+        )
+        .assert_user_error(
+            &String::from_utf8(INVALID_CALLER_NOT_PROPOSER_OR_GUARDIAN.to_vec()).unwrap(),
+        );
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &guardian_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.cancel(0);
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            assert_eq!(ProposalStatus::Cancelled, sc.get_proposal_status_view(0));
+            assert_eq!(ProposalStatus::Queued, sc.get_proposal_status_view(1));
+        })
+        .assert_ok();
+
+    gov_setup.blockchain_wrapper.set_block_nonce(
+        queue_block + DEFAULT_TIMELOCK_DELAY_IN_BLOCKS + DEFAULT_EXECUTION_GRACE_PERIOD_IN_BLOCKS,
+    );
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            assert_eq!(ProposalStatus::Expired, sc.get_proposal_status_view(1));
+        })
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.execute(1);
+            },
+        )
+        .assert_user_error(&String::from_utf8(PROPOSAL_NOT_QUEUED.to_vec()).unwrap());
+}
+
+#[test]
+fn test_proposal_deposit_and_cancel() {
+    let mut gov_setup = setup_gov(governance::contract_obj, pair_mock::contract_obj);
+    let deposit_amount = 500_000u64;
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.proposal_deposit_amount()
+                    .set(&managed_biguint!(deposit_amount));
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(MIN_WEIGHT_FOR_PROPOSAL),
+            |sc| {
+                sc.propose(ProposalCreationArgs {
+                    description: managed_buffer!(&b""[..]),
+                    actions: ManagedVec::from(Vec::<Action<DebugApi>>::new()),
+                });
+            },
+        )
+        .assert_user_error(&String::from_utf8(INVALID_PROPOSAL_DEPOSIT.to_vec()).unwrap());
+
+    let propose_payments = [
+        TxInputESDT {
+            token_identifier: MEX_TOKEN_ID.to_vec(),
+            nonce: 0,
+            value: rust_biguint!(MIN_WEIGHT_FOR_PROPOSAL),
+        },
+        TxInputESDT {
+            token_identifier: MEX_TOKEN_ID.to_vec(),
+            nonce: 0,
+            value: rust_biguint!(deposit_amount),
+        },
+    ];
+    let proposers = [
+        gov_setup.owner_address.clone(),
+        gov_setup.user_address.clone(),
+    ];
+    for proposer in proposers.iter() {
+        gov_setup
+            .blockchain_wrapper
+            .execute_esdt_multi_transfer(
+                proposer,
+                &gov_setup.gov_wrapper,
+                &propose_payments,
+                |sc| {
+                    sc.propose(ProposalCreationArgs {
+                        description: managed_buffer!(&b""[..]),
+                        actions: ManagedVec::from(Vec::<Action<DebugApi>>::new()),
+                    });
+                },
+            )
+            .assert_ok();
+    }
+
+    // Only the proposer or the guardian can cancel a proposal
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.cancel(0);
+            },
+        )
+        .assert_user_error(
+            &String::from_utf8(INVALID_CALLER_NOT_PROPOSER_OR_GUARDIAN.to_vec()).unwrap(),
+        );
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.cancel(0);
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            assert_eq!(ProposalStatus::Cancelled, sc.get_proposal_status_view(0));
+            assert_eq!(managed_biguint!(0), sc.proposal(0).get().deposit_amount);
+        })
+        .assert_ok();
+
+    // Proposal 1 ends without reaching quorum, so its deposit is slashed
+    gov_setup
+        .blockchain_wrapper
+        .set_block_nonce(VOTING_DELAY_IN_BLOCKS + VOTING_PERIOD_IN_BLOCKS);
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.settle_deposit(1);
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.settle_deposit(1);
+            },
+        )
+        .assert_user_error(&String::from_utf8(NO_DEPOSIT_TO_SETTLE.to_vec()).unwrap());
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            assert_eq!(
+                managed_biguint!(deposit_amount),
+                sc.slashed_deposits().get()
+            );
+        })
+        .assert_ok();
+
+    let owner_address = gov_setup.owner_address.clone();
+    gov_setup.blockchain_wrapper.check_esdt_balance(
+        &owner_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_MEX_TOKENS - MIN_WEIGHT_FOR_PROPOSAL),
+    );
+
+    let user_address = gov_setup.user_address.clone();
+    gov_setup.blockchain_wrapper.check_esdt_balance(
+        &user_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_MEX_TOKENS - MIN_WEIGHT_FOR_PROPOSAL - deposit_amount),
+    );
+}
+
+#[test]
+fn test_treasury_action_payments() {
+    let mut gov_setup = setup_gov(governance::contract_obj, pair_mock::contract_obj);
+    let recipient_address = gov_setup
+        .blockchain_wrapper
+        .create_user_account(&rust_biguint!(0));
+    let treasury_amount = 10_000u64;
+    let action_amount = 4_000u64;
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(treasury_amount),
+            |sc| {
+                sc.deposit_to_treasury();
+            },
+        )
+        .assert_ok();
+
+    // The treasury cannot cover the proposal's payments
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(QUORUM),
+            |sc| {
+                let action = Action::<DebugApi> {
+                    gas_limit: 0,
+                    dest_address: managed_address!(&recipient_address),
+                    payments: ManagedVec::from(vec![EsdtTokenPayment::new(
+                        managed_token_id!(MEX_TOKEN_ID),
+                        0,
+                        managed_biguint!(treasury_amount + 1),
+                    )]),
+                    endpoint_name: managed_buffer!(&b""[..]),
+                    arguments: ManagedVec::new(),
+                };
+
+                sc.propose(ProposalCreationArgs {
+                    description: managed_buffer!(&b""[..]),
+                    actions: ManagedVec::from(vec![action]),
+                });
+            },
+        )
+        .assert_user_error(&String::from_utf8(INSUFFICIENT_TREASURY_BALANCE.to_vec()).unwrap());
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(QUORUM),
+            |sc| {
+                let action = Action::<DebugApi> {
+                    gas_limit: 0,
+                    dest_address: managed_address!(&recipient_address),
+                    payments: ManagedVec::from(vec![EsdtTokenPayment::new(
+                        managed_token_id!(MEX_TOKEN_ID),
+                        0,
+                        managed_biguint!(action_amount),
+                    )]),
+                    endpoint_name: managed_buffer!(&b""[..]),
+                    arguments: ManagedVec::new(),
+                };
+
+                sc.propose(ProposalCreationArgs {
+                    description: managed_buffer!(&b""[..]),
+                    actions: ManagedVec::from(vec![action]),
+                });
+            },
+        )
+        .assert_ok();
+
+    let queue_block = VOTING_DELAY_IN_BLOCKS + VOTING_PERIOD_IN_BLOCKS;
+    gov_setup.blockchain_wrapper.set_block_nonce(queue_block);
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.queue(0);
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .set_block_nonce(queue_block + DEFAULT_TIMELOCK_DELAY_IN_BLOCKS);
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.execute(0);
+            },
+        )
+        .assert_ok();
+
+    gov_setup.blockchain_wrapper.check_esdt_balance(
+        &recipient_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(action_amount),
+    );
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            assert_eq!(
+                managed_biguint!(treasury_amount - action_amount),
+                sc.treasury_balance(&managed_token_id!(MEX_TOKEN_ID), 0)
+                    .get()
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_locked_asset_weight_provider() {
+    let _ = DebugApi::dummy();
+    let mut gov_setup = setup_gov(governance::contract_obj, pair_mock::contract_obj);
+    let factory_address = setup_locked_asset_factory(&mut gov_setup);
+    let legacy_holder_address = gov_setup
+        .blockchain_wrapper
+        .create_user_account(&rust_biguint!(0));
+    let locked_amount = 1_000_000_000u64;
+
+    // half unlocks in 365 epochs, half after the max weight period
+    let legacy_attributes = LockedAssetTokenAttributes::<DebugApi> {
+        unlock_schedule: UnlockSchedule {
+            unlock_milestones: ManagedVec::from(vec![
+                UnlockMilestone {
+                    unlock_epoch: 365,
+                    unlock_percent: 50,
+                },
+                UnlockMilestone {
+                    unlock_epoch: 1_460,
+                    unlock_percent: 50,
+                },
+            ]),
+        },
+        is_merged: false,
+    };
+    let attributes = LockedAssetTokenAttributesEx::<DebugApi> {
+        unlock_schedule: UnlockScheduleEx {
+            unlock_milestones: ManagedVec::from(vec![
+                UnlockMilestoneEx {
+                    unlock_epoch: 365,
+                    unlock_percent: 50_000,
+                },
+                UnlockMilestoneEx {
+                    unlock_epoch: 1_460,
+                    unlock_percent: 50_000,
+                },
+            ]),
+        },
+        is_merged: false,
+    };
+    gov_setup.blockchain_wrapper.set_nft_balance(
+        &legacy_holder_address,
+        LKMEX_TOKEN_ID,
+        1,
+        &rust_biguint!(locked_amount),
+        &legacy_attributes,
+    );
+    gov_setup.blockchain_wrapper.set_nft_balance(
+        &gov_setup.user_address,
+        LKMEX_TOKEN_ID,
+        2,
+        &rust_biguint!(locked_amount),
+        &attributes,
+    );
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.add_locked_asset_weight_provider(
+                    managed_token_id!(LKMEX_TOKEN_ID),
+                    managed_address!(&factory_address),
+                    true,
+                );
+            },
+        )
+        .assert_user_error(&String::from_utf8(INVALID_CALLER_NOT_OWNER_OR_SELF.to_vec()).unwrap());
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.add_locked_asset_weight_provider(
+                    managed_token_id!(LKMEX_TOKEN_ID),
+                    managed_address!(&factory_address),
+                    true,
+                );
+                let lkmex_id = managed_token_id!(LKMEX_TOKEN_ID);
+                assert_eq!(
+                    sc.weight_provider(&lkmex_id)
+                        .get()
+                        .extended_attributes_activation_nonce,
+                    1
+                );
+
+                // as if the factory was upgraded after creating the first token
+                sc.weight_provider(&lkmex_id)
+                    .update(|provider| provider.extended_attributes_activation_nonce = 2);
+
+                // registering another locked asset token does not change it
+                sc.add_locked_asset_weight_provider(
+                    managed_token_id!(OTHER_LKMEX_TOKEN_ID),
+                    managed_address!(&factory_address),
+                    true,
+                );
+                assert_eq!(
+                    sc.weight_provider(&lkmex_id)
+                        .get()
+                        .extended_attributes_activation_nonce,
+                    2
+                );
+
+                // tokens with a weight provider are kept
+                sc.try_change_governance_token_ids(ManagedVec::from(vec![managed_token_id!(
+                    MEX_TOKEN_ID
+                )]));
+                assert!(sc
+                    .governance_token_ids()
+                    .contains(&managed_token_id!(LKMEX_TOKEN_ID)));
+
+                let dummy_proposal = Proposal::<DebugApi> {
+                    actions: ManagedVec::from(Vec::<Action<DebugApi>>::new()),
+                    creation_block: 0,
+                    creation_epoch: 0,
+                    deposit_amount: managed_biguint!(0),
+                    description: managed_buffer!(&[]),
+                    eta_block: 0,
+                    id: 0,
+                    is_critical: false,
+                    num_abstains: managed_biguint!(0),
+                    num_downvotes: managed_biguint!(0),
+                    num_upvotes: managed_biguint!(0),
+                    proposer: managed_address!(&Address::zero()),
+                    was_cancelled: false,
+                    was_executed: false,
+                };
+
+                sc.proposal(0).set(dummy_proposal);
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .set_block_nonce(VOTING_DELAY_IN_BLOCKS);
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            LKMEX_TOKEN_ID,
+            2,
+            &rust_biguint!(locked_amount),
+            |sc| {
+                sc.upvote(0);
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &legacy_holder_address,
+            &gov_setup.gov_wrapper,
+            LKMEX_TOKEN_ID,
+            1,
+            &rust_biguint!(locked_amount),
+            |sc| {
+                sc.upvote(0);
+            },
+        )
+        .assert_ok();
+
+    // (50% * 365 + 50% * 730) / 730 of each locked amount, with either attributes
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            let proposal = sc.proposal(0).get();
+            assert_eq!(managed_biguint!(1_500_000_000), proposal.num_upvotes);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_snapshot_voting() {
+    let _ = DebugApi::dummy();
+    let mut gov_setup = setup_gov(governance::contract_obj, pair_mock::contract_obj);
+    let staked_amount = 2_000_000_000u64;
+
+    let factory_address = setup_locked_asset_factory(&mut gov_setup);
+    let staking_wrapper = gov_setup.blockchain_wrapper.create_sc_account(
+        &rust_biguint!(0),
+        Some(&gov_setup.owner_address),
+        metabonding_staking::contract_obj,
+        METABONDING_STAKING_WASM_PATH,
+    );
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &staking_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.init(
+                    managed_token_id!(LKMEX_TOKEN_ID),
+                    managed_address!(&factory_address),
+                );
+            },
+        )
+        .assert_ok();
+
+    let attributes = LockedAssetTokenAttributesEx::<DebugApi> {
+        unlock_schedule: UnlockScheduleEx {
+            unlock_milestones: ManagedVec::from(vec![UnlockMilestoneEx {
+                unlock_epoch: 1_000,
+                unlock_percent: 100_000,
+            }]),
+        },
+        is_merged: false,
+    };
+    gov_setup.blockchain_wrapper.set_nft_balance(
+        &gov_setup.user_address,
+        LKMEX_TOKEN_ID,
+        1,
+        &rust_biguint!(staked_amount),
+        &attributes,
+    );
+
+    // stake in epoch 5, the proposal is created in epoch 6
+    gov_setup.blockchain_wrapper.set_block_epoch(5);
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.user_address,
+            &staking_wrapper,
+            LKMEX_TOKEN_ID,
+            1,
+            &rust_biguint!(staked_amount),
+            |sc| {
+                sc.stake_locked_asset();
+            },
+        )
+        .assert_ok();
+
+    // snapshot sources can only be added through proposals
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.add_snapshot_source(managed_address!(staking_wrapper.address_ref()));
+            },
+        )
+        .assert_user_error(&String::from_utf8(INVALID_CALLER_NOT_SELF.to_vec()).unwrap());
+
+    let gov_address = gov_setup.gov_wrapper.address_ref().clone();
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.add_snapshot_source(managed_address!(staking_wrapper.address_ref()));
+
+                let legacy_proposal = Proposal::<DebugApi> {
+                    actions: ManagedVec::from(Vec::<Action<DebugApi>>::new()),
+                    creation_block: 0,
+                    creation_epoch: 0,
+                    deposit_amount: managed_biguint!(0),
+                    description: managed_buffer!(&[]),
+                    eta_block: 0,
+                    id: 0,
+                    is_critical: false,
+                    num_abstains: managed_biguint!(0),
+                    num_downvotes: managed_biguint!(0),
+                    num_upvotes: managed_biguint!(0),
+                    proposer: managed_address!(&Address::zero()),
+                    was_cancelled: false,
+                    was_executed: false,
+                };
+                let snapshot_proposal = Proposal::<DebugApi> {
+                    actions: ManagedVec::from(Vec::<Action<DebugApi>>::new()),
+                    creation_block: 0,
+                    creation_epoch: 6,
+                    deposit_amount: managed_biguint!(0),
+                    description: managed_buffer!(&[]),
+                    eta_block: 0,
+                    id: 1,
+                    is_critical: false,
+                    num_abstains: managed_biguint!(0),
+                    num_downvotes: managed_biguint!(0),
+                    num_upvotes: managed_biguint!(0),
+                    proposer: managed_address!(&Address::zero()),
+                    was_cancelled: false,
+                    was_executed: false,
+                };
+
+                sc.proposal(0).set(legacy_proposal);
+                sc.proposal(1).set(snapshot_proposal);
+            },
+        )
+        .assert_ok();
+
+    gov_setup.blockchain_wrapper.set_block_epoch(6);
+    gov_setup
+        .blockchain_wrapper
+        .set_block_nonce(VOTING_DELAY_IN_BLOCKS);
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.vote_with_snapshot(0, VoteType::Upvote);
+            },
+        )
+        .assert_user_error(&String::from_utf8(SNAPSHOT_NOT_AVAILABLE.to_vec()).unwrap());
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.vote_with_snapshot(1, VoteType::Upvote);
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.vote_with_snapshot(1, VoteType::Downvote);
+            },
+        )
+        .assert_user_error(&String::from_utf8(ALREADY_VOTED.to_vec()).unwrap());
+
+    // the owner had no stake in the snapshot epoch
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.vote_with_snapshot(1, VoteType::Downvote);
+            },
+        )
+        .assert_user_error(&String::from_utf8(ERROR_ZERO_VALUE.to_vec()).unwrap());
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            let proposal = sc.proposal(1).get();
+            assert_eq!(managed_biguint!(staked_amount), proposal.num_upvotes);
+            assert_eq!(
+                managed_biguint!(staked_amount),
+                sc.get_snapshot_voting_power(managed_address!(&gov_setup.user_address), 1)
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_proposal_actions_view_and_dry_run() {
+    let mut gov_setup = setup_gov(governance::contract_obj, pair_mock::contract_obj);
+    let recipient_address = gov_setup
+        .blockchain_wrapper
+        .create_user_account(&rust_biguint!(0));
+    let pair_address = gov_setup.pair_wrapper.address_ref().clone();
+    let action_amount = 4_000u64;
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.user_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(action_amount),
+            |sc| {
+                sc.deposit_to_treasury();
+            },
+        )
+        .assert_ok();
+
+    // the second action fails, as it adds liquidity without any payment
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(QUORUM),
+            |sc| {
+                let transfer_action = Action::<DebugApi> {
+                    gas_limit: 0,
+                    dest_address: managed_address!(&recipient_address),
+                    payments: ManagedVec::from(vec![EsdtTokenPayment::new(
+                        managed_token_id!(MEX_TOKEN_ID),
+                        0,
+                        managed_biguint!(action_amount),
+                    )]),
+                    endpoint_name: managed_buffer!(&b""[..]),
+                    arguments: ManagedVec::new(),
+                };
+                let failing_action = Action::<DebugApi> {
+                    gas_limit: 10_000_000,
+                    dest_address: managed_address!(&pair_address),
+                    payments: ManagedVec::new(),
+                    endpoint_name: managed_buffer!(b"addInitialLiquidity"),
+                    arguments: ManagedVec::new(),
+                };
+
+                sc.propose(ProposalCreationArgs {
+                    description: managed_buffer!(&b""[..]),
+                    actions: ManagedVec::from(vec![transfer_action, failing_action]),
+                });
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            let actions: Vec<ActionDescription<DebugApi>> =
+                sc.get_proposal_actions(0).into_iter().collect();
+            assert_eq!(2, actions.len());
+
+            assert_eq!(
+                managed_address!(&recipient_address),
+                actions[0].dest_address
+            );
+            assert!(!actions[0].dest_is_smart_contract);
+            assert_eq!(1, actions[0].payments.len());
+
+            assert_eq!(managed_address!(&pair_address), actions[1].dest_address);
+            assert!(actions[1].dest_is_smart_contract);
+            assert_eq!(
+                managed_buffer!(b"addInitialLiquidity"),
+                actions[1].endpoint_name
+            );
+            assert_eq!(10_000_000, actions[1].gas_limit);
+        })
+        .assert_ok();
+
+    // dry run while the voting is still active
+    gov_setup
+        .blockchain_wrapper
+        .set_block_nonce(VOTING_DELAY_IN_BLOCKS);
+
+    let results =
+        dry_run_proposal_actions(&mut gov_setup.blockchain_wrapper, &gov_setup.gov_wrapper, 0);
+    assert_eq!(2, results.len());
+    assert_eq!(Ok(()), results[0]);
+    assert!(results[1].is_err());
+
+    // the dry run leaves the state untouched
+    gov_setup.blockchain_wrapper.check_esdt_balance(
+        &recipient_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(0),
+    );
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            let proposal = sc.proposal(0).get();
+            assert!(!proposal.was_executed);
+            assert_eq!(
+                managed_biguint!(action_amount),
+                sc.treasury_balance(&managed_token_id!(MEX_TOKEN_ID), 0)
+                    .get()
+            );
+        })
+        .assert_ok();
+
+    // as in execute, the treasury is charged for all the actions first,
+    // which fails if it was spent by another proposal in the meantime
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(QUORUM),
+            |sc| {
+                let transfer_action = Action::<DebugApi> {
+                    gas_limit: 0,
+                    dest_address: managed_address!(&recipient_address),
+                    payments: ManagedVec::from(vec![EsdtTokenPayment::new(
+                        managed_token_id!(MEX_TOKEN_ID),
+                        0,
+                        managed_biguint!(action_amount),
+                    )]),
+                    endpoint_name: managed_buffer!(&b""[..]),
+                    arguments: ManagedVec::new(),
+                };
+
+                sc.propose(ProposalCreationArgs {
+                    description: managed_buffer!(&b""[..]),
+                    actions: ManagedVec::from(vec![transfer_action]),
+                });
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.treasury_balance(&managed_token_id!(MEX_TOKEN_ID), 0)
+                    .set(&managed_biguint!(action_amount - 1));
+            },
+        )
+        .assert_ok();
+
+    let results =
+        dry_run_proposal_actions(&mut gov_setup.blockchain_wrapper, &gov_setup.gov_wrapper, 1);
+    assert_eq!(
+        vec![Err(String::from_utf8(
+            INSUFFICIENT_TREASURY_BALANCE.to_vec()
+        )
+        .unwrap())],
+        results
+    );
+}
+
+#[test]
+fn test_critical_actions_and_allowed_destinations() {
+    let mut gov_setup = setup_gov(governance::contract_obj, pair_mock::contract_obj);
+    let recipient_address = gov_setup
+        .blockchain_wrapper
+        .create_user_account(&rust_biguint!(0));
+    let pair_address = gov_setup.pair_wrapper.address_ref().clone();
+    let critical_voting_period = VOTING_PERIOD_IN_BLOCKS + 10;
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_destination_allowlist_enabled(true);
+            },
+        )
+        .assert_user_error(&String::from_utf8(INVALID_CALLER_NOT_SELF.to_vec()).unwrap());
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.critical_actions().insert(CriticalAction {
+                    dest_address: managed_address!(&pair_address),
+                    endpoint_name: managed_buffer!(b"addInitialLiquidity"),
+                });
+                sc.try_change_critical_quorum(managed_biguint!(2 * QUORUM));
+                sc.try_change_critical_voting_period_in_blocks(critical_voting_period);
+                sc.destination_allowlist_enabled().set(true);
+            },
+        )
+        .assert_ok();
+
+    let critical_action = || Action::<DebugApi> {
+        gas_limit: 10_000_000,
+        dest_address: managed_address!(&pair_address),
+        payments: ManagedVec::new(),
+        endpoint_name: managed_buffer!(b"addInitialLiquidity"),
+        arguments: ManagedVec::new(),
+    };
+
+    // the pair is not in the allowlist yet
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(QUORUM),
+            |sc| {
+                sc.propose(ProposalCreationArgs {
+                    description: managed_buffer!(&b""[..]),
+                    actions: ManagedVec::from(vec![critical_action()]),
+                });
+            },
+        )
+        .assert_user_error(&String::from_utf8(DESTINATION_NOT_ALLOWED.to_vec()).unwrap());
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.allowed_destinations()
+                    .insert(managed_address!(&pair_address));
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(QUORUM),
+            |sc| {
+                let proposal_id = sc.propose(ProposalCreationArgs {
+                    description: managed_buffer!(&b""[..]),
+                    actions: ManagedVec::from(vec![critical_action()]),
+                });
+                assert_eq!(0, proposal_id);
+            },
+        )
+        .assert_ok();
+
+    // user accounts are not subject to the allowlist
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(QUORUM),
+            |sc| {
+                let action = Action::<DebugApi> {
+                    gas_limit: 0,
+                    dest_address: managed_address!(&recipient_address),
+                    payments: ManagedVec::new(),
+                    endpoint_name: managed_buffer!(&b""[..]),
+                    arguments: ManagedVec::new(),
+                };
+
+                let proposal_id = sc.propose(ProposalCreationArgs {
+                    description: managed_buffer!(&b""[..]),
+                    actions: ManagedVec::from(vec![action]),
+                });
+                assert_eq!(1, proposal_id);
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .set_block_nonce(VOTING_DELAY_IN_BLOCKS + VOTING_PERIOD_IN_BLOCKS);
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            assert!(sc.proposal(0).get().is_critical);
+            assert!(!sc.proposal(1).get().is_critical);
+
+            assert_eq!(ProposalStatus::Active, sc.get_proposal_status_view(0));
+            assert_eq!(ProposalStatus::Succeeded, sc.get_proposal_status_view(1));
+        })
+        .assert_ok();
+
+    // the proposer's weight reaches the regular quorum, but not the critical one
+    gov_setup
+        .blockchain_wrapper
+        .set_block_nonce(VOTING_DELAY_IN_BLOCKS + critical_voting_period);
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            assert_eq!(ProposalStatus::Defeated, sc.get_proposal_status_view(0));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_policy_self_calls_are_critical() {
+    let mut gov_setup = setup_gov(governance::contract_obj, pair_mock::contract_obj);
+    let gov_address = gov_setup.gov_wrapper.address_ref().clone();
+
+    let self_call = |endpoint_name: &[u8]| Action::<DebugApi> {
+        gas_limit: 10_000_000,
+        dest_address: managed_address!(&gov_address),
+        payments: ManagedVec::new(),
+        endpoint_name: managed_buffer!(endpoint_name),
+        arguments: ManagedVec::new(),
+    };
+
+    for (expected_id, endpoint_name) in [
+        &b"removeCriticalActions"[..],
+        &b"changeCriticalQuorum"[..],
+        &b"setDestinationAllowlistEnabled"[..],
+        &b"changeQuorum"[..],
+        &b"changeTimelockDelayInBlocks"[..],
+        &b"addLockedAssetWeightProvider"[..],
+    ]
+    .iter()
+    .enumerate()
+    {
+        gov_setup
+            .blockchain_wrapper
+            .execute_esdt_transfer(
+                &gov_setup.owner_address,
+                &gov_setup.gov_wrapper,
+                MEX_TOKEN_ID,
+                0,
+                &rust_biguint!(QUORUM),
+                |sc| {
+                    let proposal_id = sc.propose(ProposalCreationArgs {
+                        description: managed_buffer!(&b""[..]),
+                        actions: ManagedVec::from(vec![self_call(endpoint_name)]),
+                    });
+                    assert_eq!(expected_id as u64, proposal_id);
+                },
+            )
+            .assert_ok();
+    }
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            for proposal_id in 0..6 {
+                assert!(sc.proposal(proposal_id).get().is_critical);
+            }
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_snapshot_source_self_call_needs_critical_quorum() {
+    let mut gov_setup = setup_gov(governance::contract_obj, pair_mock::contract_obj);
+    let gov_address = gov_setup.gov_wrapper.address_ref().clone();
+    let source_address = gov_setup.pair_wrapper.address_ref().clone();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.try_change_critical_quorum(managed_biguint!(2 * QUORUM));
+            },
+        )
+        .assert_ok();
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &gov_setup.owner_address,
+            &gov_setup.gov_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(QUORUM),
+            |sc| {
+                let mut arguments = ManagedVec::new();
+                arguments.push(managed_buffer!(source_address.as_bytes()));
+                let action = Action::<DebugApi> {
+                    gas_limit: 10_000_000,
+                    dest_address: managed_address!(&gov_address),
+                    payments: ManagedVec::new(),
+                    endpoint_name: managed_buffer!(b"addSnapshotSource"),
+                    arguments,
+                };
+
+                let proposal_id = sc.propose(ProposalCreationArgs {
+                    description: managed_buffer!(&b""[..]),
+                    actions: ManagedVec::from(vec![action]),
+                });
+                assert_eq!(0, proposal_id);
+            },
+        )
+        .assert_ok();
+
+    // the proposer's weight reaches the regular quorum, but not the critical one
+    gov_setup
+        .blockchain_wrapper
+        .set_block_nonce(VOTING_DELAY_IN_BLOCKS + VOTING_PERIOD_IN_BLOCKS);
+
+    gov_setup
+        .blockchain_wrapper
+        .execute_query(&gov_setup.gov_wrapper, |sc| {
+            assert!(sc.proposal(0).get().is_critical);
+            assert_eq!(ProposalStatus::Defeated, sc.get_proposal_status_view(0));
+        })
+        .assert_ok();
+}
@@ -7,27 +7,86 @@
 elrond_wasm_node::wasm_endpoints! {
     governance
     (
+        abstain
+        addAllowedDestinations
+        addCriticalActions
+        addDualYieldWeightProvider
+        addFarmWeightProvider
+        addLockedAssetWeightProvider
+        addSnapshotSource
+        cancel
+        changeCirculatingVotingSupply
+        changeCriticalQuorum
+        changeCriticalQuorumPercentage
+        changeCriticalVotingPeriodInBlocks
+        changeDelegate
+        changeExecutionGracePeriodInBlocks
         changeGovernanceTokenIds
+        changeGuardian
+        changeMinApprovalRatio
         changeMinTokenBalanceForProposing
         changePriceProviders
+        changeProposalDepositAmount
         changeQuorum
+        changeQuorumPercentage
+        changeTimelockDelayInBlocks
         changeVotingDelayInBlocks
         changeVotingPeriodInBlocks
+        delegate
+        depositToTreasury
         downvote
         execute
+        getAllowedDestinations
+        getCirculatingVotingSupply
+        getCriticalActions
+        getCriticalQuorum
+        getCriticalQuorumPercentage
+        getCriticalVotingPeriodInBlocks
+        getDelegateVote
+        getDelegatedWeight
+        getDelegatedWeightAt
+        getDelegation
+        getExecutionGracePeriodInBlocks
+        getExtendedAttributesActivationNonce
         getGovernanceTokenId
+        getGuardian
         getMexTokenId
+        getMinApprovalRatio
         getMinWeightForProposal
         getProposal
+        getProposalActions
+        getProposalDepositAmount
+        getProposalEta
         getProposalIdCounter
         getProposalStatus
         getQuorum
+        getQuorumPercentage
+        getRequiredCriticalQuorum
+        getRequiredQuorum
+        getSlashedDeposits
+        getSnapshotSources
+        getSnapshotVotingPower
+        getTimelockDelayInBlocks
+        getTreasuryBalance
         getVoteNFTId
         getVotingDelayInBlocks
         getVotingPeriodInBlocks
+        getWeightProvider
+        isDestinationAllowlistEnabled
         propose
+        queue
         redeem
+        removeAllowedDestinations
+        removeCriticalActions
+        removeSnapshotSource
+        removeWeightProvider
+        setDestinationAllowlistEnabled
+        settleDeposit
+        undelegate
         upvote
+        voteAsDelegate
+        voteAsDelegator
+        voteWithSnapshot
     )
 }
 
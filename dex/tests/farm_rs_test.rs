@@ -1,4 +1,5 @@
 use common_structs::FarmTokenAttributes;
+use elrond_wasm::elrond_codec::multi_types::OptionalValue;
 use elrond_wasm::storage::mappers::StorageTokenWrapper;
 use elrond_wasm::types::{Address, EsdtLocalRole, EsdtTokenPayment};
 use elrond_wasm_debug::tx_mock::{TxContextStack, TxInputESDT};
@@ -11,13 +12,17 @@ type RustBigUint = num_bigint::BigUint;
 use migration_from_v1_2::{FarmTokenAttributesV1_2, MigrationModule};
 
 use config::*;
+use farm::custom_rewards::CustomRewardsModule;
 use farm::*;
 use farm_token::FarmTokenModule;
+use pair::config::ConfigModule as PairConfigModule;
+use pair::Pair;
 use pausable::{PausableModule, State};
 
 // const GENERATED_FILE_PREFIX: &'static str = "_generated_";
 // const MANDOS_FILE_EXTENSION: &'static str = ".scen.json";
 const FARM_WASM_PATH: &'static str = "farm/output/farm.wasm";
+const PAIR_WASM_PATH: &'static str = "pair/output/pair.wasm";
 
 const WEGLD_TOKEN_ID: &[u8] = b"WEGLD-abcdef";
 const MEX_TOKEN_ID: &[u8] = b"MEX-abcdef"; // reward token ID
@@ -410,6 +415,125 @@ fn test_enter_farm() {
     */
 }
 
+#[test]
+fn test_reward_rate_views() {
+    let mut farm_setup = setup_farm(farm::contract_obj);
+
+    let farm_in_amount = 100_000_000;
+    enter_farm(
+        &mut farm_setup,
+        farm_in_amount,
+        &[],
+        1,
+        0,
+        0,
+        0,
+        farm_in_amount,
+        0,
+    );
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            let expected_per_block =
+                PER_BLOCK_REWARD_AMOUNT * DIVISION_SAFETY_CONSTANT / farm_in_amount;
+            assert_eq!(
+                sc.get_reward_per_block_per_farming_token(),
+                managed_biguint!(expected_per_block)
+            );
+            assert_eq!(
+                sc.get_annual_reward_per_farming_token(),
+                managed_biguint!(expected_per_block * BLOCKS_IN_YEAR)
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_farming_token_valuation() {
+    let mut farm_setup = setup_farm(farm::contract_obj);
+
+    let b_mock = &mut farm_setup.blockchain_wrapper;
+    b_mock
+        .execute_tx(
+            &farm_setup.user_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let _ = sc.get_farming_token_valuation(managed_biguint!(100));
+            },
+        )
+        .assert_user_error("No pair contract");
+
+    let pair_wrapper = b_mock.create_sc_account(
+        &rust_biguint!(0),
+        Some(&farm_setup.owner_address),
+        pair::contract_obj,
+        PAIR_WASM_PATH,
+    );
+    b_mock
+        .execute_tx(
+            &farm_setup.owner_address,
+            &pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.init(
+                    managed_token_id!(WEGLD_TOKEN_ID),
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_address!(&farm_setup.owner_address),
+                    managed_address!(&farm_setup.owner_address),
+                    300,
+                    50,
+                    OptionalValue::None,
+                );
+                sc.lp_token_identifier()
+                    .set(&managed_token_id!(LP_TOKEN_ID));
+
+                sc.pair_reserve(&managed_token_id!(WEGLD_TOKEN_ID))
+                    .set(&managed_biguint!(1_000));
+                sc.pair_reserve(&managed_token_id!(MEX_TOKEN_ID))
+                    .set(&managed_biguint!(2_000));
+                sc.lp_token_supply().set(&managed_biguint!(1_000));
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .execute_tx(
+            &farm_setup.owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.pair_contract_address()
+                    .set(&managed_address!(pair_wrapper.address_ref()));
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .execute_tx(
+            &farm_setup.user_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let (first_token, second_token) = sc
+                    .get_farming_token_valuation(managed_biguint!(100))
+                    .into_tuple();
+                assert_eq!(
+                    first_token.token_identifier,
+                    managed_token_id!(WEGLD_TOKEN_ID)
+                );
+                assert_eq!(first_token.amount, managed_biguint!(100));
+                assert_eq!(
+                    second_token.token_identifier,
+                    managed_token_id!(MEX_TOKEN_ID)
+                );
+                assert_eq!(second_token.amount, managed_biguint!(200));
+            },
+        )
+        .assert_ok();
+}
+
 #[test]
 fn test_exit_farm() {
     let mut farm_setup = setup_farm(farm::contract_obj);
@@ -446,9 +570,353 @@ fn test_exit_farm() {
 }
 
 #[test]
-fn test_claim_rewards() {
+fn test_claim_rewards() {
+    let mut farm_setup = setup_farm(farm::contract_obj);
+
+    let farm_in_amount = 100_000_000;
+    let expected_farm_token_nonce = 1;
+    enter_farm(
+        &mut farm_setup,
+        farm_in_amount,
+        &[],
+        expected_farm_token_nonce,
+        0,
+        0,
+        0,
+        farm_in_amount,
+        0,
+    );
+    check_farm_token_supply(&mut farm_setup, farm_in_amount);
+
+    set_block_epoch(&mut farm_setup, 5);
+    set_block_nonce(&mut farm_setup, 10);
+
+    let expected_mex_out = 10 * PER_BLOCK_REWARD_AMOUNT;
+    let expected_lp_token_balance = rust_biguint!(USER_TOTAL_LP_TOKENS - farm_in_amount);
+    let expected_reward_per_share = 500_000_000;
+    claim_rewards(
+        &mut farm_setup,
+        farm_in_amount,
+        expected_farm_token_nonce,
+        expected_mex_out,
+        &rust_biguint!(expected_mex_out),
+        &expected_lp_token_balance,
+        expected_farm_token_nonce + 1,
+        expected_reward_per_share,
+    );
+    check_farm_token_supply(&mut farm_setup, farm_in_amount);
+}
+
+#[test]
+fn test_account_position_claim_rewards() {
+    use farm::account_positions::AccountPositionsModule;
+
+    let mut farm_setup = setup_farm(farm::contract_obj);
+
+    let farm_in_amount = 100_000_000;
+    let expected_farm_token_nonce = 1;
+    enter_farm(
+        &mut farm_setup,
+        farm_in_amount,
+        &[],
+        expected_farm_token_nonce,
+        0,
+        0,
+        0,
+        farm_in_amount,
+        0,
+    );
+
+    let owner = farm_setup.owner_address.clone();
+    let user = farm_setup.user_address.clone();
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &farm_setup.farm_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_account_mode_enabled(true);
+        })
+        .assert_ok();
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &user,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            expected_farm_token_nonce,
+            &rust_biguint!(farm_in_amount),
+            |sc| {
+                let account_amount = sc.convert_to_account_position();
+                assert_eq!(account_amount, managed_biguint!(farm_in_amount));
+            },
+        )
+        .assert_ok();
+
+    // the position stays in the farm, so the supply is unchanged
+    check_farm_token_supply(&mut farm_setup, farm_in_amount);
+    farm_setup
+        .blockchain_wrapper
+        .check_nft_balance::<FarmTokenAttributes<DebugApi>>(
+            &user,
+            FARM_TOKEN_ID,
+            expected_farm_token_nonce,
+            &rust_biguint!(0),
+            None,
+        );
+
+    set_block_nonce(&mut farm_setup, 10);
+
+    let expected_mex_out = 10 * PER_BLOCK_REWARD_AMOUNT;
+    farm_setup
+        .blockchain_wrapper
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            let rewards = sc.calculate_rewards_for_account(managed_address!(&user));
+            assert_eq!(rewards, managed_biguint!(expected_mex_out));
+        })
+        .assert_ok();
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(&user, &farm_setup.farm_wrapper, &rust_biguint!(0), |sc| {
+            let reward = sc.claim_rewards_account();
+            assert_eq!(reward.amount, managed_biguint!(expected_mex_out));
+
+            let position = sc.account_position(&managed_address!(&user)).get();
+            assert_eq!(position.reward_per_share, managed_biguint!(500_000_000));
+        })
+        .assert_ok();
+    farm_setup.blockchain_wrapper.check_esdt_balance(
+        &user,
+        MEX_TOKEN_ID,
+        &rust_biguint!(expected_mex_out),
+    );
+
+    // claiming again in the same block gives nothing
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(&user, &farm_setup.farm_wrapper, &rust_biguint!(0), |sc| {
+            let reward = sc.claim_rewards_account();
+            assert_eq!(reward.amount, managed_biguint!(0));
+        })
+        .assert_ok();
+
+    // convert the whole position back to a farm token
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(&user, &farm_setup.farm_wrapper, &rust_biguint!(0), |sc| {
+            let farm_token = sc.convert_to_farm_token(managed_biguint!(farm_in_amount));
+            assert_eq!(farm_token.token_nonce, expected_farm_token_nonce + 1);
+            assert!(sc.account_position(&managed_address!(&user)).is_empty());
+        })
+        .assert_ok();
+
+    let _ = DebugApi::dummy();
+    farm_setup.blockchain_wrapper.check_nft_balance(
+        &user,
+        FARM_TOKEN_ID,
+        expected_farm_token_nonce + 1,
+        &rust_biguint!(farm_in_amount),
+        Some(&FarmTokenAttributes::<DebugApi> {
+            reward_per_share: managed_biguint!(500_000_000),
+            original_entering_epoch: 0,
+            entering_epoch: 0,
+            initial_farming_amount: managed_biguint!(farm_in_amount),
+            compounded_reward: managed_biguint!(0),
+            current_farm_amount: managed_biguint!(farm_in_amount),
+        }),
+    );
+    check_farm_token_supply(&mut farm_setup, farm_in_amount);
+}
+
+#[test]
+fn test_split_farm_token() {
+    use elrond_wasm::types::MultiValueEncoded;
+    use farm::farm_token_merge::FarmTokenMergeModule;
+
+    let mut farm_setup = setup_farm(farm::contract_obj);
+
+    let farm_in_amount = 100_000_000;
+    let expected_farm_token_nonce = 1;
+    enter_farm(
+        &mut farm_setup,
+        farm_in_amount,
+        &[],
+        expected_farm_token_nonce,
+        0,
+        0,
+        0,
+        farm_in_amount,
+        0,
+    );
+    check_farm_token_supply(&mut farm_setup, farm_in_amount);
+
+    let user = farm_setup.user_address.clone();
+    farm_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &user,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            expected_farm_token_nonce,
+            &rust_biguint!(farm_in_amount),
+            |sc| {
+                let mut amounts = MultiValueEncoded::new();
+                amounts.push(managed_biguint!(30_000_000));
+                amounts.push(managed_biguint!(70_000_000));
+
+                let new_tokens = sc.split_farm_token(amounts).to_vec();
+                assert_eq!(new_tokens.len(), 2);
+                assert_eq!(new_tokens.get(0).token_nonce, 2);
+                assert_eq!(new_tokens.get(1).token_nonce, 3);
+            },
+        )
+        .assert_ok();
+
+    // the farm token supply is not changed by splitting
+    check_farm_token_supply(&mut farm_setup, farm_in_amount);
+
+    let _ = DebugApi::dummy();
+    for (nonce, amount) in [(2u64, 30_000_000u64), (3, 70_000_000)] {
+        farm_setup.blockchain_wrapper.check_nft_balance(
+            &user,
+            FARM_TOKEN_ID,
+            nonce,
+            &rust_biguint!(amount),
+            Some(&FarmTokenAttributes::<DebugApi> {
+                reward_per_share: managed_biguint!(0),
+                original_entering_epoch: 0,
+                entering_epoch: 0,
+                initial_farming_amount: managed_biguint!(amount),
+                compounded_reward: managed_biguint!(0),
+                current_farm_amount: managed_biguint!(amount),
+            }),
+        );
+    }
+
+    // the amounts must add up to the payment
+    farm_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &user,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            2,
+            &rust_biguint!(30_000_000),
+            |sc| {
+                let mut amounts = MultiValueEncoded::new();
+                amounts.push(managed_biguint!(10_000_000));
+                amounts.push(managed_biguint!(10_000_000));
+
+                let _ = sc.split_farm_token(amounts);
+            },
+        )
+        .assert_user_error("Bad split amounts");
+}
+
+#[test]
+fn test_emergency_exit() {
+    let mut farm_setup = setup_farm(farm::contract_obj);
+
+    let farm_in_amount = 100_000_000;
+    let expected_farm_token_nonce = 1;
+    enter_farm(
+        &mut farm_setup,
+        farm_in_amount,
+        &[],
+        expected_farm_token_nonce,
+        0,
+        0,
+        0,
+        farm_in_amount,
+        0,
+    );
+
+    set_block_epoch(&mut farm_setup, 5);
+    set_block_nonce(&mut farm_setup, 10);
+
+    let b_mock = &mut farm_setup.blockchain_wrapper;
+    b_mock
+        .execute_esdt_transfer(
+            &farm_setup.user_address,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            expected_farm_token_nonce,
+            &rust_biguint!(farm_in_amount),
+            |sc| {
+                let _ = sc.emergency_exit();
+            },
+        )
+        .assert_user_error("Not paused");
+
+    b_mock
+        .execute_tx(
+            &farm_setup.owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.pause();
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .execute_esdt_transfer(
+            &farm_setup.user_address,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            expected_farm_token_nonce,
+            &rust_biguint!(farm_in_amount),
+            |sc| {
+                let payment = sc.emergency_exit();
+                assert_eq!(payment.token_identifier, managed_token_id!(LP_TOKEN_ID));
+                assert_eq!(payment.amount, managed_biguint!(farm_in_amount));
+            },
+        )
+        .assert_ok();
+
+    b_mock.check_esdt_balance(
+        &farm_setup.user_address,
+        LP_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_LP_TOKENS),
+    );
+    b_mock.check_esdt_balance(&farm_setup.user_address, MEX_TOKEN_ID, &rust_biguint!(0));
+    check_farm_token_supply(&mut farm_setup, 0);
+}
+
+#[test]
+fn test_claim_rewards_funded_mode() {
     let mut farm_setup = setup_farm(farm::contract_obj);
 
+    let reward_funds = 3 * PER_BLOCK_REWARD_AMOUNT;
+    farm_setup.blockchain_wrapper.set_esdt_balance(
+        &farm_setup.owner_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(reward_funds),
+    );
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &farm_setup.owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_funded_mode(true);
+            },
+        )
+        .assert_ok();
+    farm_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &farm_setup.owner_address,
+            &farm_setup.farm_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(reward_funds),
+            |sc| {
+                sc.top_up_rewards();
+            },
+        )
+        .assert_ok();
+
     let farm_in_amount = 100_000_000;
     let expected_farm_token_nonce = 1;
     enter_farm(
@@ -462,14 +930,27 @@ fn test_claim_rewards() {
         farm_in_amount,
         0,
     );
-    check_farm_token_supply(&mut farm_setup, farm_in_amount);
 
     set_block_epoch(&mut farm_setup, 5);
+    set_block_nonce(&mut farm_setup, 2);
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            assert_eq!(
+                sc.get_remaining_reward_funds(),
+                managed_biguint!(PER_BLOCK_REWARD_AMOUNT)
+            );
+            assert_eq!(sc.get_rewards_depletion_blocks(), managed_biguint!(1));
+        })
+        .assert_ok();
+
+    // emission stops after 3 blocks, when the funds are exhausted
     set_block_nonce(&mut farm_setup, 10);
 
-    let expected_mex_out = 10 * PER_BLOCK_REWARD_AMOUNT;
+    let expected_mex_out = reward_funds;
     let expected_lp_token_balance = rust_biguint!(USER_TOTAL_LP_TOKENS - farm_in_amount);
-    let expected_reward_per_share = 500_000_000;
+    let expected_reward_per_share = 150_000_000;
     claim_rewards(
         &mut farm_setup,
         farm_in_amount,
@@ -480,7 +961,118 @@ fn test_claim_rewards() {
         expected_farm_token_nonce + 1,
         expected_reward_per_share,
     );
-    check_farm_token_supply(&mut farm_setup, farm_in_amount);
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            assert_eq!(sc.get_remaining_reward_funds(), managed_biguint!(0));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_reward_funds_top_up_and_withdraw() {
+    let mut farm_setup = setup_farm(farm::contract_obj);
+
+    let reward_funds = 3 * PER_BLOCK_REWARD_AMOUNT;
+    farm_setup.blockchain_wrapper.set_esdt_balance(
+        &farm_setup.user_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(reward_funds),
+    );
+
+    let b_mock = &mut farm_setup.blockchain_wrapper;
+    b_mock
+        .execute_esdt_transfer(
+            &farm_setup.user_address,
+            &farm_setup.farm_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(reward_funds),
+            |sc| {
+                sc.top_up_rewards();
+            },
+        )
+        .assert_user_error("Not in funded mode");
+
+    b_mock
+        .execute_tx(
+            &farm_setup.owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_funded_mode(true);
+            },
+        )
+        .assert_ok();
+
+    // anyone can fund the farm
+    b_mock
+        .execute_esdt_transfer(
+            &farm_setup.user_address,
+            &farm_setup.farm_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(reward_funds),
+            |sc| {
+                sc.top_up_rewards();
+            },
+        )
+        .assert_ok();
+
+    // no one is farming, so the funds are not distributed and can be withdrawn
+    b_mock.set_block_nonce(10);
+    b_mock
+        .execute_tx(
+            &farm_setup.user_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.withdraw_reward_funds(managed_biguint!(reward_funds));
+            },
+        )
+        .assert_user_error("Endpoint can only be called by owner");
+    b_mock
+        .execute_tx(
+            &farm_setup.owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_funded_mode(false);
+            },
+        )
+        .assert_ok();
+    b_mock
+        .execute_tx(
+            &farm_setup.owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.withdraw_reward_funds(managed_biguint!(reward_funds + 1));
+            },
+        )
+        .assert_user_error("Not enough reward funds");
+    b_mock
+        .execute_tx(
+            &farm_setup.owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.withdraw_reward_funds(managed_biguint!(reward_funds));
+            },
+        )
+        .assert_ok();
+
+    b_mock.check_esdt_balance(
+        &farm_setup.owner_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(reward_funds),
+    );
+    b_mock
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            assert_eq!(sc.reward_capacity().get(), managed_biguint!(0));
+        })
+        .assert_ok();
 }
 
 fn steps_enter_farm_twice<FarmObjBuilder>(farm_builder: FarmObjBuilder) -> FarmSetup<FarmObjBuilder>
@@ -692,6 +1284,211 @@ fn test_migration() {
     do_basic_migration(&mut farm_setup);
 }
 
+const NEXT_FARM_TOKEN_ID: &[u8] = b"NFARM-abcdef";
+
+fn setup_next_farm<FarmObjBuilder>(
+    farm_setup: &mut FarmSetup<FarmObjBuilder>,
+) -> ContractObjWrapper<farm::ContractObj<DebugApi>, fn() -> farm::ContractObj<DebugApi>>
+where
+    FarmObjBuilder: 'static + Copy + Fn() -> farm::ContractObj<DebugApi>,
+{
+    use farm_migration::FarmMigrationModule;
+
+    let rust_zero = rust_biguint!(0);
+    let owner = farm_setup.owner_address.clone();
+    let source_farm_address = farm_setup.farm_wrapper.address_ref().clone();
+
+    let b_mock = &mut farm_setup.blockchain_wrapper;
+    let next_farm_wrapper = b_mock.create_sc_account(
+        &rust_zero,
+        Some(&owner),
+        farm::contract_obj::<DebugApi> as fn() -> farm::ContractObj<DebugApi>,
+        FARM_WASM_PATH,
+    );
+    b_mock
+        .execute_tx(&owner, &next_farm_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_token_id!(MEX_TOKEN_ID),
+                managed_token_id!(LP_TOKEN_ID),
+                managed_biguint!(DIVISION_SAFETY_CONSTANT),
+                managed_address!(&Address::zero()),
+            );
+
+            sc.farm_token()
+                .set_token_id(&managed_token_id!(NEXT_FARM_TOKEN_ID));
+            sc.per_block_reward_amount()
+                .set(&managed_biguint!(PER_BLOCK_REWARD_AMOUNT));
+            sc.minimum_farming_epochs().set(&MIN_FARMING_EPOCHS);
+            sc.penalty_percent().set(&PENALTY_PERCENT);
+
+            sc.state().set(&State::Active);
+            sc.produce_rewards_enabled().set(&true);
+
+            sc.add_migration_source(managed_address!(&source_farm_address));
+        })
+        .assert_ok();
+
+    b_mock.set_esdt_local_roles(
+        next_farm_wrapper.address_ref(),
+        NEXT_FARM_TOKEN_ID,
+        &[
+            EsdtLocalRole::NftCreate,
+            EsdtLocalRole::NftAddQuantity,
+            EsdtLocalRole::NftBurn,
+        ][..],
+    );
+    b_mock.set_esdt_local_roles(
+        next_farm_wrapper.address_ref(),
+        MEX_TOKEN_ID,
+        &[EsdtLocalRole::Mint][..],
+    );
+
+    b_mock
+        .execute_tx(&owner, &farm_setup.farm_wrapper, &rust_zero, |sc| {
+            sc.set_migration_destination(managed_address!(next_farm_wrapper.address_ref()));
+        })
+        .assert_ok();
+
+    next_farm_wrapper
+}
+
+#[test]
+fn test_migration_to_next_farm() {
+    use farm_migration::FarmMigrationModule;
+    use rewards::RewardsModule;
+
+    let mut farm_setup = setup_farm(farm::contract_obj);
+
+    let farm_in_amount = 100_000_000;
+    enter_farm(
+        &mut farm_setup,
+        farm_in_amount,
+        &[],
+        1,
+        0,
+        0,
+        0,
+        farm_in_amount,
+        0,
+    );
+
+    set_block_nonce(&mut farm_setup, 10);
+    let next_farm_wrapper = setup_next_farm(&mut farm_setup);
+
+    let user = farm_setup.user_address.clone();
+    let owner = farm_setup.owner_address.clone();
+    let source_farm_address = farm_setup.farm_wrapper.address_ref().clone();
+    let b_mock = &mut farm_setup.blockchain_wrapper;
+
+    // positions can only be migrated after the source farm stopped producing rewards
+    b_mock
+        .execute_esdt_transfer(
+            &user,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            1,
+            &rust_biguint!(farm_in_amount),
+            |sc| {
+                let _ = sc.migrate_to_next_farm();
+            },
+        )
+        .assert_user_error("Rewards not stopped");
+
+    b_mock
+        .execute_tx(&owner, &farm_setup.farm_wrapper, &rust_biguint!(0), |sc| {
+            sc.end_produce_rewards();
+        })
+        .assert_ok();
+
+    // the rewards earned in the source farm are paid out by the source farm
+    set_block_nonce(&mut farm_setup, 15);
+    let expected_source_rewards = 10 * PER_BLOCK_REWARD_AMOUNT;
+    let b_mock = &mut farm_setup.blockchain_wrapper;
+    b_mock
+        .execute_esdt_transfer(
+            &user,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            1,
+            &rust_biguint!(farm_in_amount),
+            |sc| {
+                let (new_position, rewards) = sc.migrate_to_next_farm().into_tuple();
+                assert_eq!(
+                    new_position.token_identifier,
+                    managed_token_id!(NEXT_FARM_TOKEN_ID)
+                );
+                assert_eq!(new_position.amount, managed_biguint!(farm_in_amount));
+                assert_eq!(rewards.amount, managed_biguint!(expected_source_rewards));
+
+                assert_eq!(sc.farm_token_supply().get(), managed_biguint!(0));
+                assert_eq!(sc.reward_reserve().get(), managed_biguint!(0));
+            },
+        )
+        .assert_ok();
+
+    b_mock.check_esdt_balance(&user, MEX_TOKEN_ID, &rust_biguint!(expected_source_rewards));
+    b_mock
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            let (positions, amount) = sc.get_outgoing_migration_progress().into_tuple();
+            assert_eq!(positions, 1);
+            assert_eq!(amount, managed_biguint!(farm_in_amount));
+        })
+        .assert_ok();
+
+    // the new position starts from the current reward per share of the destination farm
+    b_mock
+        .execute_query(&next_farm_wrapper, |sc| {
+            let (positions, amount) = sc
+                .get_incoming_migration_progress(managed_address!(&source_farm_address))
+                .into_tuple();
+            assert_eq!(positions, 1);
+            assert_eq!(amount, managed_biguint!(farm_in_amount));
+            assert_eq!(sc.reward_per_share().get(), managed_biguint!(0));
+        })
+        .assert_ok();
+
+    let _ = DebugApi::dummy();
+    b_mock.check_nft_balance(
+        &user,
+        NEXT_FARM_TOKEN_ID,
+        1,
+        &rust_biguint!(farm_in_amount),
+        Some(&FarmTokenAttributes::<DebugApi> {
+            reward_per_share: managed_biguint!(0),
+            original_entering_epoch: 0,
+            entering_epoch: 0,
+            initial_farming_amount: managed_biguint!(farm_in_amount),
+            compounded_reward: managed_biguint!(0),
+            current_farm_amount: managed_biguint!(farm_in_amount),
+        }),
+    );
+    let _ = TxContextStack::static_pop();
+
+    // only the rewards generated after the migration are paid by the destination farm
+    b_mock.set_block_nonce(25);
+    b_mock
+        .execute_esdt_transfer(
+            &user,
+            &next_farm_wrapper,
+            NEXT_FARM_TOKEN_ID,
+            1,
+            &rust_biguint!(farm_in_amount),
+            |sc| {
+                let (_, rewards) = sc.claim_rewards().into_tuple();
+                assert_eq!(
+                    rewards.amount,
+                    managed_biguint!(10 * PER_BLOCK_REWARD_AMOUNT)
+                );
+            },
+        )
+        .assert_ok();
+    b_mock.check_esdt_balance(
+        &user,
+        MEX_TOKEN_ID,
+        &rust_biguint!(expected_source_rewards + 10 * PER_BLOCK_REWARD_AMOUNT),
+    );
+}
+
 #[test]
 fn test_farm_through_simple_lock() {
     use elrond_wasm::storage::mappers::StorageTokenWrapper;
@@ -3,6 +3,7 @@ use std::ops::Mul;
 use common_structs::{
     LockedAssetTokenAttributesEx, UnlockMilestone, UnlockMilestoneEx, UnlockScheduleEx,
 };
+use elrond_wasm::elrond_codec::multi_types::OptionalValue;
 use elrond_wasm::storage::mappers::StorageTokenWrapper;
 use elrond_wasm::types::{
     Address, BigUint, EsdtLocalRole, ManagedAddress, ManagedVec, MultiValueEncoded,
@@ -20,6 +21,7 @@ use factory::locked_asset::LockedAssetModule;
 use factory::*;
 use farm_token::FarmTokenModule;
 use farm_with_lock::custom_rewards::CustomRewardsModule;
+use farm_with_lock::reward_unlock_options::RewardUnlockOptionsModule;
 use farm_with_lock::*;
 use pausable::{PausableModule, State};
 use rewards::*;
@@ -294,7 +296,7 @@ fn exit_farm<FarmObjBuilder, FactoryObjBuilder>(
             farm_token_nonce,
             &farm_out_amount.clone(),
             |sc| {
-                let multi_result = sc.exit_farm();
+                let multi_result = sc.exit_farm(OptionalValue::None);
 
                 let (first_result, second_result) = multi_result.into_tuple();
 
@@ -1063,3 +1065,435 @@ fn test_lock_rv_earn_twice() {
 
     let _ = TxContextStack::static_pop();
 }
+
+#[test]
+fn test_lock_reward_rate_views() {
+    let _ = DebugApi::dummy();
+
+    let per_block_reward_amount = rust_biguint!(100);
+    let mut farm_setup = setup_farm(
+        farm_with_lock::contract_obj,
+        factory::contract_obj,
+        per_block_reward_amount,
+    );
+    let alice = new_address_with_lp_tokens(&mut farm_setup, rust_biguint!(5_000));
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            assert_eq!(
+                sc.get_reward_per_block_per_farming_token(),
+                managed_biguint!(0)
+            );
+            assert_eq!(
+                sc.get_annual_reward_per_farming_token(),
+                managed_biguint!(0)
+            );
+        })
+        .assert_ok();
+
+    enter_farm(&mut farm_setup, &alice, rust_biguint!(1_000));
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            let expected_per_block = 100 * DIVISION_SAFETY_CONSTANT / 1_000;
+            assert_eq!(
+                sc.get_reward_per_block_per_farming_token(),
+                managed_biguint!(expected_per_block)
+            );
+            assert_eq!(
+                sc.get_annual_reward_per_farming_token(),
+                managed_biguint!(expected_per_block * BLOCKS_IN_YEAR)
+            );
+        })
+        .assert_ok();
+
+    let _ = TxContextStack::static_pop();
+}
+
+fn add_short_reward_unlock_option<FarmObjBuilder, FactoryObjBuilder>(
+    farm_setup: &mut FarmSetup<FarmObjBuilder, FactoryObjBuilder>,
+) where
+    FarmObjBuilder: 'static + Copy + Fn() -> farm_with_lock::ContractObj<DebugApi>,
+    FactoryObjBuilder: 'static + Copy + Fn() -> factory::ContractObj<DebugApi>,
+{
+    // 0.75x rewards, fully unlocked after 60 epochs
+    let owner_address = farm_setup.owner_address.clone();
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut unlock_milestones = MultiValueEncoded::new();
+                unlock_milestones.push(UnlockMilestone {
+                    unlock_epoch: 60,
+                    unlock_percent: 100,
+                });
+                let option_id = sc.add_reward_unlock_option(7_500, unlock_milestones);
+                assert_eq!(option_id, 1);
+            },
+        )
+        .assert_ok();
+}
+
+#[test]
+fn test_lock_custom_reward_unlock_option() {
+    let _ = DebugApi::dummy();
+
+    let per_block_reward_amount = rust_biguint!(100);
+    let mut farm_setup = setup_farm(
+        farm_with_lock::contract_obj,
+        factory::contract_obj,
+        per_block_reward_amount,
+    );
+    let alice = new_address_with_lp_tokens(&mut farm_setup, rust_biguint!(5_000));
+
+    // no bonus is allowed until the owner sets a maximum multiplier
+    let owner_address = farm_setup.owner_address.clone();
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut unlock_milestones = MultiValueEncoded::new();
+                unlock_milestones.push(UnlockMilestone {
+                    unlock_epoch: 60,
+                    unlock_percent: 100,
+                });
+                let _ = sc.add_reward_unlock_option(15_000, unlock_milestones);
+            },
+        )
+        .assert_user_error("Bad parameters");
+
+    add_short_reward_unlock_option(&mut farm_setup);
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(&alice, &farm_setup.farm_wrapper, &rust_biguint!(0), |sc| {
+            sc.select_reward_unlock_option(1);
+        })
+        .assert_ok();
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(&alice, &farm_setup.farm_wrapper, &rust_biguint!(0), |sc| {
+            sc.select_reward_unlock_option(2);
+        })
+        .assert_user_error("Unknown unlock option");
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            assert_eq!(
+                sc.get_user_effective_reward_unlock_option(managed_address!(&alice)),
+                1
+            );
+            assert_eq!(sc.get_reward_unlock_options().len(), 1);
+        })
+        .assert_ok();
+
+    step(
+        &mut farm_setup,
+        1,
+        Action::EnterFarm(alice.clone(), rust_biguint!(100)),
+        Expected::new(rust_biguint!(200), rust_biguint!(0), rust_biguint!(100)),
+    );
+    step(
+        &mut farm_setup,
+        9,
+        Action::ExitFarm(
+            alice,
+            1,
+            rust_biguint!(100),
+            rust_biguint!(600),
+            LockedAssetTokenAttributesEx {
+                unlock_schedule: UnlockScheduleEx {
+                    unlock_milestones: ManagedVec::from(vec![UnlockMilestoneEx {
+                        unlock_epoch: 60,
+                        unlock_percent: 100_000,
+                    }]),
+                },
+                is_merged: false,
+            },
+        ),
+        Expected::new(
+            rust_biguint!(200),
+            rust_biguint!(8_000_000_000_000),
+            rust_biguint!(0),
+        ),
+    );
+
+    let _ = TxContextStack::static_pop();
+}
+
+#[test]
+fn test_lock_longer_reward_unlock_option_bonus() {
+    let _ = DebugApi::dummy();
+
+    let per_block_reward_amount = rust_biguint!(100);
+    let mut farm_setup = setup_farm(
+        farm_with_lock::contract_obj,
+        factory::contract_obj,
+        per_block_reward_amount,
+    );
+    let alice = new_address_with_lp_tokens(&mut farm_setup, rust_biguint!(5_000));
+
+    let owner_address = farm_setup.owner_address.clone();
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_max_reward_multiplier(20_000);
+            },
+        )
+        .assert_ok();
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut unlock_milestones = MultiValueEncoded::new();
+                unlock_milestones.push(UnlockMilestone {
+                    unlock_epoch: 360,
+                    unlock_percent: 100,
+                });
+                let _ = sc.add_reward_unlock_option(25_000, unlock_milestones);
+            },
+        )
+        .assert_user_error("Bad parameters");
+
+    // 1.5x rewards, fully unlocked after 360 epochs
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut unlock_milestones = MultiValueEncoded::new();
+                unlock_milestones.push(UnlockMilestone {
+                    unlock_epoch: 360,
+                    unlock_percent: 100,
+                });
+                let option_id = sc.add_reward_unlock_option(15_000, unlock_milestones);
+                assert_eq!(option_id, 1);
+            },
+        )
+        .assert_ok();
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(&alice, &farm_setup.farm_wrapper, &rust_biguint!(0), |sc| {
+            sc.select_reward_unlock_option(1);
+        })
+        .assert_ok();
+
+    step(
+        &mut farm_setup,
+        1,
+        Action::EnterFarm(alice.clone(), rust_biguint!(100)),
+        Expected::new(rust_biguint!(200), rust_biguint!(0), rust_biguint!(100)),
+    );
+
+    // the factory creates the 800 rewards of the position plus the 400 bonus
+    step(
+        &mut farm_setup,
+        9,
+        Action::ExitFarm(
+            alice,
+            1,
+            rust_biguint!(100),
+            rust_biguint!(1_200),
+            LockedAssetTokenAttributesEx {
+                unlock_schedule: UnlockScheduleEx {
+                    unlock_milestones: ManagedVec::from(vec![UnlockMilestoneEx {
+                        unlock_epoch: 360,
+                        unlock_percent: 100_000,
+                    }]),
+                },
+                is_merged: false,
+            },
+        ),
+        Expected::new(
+            rust_biguint!(200),
+            rust_biguint!(8_000_000_000_000),
+            rust_biguint!(0),
+        ),
+    );
+
+    let _ = TxContextStack::static_pop();
+}
+
+#[test]
+fn test_lock_reward_unlock_option_through_trusted_proxy() {
+    let _ = DebugApi::dummy();
+
+    let per_block_reward_amount = rust_biguint!(100);
+    let mut farm_setup = setup_farm(
+        farm_with_lock::contract_obj,
+        factory::contract_obj,
+        per_block_reward_amount,
+    );
+    let alice = new_address_with_lp_tokens(&mut farm_setup, rust_biguint!(5_000));
+    let proxy = new_address_with_lp_tokens(&mut farm_setup, rust_biguint!(5_000));
+    add_short_reward_unlock_option(&mut farm_setup);
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(&alice, &farm_setup.farm_wrapper, &rust_biguint!(0), |sc| {
+            sc.select_reward_unlock_option(1);
+        })
+        .assert_ok();
+
+    step(
+        &mut farm_setup,
+        1,
+        Action::EnterFarm(proxy.clone(), rust_biguint!(100)),
+        Expected::new(rust_biguint!(200), rust_biguint!(0), rust_biguint!(100)),
+    );
+    farm_setup.blockchain_wrapper.set_block_nonce(10);
+
+    // only trusted proxies may pass the original caller
+    farm_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &proxy,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            1,
+            &rust_biguint!(100),
+            |sc| {
+                let _ = sc.exit_farm(OptionalValue::Some(managed_address!(&alice)));
+            },
+        )
+        .assert_user_error("Caller is not a trusted proxy");
+
+    let owner_address = farm_setup.owner_address.clone();
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.add_trusted_proxy(managed_address!(&proxy));
+            },
+        )
+        .assert_ok();
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &proxy,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            1,
+            &rust_biguint!(100),
+            |sc| {
+                let _ = sc.exit_farm(OptionalValue::Some(managed_address!(&alice)));
+            },
+        )
+        .assert_ok();
+
+    // the proxy receives the rewards, with the unlock option selected by alice
+    farm_setup.blockchain_wrapper.check_nft_balance(
+        &proxy,
+        LKMEX_TOKEN_ID,
+        1,
+        &rust_biguint!(600),
+        Some(&LockedAssetTokenAttributesEx::<DebugApi> {
+            unlock_schedule: UnlockScheduleEx {
+                unlock_milestones: ManagedVec::from(vec![UnlockMilestoneEx {
+                    unlock_epoch: 60,
+                    unlock_percent: 100_000,
+                }]),
+            },
+            is_merged: false,
+        }),
+    );
+
+    let _ = TxContextStack::static_pop();
+}
+
+#[test]
+fn test_lock_emergency_exit() {
+    let _ = DebugApi::dummy();
+
+    let per_block_reward_amount = rust_biguint!(100);
+    let mut farm_setup = setup_farm(
+        farm_with_lock::contract_obj,
+        factory::contract_obj,
+        per_block_reward_amount,
+    );
+    let alice = new_address_with_lp_tokens(&mut farm_setup, rust_biguint!(5_000));
+
+    farm_setup.blockchain_wrapper.set_block_nonce(1);
+    enter_farm(&mut farm_setup, &alice, rust_biguint!(1_000));
+    farm_setup.blockchain_wrapper.set_block_nonce(10);
+
+    let owner_address = farm_setup.owner_address.clone();
+    let b_mock = &mut farm_setup.blockchain_wrapper;
+    b_mock
+        .execute_esdt_transfer(
+            &alice,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            1,
+            &rust_biguint!(1_000),
+            |sc| {
+                let _ = sc.emergency_exit();
+            },
+        )
+        .assert_user_error("Not paused");
+
+    b_mock
+        .execute_tx(
+            &owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.pause();
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .execute_esdt_transfer(
+            &alice,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            1,
+            &rust_biguint!(1_000),
+            |sc| {
+                let payment = sc.emergency_exit();
+                assert_eq!(payment.token_identifier, managed_token_id!(LP_TOKEN_ID));
+                assert_eq!(payment.amount, managed_biguint!(1_000));
+            },
+        )
+        .assert_ok();
+
+    // the pending rewards are forfeited, no locked rewards are created
+    b_mock.check_esdt_balance(&alice, LP_TOKEN_ID, &rust_biguint!(5_000));
+    assert_eq!(
+        b_mock.get_esdt_balance(&alice, LKMEX_TOKEN_ID, 1),
+        rust_biguint!(0)
+    );
+    b_mock
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            assert_eq!(sc.farm_token_supply().get(), managed_biguint!(0));
+        })
+        .assert_ok();
+
+    let _ = TxContextStack::static_pop();
+}
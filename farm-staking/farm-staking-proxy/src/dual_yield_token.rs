@@ -1,12 +1,49 @@
 elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
-#[derive(TypeAbi, TopEncode, TopDecode, PartialEq, Debug)]
+use elrond_wasm::elrond_codec::{NestedDecodeInput, TopDecodeInput};
+
+use crate::lp_farm_registry::INIT_LP_FARM_ENTRY_ID;
+
+#[derive(TypeAbi, TopEncode, PartialEq, Debug)]
 pub struct DualYieldTokenAttributes<M: ManagedTypeApi> {
     pub lp_farm_token_nonce: u64,
     pub lp_farm_token_amount: BigUint<M>,
     pub staking_farm_token_nonce: u64,
     pub staking_farm_token_amount: BigUint<M>,
+    pub lp_farm_entry_id: u64,
+}
+
+/// Dual yield tokens created before the LP farm registry do not have the entry ID,
+/// in which case they belong to the entry created on init.
+impl<M: ManagedTypeApi> TopDecode for DualYieldTokenAttributes<M> {
+    fn top_decode<I>(input: I) -> Result<Self, DecodeError>
+    where
+        I: TopDecodeInput,
+    {
+        let mut buffer = input.into_nested_buffer();
+        let lp_farm_token_nonce = u64::dep_decode(&mut buffer)?;
+        let lp_farm_token_amount = BigUint::dep_decode(&mut buffer)?;
+        let staking_farm_token_nonce = u64::dep_decode(&mut buffer)?;
+        let staking_farm_token_amount = BigUint::dep_decode(&mut buffer)?;
+        let lp_farm_entry_id = if buffer.is_depleted() {
+            INIT_LP_FARM_ENTRY_ID
+        } else {
+            u64::dep_decode(&mut buffer)?
+        };
+
+        if !buffer.is_depleted() {
+            return Err(DecodeError::INPUT_TOO_LONG);
+        }
+
+        Ok(DualYieldTokenAttributes {
+            lp_farm_token_nonce,
+            lp_farm_token_amount,
+            staking_farm_token_nonce,
+            staking_farm_token_amount,
+            lp_farm_entry_id,
+        })
+    }
 }
 
 impl<M: ManagedTypeApi> DualYieldTokenAttributes<M> {
@@ -46,12 +83,14 @@ pub trait DualYieldTokenModule:
     fn create_and_send_dual_yield_tokens(
         &self,
         to: &ManagedAddress,
+        lp_farm_entry_id: u64,
         lp_farm_token_nonce: u64,
         lp_farm_token_amount: BigUint,
         staking_farm_token_nonce: u64,
         staking_farm_token_amount: BigUint,
     ) -> EsdtTokenPayment<Self::Api> {
         let payment = self.create_dual_yield_tokens(
+            lp_farm_entry_id,
             lp_farm_token_nonce,
             lp_farm_token_amount,
             staking_farm_token_nonce,
@@ -69,6 +108,7 @@ pub trait DualYieldTokenModule:
 
     fn create_dual_yield_tokens(
         &self,
+        lp_farm_entry_id: u64,
         lp_farm_token_nonce: u64,
         lp_farm_token_amount: BigUint,
         staking_farm_token_nonce: u64,
@@ -79,6 +119,7 @@ pub trait DualYieldTokenModule:
             lp_farm_token_amount,
             staking_farm_token_nonce,
             staking_farm_token_amount,
+            lp_farm_entry_id,
         };
         let amount = attributes.get_total_dual_yield_tokens_for_position();
 
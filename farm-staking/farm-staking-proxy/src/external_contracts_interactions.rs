@@ -3,10 +3,14 @@ elrond_wasm::imports!();
 use core::mem::swap;
 
 use farm::farm_token_merge::ProxyTrait as _;
+use pair::config::ProxyTrait as _;
 use pair::safe_price::ProxyTrait as _;
 
+use crate::lp_farm_registry::LpFarmEntry;
 use crate::result_types::*;
-use farm_staking::{ClaimRewardsResultType, EnterFarmResultType, ExitFarmResultType};
+use farm_staking::{
+    ClaimRewardsResultType, CompoundRewardsResultType, EnterFarmResultType, ExitFarmResultType,
+};
 use pair::RemoveLiquidityResultType;
 
 pub type SafePriceResult<Api> = MultiValue2<EsdtTokenPayment<Api>, EsdtTokenPayment<Api>>;
@@ -19,13 +23,13 @@ pub trait ExternalContractsInteractionsModule:
 
     fn lp_farm_claim_rewards(
         &self,
-        lp_farm_token_id: TokenIdentifier,
+        lp_farm_entry: &LpFarmEntry<Self::Api>,
         lp_farm_token_nonce: u64,
         lp_farm_token_amount: BigUint,
     ) -> LpFarmClaimRewardsResult<Self::Api> {
-        let lp_farm_address = self.lp_farm_address().get();
+        let lp_farm_token_id = &lp_farm_entry.lp_farm_token_id;
         let lp_farm_result: ClaimRewardsResultType<Self::Api> = self
-            .lp_farm_proxy_obj(lp_farm_address)
+            .lp_farm_proxy_obj(lp_farm_entry.lp_farm_address.clone())
             .claim_rewards()
             .add_esdt_token_transfer(
                 lp_farm_token_id.clone(),
@@ -38,7 +42,7 @@ pub trait ExternalContractsInteractionsModule:
         self.swap_payments_if_wrong_order(
             &mut new_lp_farm_tokens,
             &mut lp_farm_rewards,
-            &lp_farm_token_id,
+            lp_farm_token_id,
             b"lp_farm_claim_rewards",
         );
 
@@ -50,23 +54,25 @@ pub trait ExternalContractsInteractionsModule:
 
     fn lp_farm_exit(
         &self,
+        lp_farm_entry: &LpFarmEntry<Self::Api>,
         lp_farm_token_nonce: u64,
         lp_farm_token_amount: BigUint,
     ) -> LpFarmExitResult<Self::Api> {
-        let lp_farm_token_id = self.lp_farm_token_id().get();
-        let lp_farm_address = self.lp_farm_address().get();
         let exit_farm_result: ExitFarmResultType<Self::Api> = self
-            .lp_farm_proxy_obj(lp_farm_address)
+            .lp_farm_proxy_obj(lp_farm_entry.lp_farm_address.clone())
             .exit_farm()
-            .add_esdt_token_transfer(lp_farm_token_id, lp_farm_token_nonce, lp_farm_token_amount)
+            .add_esdt_token_transfer(
+                lp_farm_entry.lp_farm_token_id.clone(),
+                lp_farm_token_nonce,
+                lp_farm_token_amount,
+            )
             .execute_on_dest_context();
         let (mut lp_tokens, mut lp_farm_rewards) = exit_farm_result.into_tuple();
-        let expected_lp_token_id = self.lp_token_id().get();
 
         self.swap_payments_if_wrong_order(
             &mut lp_tokens,
             &mut lp_farm_rewards,
-            &expected_lp_token_id,
+            &lp_farm_entry.lp_token_id,
             b"lp_farm_exit",
         );
 
@@ -78,6 +84,7 @@ pub trait ExternalContractsInteractionsModule:
 
     fn merge_lp_farm_tokens(
         &self,
+        lp_farm_entry: &LpFarmEntry<Self::Api>,
         base_lp_token: EsdtTokenPayment<Self::Api>,
         mut additional_lp_tokens: ManagedVec<EsdtTokenPayment<Self::Api>>,
     ) -> EsdtTokenPayment<Self::Api> {
@@ -87,8 +94,7 @@ pub trait ExternalContractsInteractionsModule:
 
         additional_lp_tokens.push(base_lp_token);
 
-        let lp_farm_address = self.lp_farm_address().get();
-        self.lp_farm_proxy_obj(lp_farm_address)
+        self.lp_farm_proxy_obj(lp_farm_entry.lp_farm_address.clone())
             .merge_farm_tokens()
             .with_multi_token_transfer(additional_lp_tokens)
             .execute_on_dest_context()
@@ -146,14 +152,46 @@ pub trait ExternalContractsInteractionsModule:
         }
     }
 
+    fn staking_farm_compound_rewards(
+        &self,
+        staking_farm_token_nonce: u64,
+        staking_farm_token_amount: BigUint,
+        new_staking_farm_value: BigUint,
+        opt_staking_tokens: Option<EsdtTokenPayment<Self::Api>>,
+    ) -> EsdtTokenPayment<Self::Api> {
+        let staking_farm_token_id = self.staking_farm_token_id().get();
+        let mut payments = ManagedVec::from_single_item(EsdtTokenPayment::new(
+            staking_farm_token_id,
+            staking_farm_token_nonce,
+            staking_farm_token_amount,
+        ));
+        if let Some(staking_tokens) = opt_staking_tokens {
+            payments.push(staking_tokens);
+        }
+
+        let staking_farm_address = self.staking_farm_address().get();
+        let new_staking_farm_tokens: CompoundRewardsResultType<Self::Api> = self
+            .staking_farm_proxy_obj(staking_farm_address)
+            .compound_rewards_through_proxy(new_staking_farm_value)
+            .with_multi_token_transfer(payments)
+            .execute_on_dest_context();
+
+        new_staking_farm_tokens
+    }
+
+    /// Without staking tokens, only the staking position is exited,
+    /// and the unbond tokens cover the compounded rewards only.
     fn staking_farm_unstake(
         &self,
-        staking_tokens: EsdtTokenPayment<Self::Api>,
+        opt_staking_tokens: Option<EsdtTokenPayment<Self::Api>>,
         farm_token_nonce: u64,
         farm_token_amount: BigUint,
     ) -> StakingFarmExitResult<Self::Api> {
         let staking_farm_token_id = self.staking_farm_token_id().get();
-        let mut payments = ManagedVec::from_single_item(staking_tokens);
+        let mut payments = ManagedVec::new();
+        if let Some(staking_tokens) = opt_staking_tokens {
+            payments.push(staking_tokens);
+        }
         payments.push(EsdtTokenPayment::new(
             staking_farm_token_id.clone(),
             farm_token_nonce,
@@ -185,13 +223,13 @@ pub trait ExternalContractsInteractionsModule:
 
     fn pair_remove_liquidity(
         &self,
+        lp_farm_entry: &LpFarmEntry<Self::Api>,
         lp_tokens: EsdtTokenPayment<Self::Api>,
         pair_first_token_min_amount: BigUint,
         pair_second_token_min_amount: BigUint,
     ) -> PairRemoveLiquidityResult<Self::Api> {
-        let pair_address = self.pair_address().get();
         let pair_withdraw_result: RemoveLiquidityResultType<Self::Api> = self
-            .pair_proxy_obj(pair_address)
+            .pair_proxy_obj(lp_farm_entry.pair_address.clone())
             .remove_liquidity(pair_first_token_min_amount, pair_second_token_min_amount)
             .add_esdt_token_transfer(
                 lp_tokens.token_identifier,
@@ -218,10 +256,13 @@ pub trait ExternalContractsInteractionsModule:
         }
     }
 
-    fn get_lp_tokens_safe_price(&self, lp_tokens_amount: BigUint) -> BigUint {
-        let pair_address = self.pair_address().get();
+    fn get_lp_tokens_safe_price(
+        &self,
+        lp_farm_entry: &LpFarmEntry<Self::Api>,
+        lp_tokens_amount: BigUint,
+    ) -> BigUint {
         let result: SafePriceResult<Self::Api> = self
-            .pair_proxy_obj(pair_address)
+            .pair_proxy_obj(lp_farm_entry.pair_address.clone())
             .update_and_get_tokens_for_given_position_with_safe_price(lp_tokens_amount)
             .execute_on_dest_context();
         let (first_token_info, second_token_info) = result.into_tuple();
@@ -236,6 +277,23 @@ pub trait ExternalContractsInteractionsModule:
         }
     }
 
+    fn require_pair_has_staking_token(&self, pair_address: &ManagedAddress) {
+        let first_token_id: TokenIdentifier = self
+            .pair_proxy_obj(pair_address.clone())
+            .first_token_id()
+            .execute_on_dest_context();
+        let second_token_id: TokenIdentifier = self
+            .pair_proxy_obj(pair_address.clone())
+            .second_token_id()
+            .execute_on_dest_context();
+
+        let staking_token_id = self.staking_token_id().get();
+        require!(
+            first_token_id == staking_token_id || second_token_id == staking_token_id,
+            "Pair does not have the staking token"
+        );
+    }
+
     fn swap_payments_if_wrong_order(
         &self,
         first_payment: &mut EsdtTokenPayment<Self::Api>,
@@ -268,18 +326,10 @@ pub trait ExternalContractsInteractionsModule:
 
     // storage
 
-    #[view(getLpFarmAddress)]
-    #[storage_mapper("lpFarmAddress")]
-    fn lp_farm_address(&self) -> SingleValueMapper<ManagedAddress>;
-
     #[view(getStakingFarmAddress)]
     #[storage_mapper("stakingFarmAddress")]
     fn staking_farm_address(&self) -> SingleValueMapper<ManagedAddress>;
 
-    #[view(getPairAddress)]
-    #[storage_mapper("pairAddress")]
-    fn pair_address(&self) -> SingleValueMapper<ManagedAddress>;
-
     #[view(getStakingTokenId)]
     #[storage_mapper("stakingTokenId")]
     fn staking_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
@@ -287,8 +337,4 @@ pub trait ExternalContractsInteractionsModule:
     #[view(getFarmTokenId)]
     #[storage_mapper("farmTokenId")]
     fn staking_farm_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
-
-    #[view(getLpTokenId)]
-    #[storage_mapper("lpTokenId")]
-    fn lp_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
 }
@@ -4,17 +4,23 @@ elrond_wasm::imports!();
 
 pub mod dual_yield_token;
 pub mod external_contracts_interactions;
+pub mod lp_farm_registry;
 pub mod lp_farm_token;
 pub mod result_types;
 
 pub type StakeResult<Api> = EsdtTokenPayment<Api>;
 pub type ClaimDualYieldResult<Api> = MultiValueEncoded<Api, EsdtTokenPayment<Api>>;
+pub type CompoundDualYieldResult<Api> = MultiValueEncoded<Api, EsdtTokenPayment<Api>>;
 pub type UnstakeResult<Api> = MultiValueEncoded<Api, EsdtTokenPayment<Api>>;
 
+use lp_farm_registry::{LpFarmEntry, INIT_LP_FARM_ENTRY_ID};
+use result_types::PaymentsVec;
+
 #[elrond_wasm::contract]
 pub trait FarmStakingProxy:
     dual_yield_token::DualYieldTokenModule
     + external_contracts_interactions::ExternalContractsInteractionsModule
+    + lp_farm_registry::LpFarmRegistryModule
     + lp_farm_token::LpFarmTokenModule
     + token_merge::TokenMergeModule
     + elrond_wasm_modules::default_issue_callbacks::DefaultIssueCallbacksModule
@@ -30,43 +36,35 @@ pub trait FarmStakingProxy:
         staking_farm_token_id: TokenIdentifier,
         lp_token_id: TokenIdentifier,
     ) {
-        require!(
-            self.blockchain().is_smart_contract(&lp_farm_address),
-            "Invalid LP Farm address"
-        );
         require!(
             self.blockchain().is_smart_contract(&staking_farm_address),
             "Invalid Staking Farm address"
         );
-        require!(
-            self.blockchain().is_smart_contract(&pair_address),
-            "Invalid Pair address"
-        );
         require!(
             staking_token_id.is_valid_esdt_identifier(),
             "Invalid Staking token ID"
         );
-        require!(
-            lp_farm_token_id.is_valid_esdt_identifier(),
-            "Invalid Farm token ID"
-        );
         require!(
             staking_farm_token_id.is_valid_esdt_identifier(),
             "Invalid Staking Farm token ID"
         );
 
-        require!(
-            lp_token_id.is_valid_esdt_identifier(),
-            "Invalide LP token ID"
-        );
-
-        self.lp_farm_address().set(&lp_farm_address);
         self.staking_farm_address().set(&staking_farm_address);
-        self.pair_address().set(&pair_address);
         self.staking_token_id().set(&staking_token_id);
-        self.lp_farm_token_id().set(&lp_farm_token_id);
         self.staking_farm_token_id().set(&staking_farm_token_id);
-        self.lp_token_id().set(&lp_token_id);
+
+        // on upgrade, the init entry is kept as is
+        if self.last_lp_farm_entry_id().get() == 0 {
+            self.insert_lp_farm_entry(
+                INIT_LP_FARM_ENTRY_ID,
+                LpFarmEntry {
+                    pair_address,
+                    lp_farm_address,
+                    lp_token_id,
+                    lp_farm_token_id,
+                },
+            );
+        }
     }
 
     #[payable("*")]
@@ -78,11 +76,8 @@ pub trait FarmStakingProxy:
             .unwrap_or_else(|| sc_panic!("empty payments"));
         let additional_payments = payments.slice(1, payments.len()).unwrap_or_default();
 
-        let lp_farm_token_id = self.lp_farm_token_id().get();
-        require!(
-            lp_farm_token_payment.token_identifier == lp_farm_token_id,
-            "Invalid first payment"
-        );
+        let (lp_farm_entry_id, lp_farm_entry) =
+            self.get_active_lp_farm_entry_for_token(&lp_farm_token_payment.token_identifier);
         self.dual_yield_token()
             .require_all_same_token(&additional_payments);
 
@@ -91,6 +86,10 @@ pub trait FarmStakingProxy:
         let mut additional_lp_farm_tokens = ManagedVec::new();
         for p in &additional_payments {
             let attributes = self.get_dual_yield_token_attributes(p.token_nonce);
+            require!(
+                attributes.lp_farm_entry_id == lp_farm_entry_id,
+                "Dual yield tokens of a different LP farm"
+            );
 
             additional_staking_farm_tokens.push(EsdtTokenPayment::new(
                 staking_farm_token_id.clone(),
@@ -99,7 +98,7 @@ pub trait FarmStakingProxy:
             ));
 
             additional_lp_farm_tokens.push(EsdtTokenPayment::new(
-                lp_farm_token_id.clone(),
+                lp_farm_entry.lp_farm_token_id.clone(),
                 attributes.lp_farm_token_nonce,
                 self.get_lp_farm_token_amount_equivalent(&attributes, &p.amount),
             ));
@@ -108,20 +107,25 @@ pub trait FarmStakingProxy:
         }
 
         let lp_tokens_in_farm = self.get_lp_tokens_in_farm_position(
+            &lp_farm_entry.lp_farm_token_id,
             lp_farm_token_payment.token_nonce,
             &lp_farm_token_payment.amount,
         );
-        let staking_token_amount = self.get_lp_tokens_safe_price(lp_tokens_in_farm);
+        let staking_token_amount = self.get_lp_tokens_safe_price(&lp_farm_entry, lp_tokens_in_farm);
         let received_staking_farm_token = self
             .staking_farm_enter(staking_token_amount, additional_staking_farm_tokens)
             .received_staking_farm_token;
 
-        let merged_lp_farm_tokens =
-            self.merge_lp_farm_tokens(lp_farm_token_payment, additional_lp_farm_tokens);
+        let merged_lp_farm_tokens = self.merge_lp_farm_tokens(
+            &lp_farm_entry,
+            lp_farm_token_payment,
+            additional_lp_farm_tokens,
+        );
 
         let caller = self.blockchain().get_caller();
         self.create_and_send_dual_yield_tokens(
             &caller,
+            lp_farm_entry_id,
             merged_lp_farm_tokens.token_nonce,
             merged_lp_farm_tokens.amount,
             received_staking_farm_token.token_nonce,
@@ -137,25 +141,29 @@ pub trait FarmStakingProxy:
         self.dual_yield_token().require_same_token(&payment_token);
 
         let attributes = self.get_dual_yield_token_attributes(payment_nonce);
+        let lp_farm_entry = self.get_lp_farm_entry(attributes.lp_farm_entry_id);
 
         let staking_farm_token_id = self.staking_farm_token_id().get();
         let staking_farm_token_nonce = attributes.staking_farm_token_nonce;
         let staking_farm_token_amount =
             self.get_staking_farm_token_amount_equivalent(&payment_amount);
 
-        let lp_farm_token_id = self.lp_farm_token_id().get();
         let lp_farm_token_nonce = attributes.lp_farm_token_nonce;
         let lp_farm_token_amount =
             self.get_lp_farm_token_amount_equivalent(&attributes, &payment_amount);
 
-        let lp_tokens_in_position =
-            self.get_lp_tokens_in_farm_position(lp_farm_token_nonce, &lp_farm_token_amount);
-        let new_staking_farm_value = self.get_lp_tokens_safe_price(lp_tokens_in_position);
+        let lp_tokens_in_position = self.get_lp_tokens_in_farm_position(
+            &lp_farm_entry.lp_farm_token_id,
+            lp_farm_token_nonce,
+            &lp_farm_token_amount,
+        );
+        let new_staking_farm_value =
+            self.get_lp_tokens_safe_price(&lp_farm_entry, lp_tokens_in_position);
 
         self.burn_dual_yield_tokens(payment_nonce, &payment_amount);
 
         let lp_farm_claim_rewards_result =
-            self.lp_farm_claim_rewards(lp_farm_token_id, lp_farm_token_nonce, lp_farm_token_amount);
+            self.lp_farm_claim_rewards(&lp_farm_entry, lp_farm_token_nonce, lp_farm_token_amount);
         let staking_farm_claim_rewards_result = self.staking_farm_claim_rewards(
             staking_farm_token_id,
             staking_farm_token_nonce,
@@ -166,6 +174,7 @@ pub trait FarmStakingProxy:
         let new_lp_farm_tokens = lp_farm_claim_rewards_result.new_lp_farm_tokens;
         let new_staking_farm_tokens = staking_farm_claim_rewards_result.new_staking_farm_tokens;
         let new_dual_yield_tokens = self.create_dual_yield_tokens(
+            attributes.lp_farm_entry_id,
             new_lp_farm_tokens.token_nonce,
             new_lp_farm_tokens.amount,
             new_staking_farm_tokens.token_nonce,
@@ -200,39 +209,134 @@ pub trait FarmStakingProxy:
         user_output_payments.into()
     }
 
+    /// Compounds the rewards of both positions and sends back a single dual yield token.
+    /// The LP farm rewards are added to the staking position if they are the staking token,
+    /// otherwise they are sent to the caller. The staking farm rewards are always compounded.
+    #[payable("*")]
+    #[endpoint(compoundDualYield)]
+    fn compound_dual_yield(&self) -> CompoundDualYieldResult<Self::Api> {
+        let (payment_token, payment_nonce, payment_amount) =
+            self.call_value().single_esdt().into_tuple();
+        self.dual_yield_token().require_same_token(&payment_token);
+
+        let attributes = self.get_dual_yield_token_attributes(payment_nonce);
+        let lp_farm_entry = self.get_lp_farm_entry(attributes.lp_farm_entry_id);
+
+        let staking_farm_token_amount =
+            self.get_staking_farm_token_amount_equivalent(&payment_amount);
+        let lp_farm_token_nonce = attributes.lp_farm_token_nonce;
+        let lp_farm_token_amount =
+            self.get_lp_farm_token_amount_equivalent(&attributes, &payment_amount);
+
+        let lp_tokens_in_position = self.get_lp_tokens_in_farm_position(
+            &lp_farm_entry.lp_farm_token_id,
+            lp_farm_token_nonce,
+            &lp_farm_token_amount,
+        );
+        let new_staking_farm_value =
+            self.get_lp_tokens_safe_price(&lp_farm_entry, lp_tokens_in_position);
+
+        self.burn_dual_yield_tokens(payment_nonce, &payment_amount);
+
+        let lp_farm_claim_rewards_result =
+            self.lp_farm_claim_rewards(&lp_farm_entry, lp_farm_token_nonce, lp_farm_token_amount);
+        let lp_farm_rewards = lp_farm_claim_rewards_result.lp_farm_rewards;
+        let staking_token_id = self.staking_token_id().get();
+
+        let mut user_payments = ManagedVec::new();
+        let opt_staking_tokens = if lp_farm_rewards.amount == 0 {
+            None
+        } else if lp_farm_rewards.token_identifier == staking_token_id {
+            Some(lp_farm_rewards)
+        } else {
+            user_payments.push(lp_farm_rewards);
+            None
+        };
+
+        let new_staking_farm_tokens = self.staking_farm_compound_rewards(
+            attributes.staking_farm_token_nonce,
+            staking_farm_token_amount,
+            new_staking_farm_value,
+            opt_staking_tokens,
+        );
+
+        let new_lp_farm_tokens = lp_farm_claim_rewards_result.new_lp_farm_tokens;
+        let new_dual_yield_tokens = self.create_dual_yield_tokens(
+            attributes.lp_farm_entry_id,
+            new_lp_farm_tokens.token_nonce,
+            new_lp_farm_tokens.amount,
+            new_staking_farm_tokens.token_nonce,
+            new_staking_farm_tokens.amount,
+        );
+
+        user_payments.push(new_dual_yield_tokens);
+
+        let caller = self.blockchain().get_caller();
+        self.send().direct_multi(&caller, &user_payments);
+
+        user_payments.into()
+    }
+
+    /// If `opt_keep_lp_farm_position` is true, only the staking position is exited,
+    /// and the LP farm tokens are sent back to the caller instead of removing the liquidity.
     #[payable("*")]
     #[endpoint(unstakeFarmTokens)]
     fn unstake_farm_tokens(
         &self,
         pair_first_token_min_amount: BigUint,
         pair_second_token_min_amount: BigUint,
+        opt_keep_lp_farm_position: OptionalValue<bool>,
     ) -> UnstakeResult<Self::Api> {
         let (payment_token, payment_nonce, payment_amount) =
             self.call_value().single_esdt().into_tuple();
         self.dual_yield_token().require_same_token(&payment_token);
 
         let attributes = self.get_dual_yield_token_attributes(payment_nonce);
+        let lp_farm_entry = self.get_lp_farm_entry(attributes.lp_farm_entry_id);
         let lp_farm_token_amount =
             self.get_lp_farm_token_amount_equivalent(&attributes, &payment_amount);
-        let lp_farm_exit_result =
-            self.lp_farm_exit(attributes.lp_farm_token_nonce, lp_farm_token_amount);
-
-        let remove_liq_result = self.pair_remove_liquidity(
-            lp_farm_exit_result.lp_tokens,
-            pair_first_token_min_amount,
-            pair_second_token_min_amount,
-        );
-
         let staking_farm_token_amount =
             self.get_staking_farm_token_amount_equivalent(&payment_amount);
+
+        let keep_lp_farm_position = opt_keep_lp_farm_position.into_option().unwrap_or_default();
+        let (lp_leg_payments, opt_staking_tokens) = if keep_lp_farm_position {
+            let lp_farm_tokens = EsdtTokenPayment::new(
+                lp_farm_entry.lp_farm_token_id,
+                attributes.lp_farm_token_nonce,
+                lp_farm_token_amount,
+            );
+
+            (ManagedVec::from_single_item(lp_farm_tokens), None)
+        } else {
+            let lp_farm_exit_result = self.lp_farm_exit(
+                &lp_farm_entry,
+                attributes.lp_farm_token_nonce,
+                lp_farm_token_amount,
+            );
+            let remove_liq_result = self.pair_remove_liquidity(
+                &lp_farm_entry,
+                lp_farm_exit_result.lp_tokens,
+                pair_first_token_min_amount,
+                pair_second_token_min_amount,
+            );
+
+            let mut lp_leg_payments =
+                ManagedVec::from_single_item(remove_liq_result.other_token_payment);
+            lp_leg_payments.push(lp_farm_exit_result.lp_farm_rewards);
+
+            (
+                lp_leg_payments,
+                Some(remove_liq_result.staking_token_payment),
+            )
+        };
+
         let staking_farm_exit_result = self.staking_farm_unstake(
-            remove_liq_result.staking_token_payment,
+            opt_staking_tokens,
             attributes.staking_farm_token_nonce,
             staking_farm_token_amount,
         );
         let unstake_result = self.send_unstake_payments(
-            remove_liq_result.other_token_payment,
-            lp_farm_exit_result.lp_farm_rewards,
+            lp_leg_payments,
             staking_farm_exit_result.staking_rewards,
             staking_farm_exit_result.unbond_staking_farm_token,
         );
@@ -244,23 +348,23 @@ pub trait FarmStakingProxy:
 
     fn send_unstake_payments(
         &self,
-        other_token_payment: EsdtTokenPayment<Self::Api>,
-        lp_farm_rewards: EsdtTokenPayment<Self::Api>,
+        lp_leg_payments: PaymentsVec<Self::Api>,
         staking_rewards: EsdtTokenPayment<Self::Api>,
         unbond_staking_farm_token: EsdtTokenPayment<Self::Api>,
     ) -> UnstakeResult<Self::Api> {
         let caller = self.blockchain().get_caller();
         let mut user_payments = ManagedVec::new();
-        if other_token_payment.amount > 0 {
-            user_payments.push(other_token_payment);
-        }
-        if lp_farm_rewards.amount > 0 {
-            user_payments.push(lp_farm_rewards);
+        for payment in &lp_leg_payments {
+            if payment.amount > 0 {
+                user_payments.push(payment);
+            }
         }
         if staking_rewards.amount > 0 {
             user_payments.push(staking_rewards);
         }
-        user_payments.push(unbond_staking_farm_token);
+        if unbond_staking_farm_token.amount > 0 {
+            user_payments.push(unbond_staking_farm_token);
+        }
 
         self.send().direct_multi(&caller, &user_payments);
 
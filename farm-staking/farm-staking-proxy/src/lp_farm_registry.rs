@@ -0,0 +1,164 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+/// The entry created from the init arguments.
+/// Dual yield tokens created before the registry belong to it.
+pub const INIT_LP_FARM_ENTRY_ID: u64 = 1;
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Debug)]
+pub struct LpFarmEntry<M: ManagedTypeApi> {
+    pub pair_address: ManagedAddress<M>,
+    pub lp_farm_address: ManagedAddress<M>,
+    pub lp_token_id: TokenIdentifier<M>,
+    pub lp_farm_token_id: TokenIdentifier<M>,
+}
+
+#[elrond_wasm::module]
+pub trait LpFarmRegistryModule:
+    crate::external_contracts_interactions::ExternalContractsInteractionsModule
+    + crate::lp_farm_token::LpFarmTokenModule
+    + token_merge::TokenMergeModule
+{
+    /// Adds a (pair, LP farm) entry, whose farm tokens can be staked through the proxy.
+    /// The pair must have the staking token as one of its tokens.
+    #[only_owner]
+    #[endpoint(addLpFarmEntry)]
+    fn add_lp_farm_entry(
+        &self,
+        pair_address: ManagedAddress,
+        lp_farm_address: ManagedAddress,
+        lp_token_id: TokenIdentifier,
+        lp_farm_token_id: TokenIdentifier,
+    ) -> u64 {
+        require!(
+            self.blockchain().is_smart_contract(&pair_address),
+            "Invalid Pair address"
+        );
+        self.require_pair_has_staking_token(&pair_address);
+
+        let entry_id = self.last_lp_farm_entry_id().get() + 1;
+        self.insert_lp_farm_entry(
+            entry_id,
+            LpFarmEntry {
+                pair_address,
+                lp_farm_address,
+                lp_token_id,
+                lp_farm_token_id,
+            },
+        );
+
+        entry_id
+    }
+
+    /// New positions can no longer be created for the removed entry.
+    /// Existing dual yield tokens can still be claimed and unstaked.
+    #[only_owner]
+    #[endpoint(removeLpFarmEntry)]
+    fn remove_lp_farm_entry(&self, entry_id: u64) {
+        let removed = self.active_lp_farm_entries().swap_remove(&entry_id);
+        require!(removed, "Unknown LP farm entry");
+
+        let entry = self.lp_farm_entry(entry_id).get();
+        self.lp_farm_token_entry(&entry.lp_farm_token_id).clear();
+    }
+
+    fn insert_lp_farm_entry(&self, entry_id: u64, entry: LpFarmEntry<Self::Api>) {
+        require!(
+            self.blockchain().is_smart_contract(&entry.pair_address),
+            "Invalid Pair address"
+        );
+        require!(
+            self.blockchain().is_smart_contract(&entry.lp_farm_address),
+            "Invalid LP Farm address"
+        );
+        require!(
+            entry.lp_token_id.is_valid_esdt_identifier(),
+            "Invalid LP token ID"
+        );
+        require!(
+            entry.lp_farm_token_id.is_valid_esdt_identifier(),
+            "Invalid Farm token ID"
+        );
+
+        let token_entry_mapper = self.lp_farm_token_entry(&entry.lp_farm_token_id);
+        require!(
+            token_entry_mapper.is_empty() || token_entry_mapper.get() == entry_id,
+            "LP farm token already registered"
+        );
+
+        token_entry_mapper.set(entry_id);
+        let _ = self.active_lp_farm_entries().insert(entry_id);
+        self.lp_farm_entry(entry_id).set(&entry);
+        if self.last_lp_farm_entry_id().get() < entry_id {
+            self.last_lp_farm_entry_id().set(entry_id);
+        }
+    }
+
+    fn get_active_lp_farm_entry_for_token(
+        &self,
+        lp_farm_token_id: &TokenIdentifier,
+    ) -> (u64, LpFarmEntry<Self::Api>) {
+        let token_entry_mapper = self.lp_farm_token_entry(lp_farm_token_id);
+        require!(!token_entry_mapper.is_empty(), "Invalid first payment");
+
+        let entry_id = token_entry_mapper.get();
+        (entry_id, self.lp_farm_entry(entry_id).get())
+    }
+
+    fn get_lp_farm_entry(&self, entry_id: u64) -> LpFarmEntry<Self::Api> {
+        let entry_mapper = self.lp_farm_entry(entry_id);
+        require!(!entry_mapper.is_empty(), "Unknown LP farm entry");
+
+        entry_mapper.get()
+    }
+
+    #[view(getActiveLpFarmEntries)]
+    fn get_active_lp_farm_entries(
+        &self,
+    ) -> MultiValueEncoded<MultiValue2<u64, LpFarmEntry<Self::Api>>> {
+        let mut result = MultiValueEncoded::new();
+        for entry_id in self.active_lp_farm_entries().iter() {
+            let entry = self.lp_farm_entry(entry_id).get();
+            result.push((entry_id, entry).into());
+        }
+        result
+    }
+
+    fn get_init_lp_farm_entry(&self) -> LpFarmEntry<Self::Api> {
+        self.get_lp_farm_entry(INIT_LP_FARM_ENTRY_ID)
+    }
+
+    #[view(getLpFarmAddress)]
+    fn get_lp_farm_address(&self) -> ManagedAddress {
+        self.get_init_lp_farm_entry().lp_farm_address
+    }
+
+    #[view(getLpFarmTokenId)]
+    fn get_lp_farm_token_id(&self) -> TokenIdentifier {
+        self.get_init_lp_farm_entry().lp_farm_token_id
+    }
+
+    #[view(getLpTokenId)]
+    fn get_lp_token_id(&self) -> TokenIdentifier {
+        self.get_init_lp_farm_entry().lp_token_id
+    }
+
+    #[view(getPairAddress)]
+    fn get_pair_address(&self) -> ManagedAddress {
+        self.get_init_lp_farm_entry().pair_address
+    }
+
+    #[view(getLpFarmEntry)]
+    #[storage_mapper("lpFarmEntry")]
+    fn lp_farm_entry(&self, entry_id: u64) -> SingleValueMapper<LpFarmEntry<Self::Api>>;
+
+    #[storage_mapper("activeLpFarmEntries")]
+    fn active_lp_farm_entries(&self) -> UnorderedSetMapper<u64>;
+
+    #[view(getLpFarmEntryIdForToken)]
+    #[storage_mapper("lpFarmTokenEntry")]
+    fn lp_farm_token_entry(&self, lp_farm_token_id: &TokenIdentifier) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("lastLpFarmEntryId")]
+    fn last_lp_farm_entry_id(&self) -> SingleValueMapper<u64>;
+}
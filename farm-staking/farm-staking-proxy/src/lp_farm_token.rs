@@ -7,14 +7,14 @@ use common_structs::FarmTokenAttributes;
 pub trait LpFarmTokenModule: token_merge::TokenMergeModule {
     fn get_lp_tokens_in_farm_position(
         &self,
+        lp_farm_token_id: &TokenIdentifier,
         farm_token_nonce: u64,
         farm_token_amount: &BigUint,
     ) -> BigUint {
         let own_sc_address = self.blockchain().get_sc_address();
-        let lp_farm_token_id = self.lp_farm_token_id().get();
         let token_data = self.blockchain().get_esdt_token_data(
             &own_sc_address,
-            &lp_farm_token_id,
+            lp_farm_token_id,
             farm_token_nonce,
         );
         let attributes = token_data.decode_attributes::<FarmTokenAttributes<Self::Api>>();
@@ -25,8 +25,4 @@ pub trait LpFarmTokenModule: token_merge::TokenMergeModule {
             &attributes.initial_farming_amount,
         )
     }
-
-    #[view(getLpFarmTokenId)]
-    #[storage_mapper("lpFarmTokenId")]
-    fn lp_farm_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
 }
@@ -6,8 +6,13 @@ pub mod staking_farm_with_lp_staking_contract_setup;
 elrond_wasm::imports!();
 
 use constants::*;
-use elrond_wasm_debug::{managed_biguint, rust_biguint, DebugApi};
+use elrond_wasm::elrond_codec::multi_types::OptionalValue;
+use elrond_wasm_debug::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
 use farm_staking_proxy::dual_yield_token::DualYieldTokenAttributes;
+use farm_staking_proxy::lp_farm_registry::LpFarmRegistryModule;
+use pair::config::ConfigModule as _;
 use staking_farm_with_lp_staking_contract_interactions::*;
 
 #[test]
@@ -295,6 +300,7 @@ fn test_stake_farm_through_proxy_with_merging() {
                 lp_farm_token_amount: managed_biguint!(400_000_000),
                 staking_farm_token_nonce: 1,
                 staking_farm_token_amount: managed_biguint!(400_000_000),
+                lp_farm_entry_id: 1,
             }),
         )
     });
@@ -326,6 +332,7 @@ fn test_stake_farm_through_proxy_with_merging() {
                 lp_farm_token_amount: managed_biguint!(1_000_000_000),
                 staking_farm_token_nonce: 2,
                 staking_farm_token_amount: managed_biguint!(1_000_000_000),
+                lp_farm_entry_id: 1,
             }),
         )
     });
@@ -348,3 +355,213 @@ fn test_stake_farm_through_proxy_with_merging() {
         )
     });
 }
+
+#[test]
+fn test_lp_farm_registry() {
+    let mut setup = FarmStakingSetup::new(
+        pair::contract_obj,
+        farm::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+    );
+
+    let dual_yield_token_nonce = setup.stake_farm_lp_proxy(1, 400_000_000, 1, 400_000_000);
+
+    let pair_addr = setup.pair_wrapper.address_ref().clone();
+    let lp_farm_addr = setup.lp_farm_wrapper.address_ref().clone();
+
+    // the views of the init arguments read the init entry
+    setup
+        .b_mock
+        .execute_query(&setup.proxy_wrapper, |sc| {
+            assert_eq!(sc.get_pair_address(), managed_address!(&pair_addr));
+            assert_eq!(sc.get_lp_farm_address(), managed_address!(&lp_farm_addr));
+            assert_eq!(sc.get_lp_token_id(), managed_token_id!(LP_TOKEN_ID));
+            assert_eq!(
+                sc.get_lp_farm_token_id(),
+                managed_token_id!(LP_FARM_TOKEN_ID)
+            );
+        })
+        .assert_ok();
+
+    // LP farm token already used by the init entry
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_addr,
+            &setup.proxy_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let _ = sc.add_lp_farm_entry(
+                    managed_address!(&pair_addr),
+                    managed_address!(&lp_farm_addr),
+                    managed_token_id!(LP_TOKEN_ID),
+                    managed_token_id!(LP_FARM_TOKEN_ID),
+                );
+            },
+        )
+        .assert_user_error("LP farm token already registered");
+
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_addr,
+            &setup.proxy_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let entry_id = sc.add_lp_farm_entry(
+                    managed_address!(&pair_addr),
+                    managed_address!(&lp_farm_addr),
+                    managed_token_id!(LP_TOKEN_ID),
+                    managed_token_id!(b"LPFARM2-abcdef"),
+                );
+                assert_eq!(entry_id, 2);
+
+                sc.remove_lp_farm_entry(1);
+            },
+        )
+        .assert_ok();
+
+    setup
+        .b_mock
+        .execute_query(&setup.proxy_wrapper, |sc| {
+            let mut active_entries = sc.get_active_lp_farm_entries().into_iter();
+            let (entry_id, entry) = active_entries.next().unwrap().into_tuple();
+            assert!(active_entries.next().is_none());
+
+            assert_eq!(entry_id, 2);
+            assert_eq!(entry.lp_farm_token_id, managed_token_id!(b"LPFARM2-abcdef"));
+        })
+        .assert_ok();
+
+    // no new positions for the removed entry
+    setup
+        .b_mock
+        .execute_esdt_transfer(
+            &setup.user_addr,
+            &setup.proxy_wrapper,
+            LP_FARM_TOKEN_ID,
+            1,
+            &rust_biguint!(100_000_000),
+            |sc| {
+                let _ = sc.stake_farm_tokens();
+            },
+        )
+        .assert_user_error("Invalid first payment");
+
+    // existing positions still work
+    setup
+        .b_mock
+        .execute_esdt_transfer(
+            &setup.user_addr,
+            &setup.proxy_wrapper,
+            DUAL_YIELD_TOKEN_ID,
+            dual_yield_token_nonce,
+            &rust_biguint!(400_000_000),
+            |sc| {
+                let _ = sc.claim_dual_yield();
+            },
+        )
+        .assert_ok();
+
+    // the pair must have the staking token
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_addr,
+            &setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.second_token_id()
+                    .set(&managed_token_id!(b"OTHER-abcdef"));
+            },
+        )
+        .assert_ok();
+
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_addr,
+            &setup.proxy_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let _ = sc.add_lp_farm_entry(
+                    managed_address!(&pair_addr),
+                    managed_address!(&lp_farm_addr),
+                    managed_token_id!(LP_TOKEN_ID),
+                    managed_token_id!(b"LPFARM3-abcdef"),
+                );
+            },
+        )
+        .assert_user_error("Pair does not have the staking token");
+}
+
+#[test]
+fn test_compound_and_unstake_staking_leg_only() {
+    let mut setup = FarmStakingSetup::new(
+        pair::contract_obj,
+        farm::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+    );
+
+    let expected_staking_token_amount = 1_001_000_000;
+    let dual_yield_token_nonce_after_stake =
+        setup.stake_farm_lp_proxy(1, USER_TOTAL_LP_TOKENS, 1, expected_staking_token_amount);
+
+    setup
+        .b_mock
+        .set_block_nonce(BLOCK_NONCE_AFTER_PAIR_SETUP + 20);
+
+    // same rewards as in test_claim_rewards_farm_proxy_full,
+    // both paid in RIDE, so both are compounded into the staking position
+    let expected_lp_farm_rewards = 99_999;
+    let expected_staking_farm_rewards = 1_899;
+    let compounded_rewards = expected_lp_farm_rewards + expected_staking_farm_rewards;
+    let new_dual_yield_token_amount = expected_staking_token_amount + compounded_rewards;
+    let dual_yield_token_nonce_after_compound = setup.compound_rewards_proxy(
+        dual_yield_token_nonce_after_stake,
+        expected_staking_token_amount,
+        new_dual_yield_token_amount,
+    );
+
+    // exit the staking position only, the LP farm tokens are sent back
+    setup
+        .b_mock
+        .execute_esdt_transfer(
+            &setup.user_addr,
+            &setup.proxy_wrapper,
+            DUAL_YIELD_TOKEN_ID,
+            dual_yield_token_nonce_after_compound,
+            &rust_biguint!(new_dual_yield_token_amount),
+            |sc| {
+                let received_tokens = sc
+                    .unstake_farm_tokens(
+                        managed_biguint!(1),
+                        managed_biguint!(1),
+                        OptionalValue::Some(true),
+                    )
+                    .to_vec();
+                assert_eq!(received_tokens.len(), 2);
+
+                let lp_farm_tokens = received_tokens.get(0);
+                assert_eq!(
+                    lp_farm_tokens.token_identifier,
+                    managed_token_id!(LP_FARM_TOKEN_ID)
+                );
+                assert_eq!(
+                    lp_farm_tokens.amount,
+                    managed_biguint!(USER_TOTAL_LP_TOKENS)
+                );
+
+                // only the compounded rewards are held by the staking farm
+                let unbond_tokens = received_tokens.get(1);
+                assert_eq!(
+                    unbond_tokens.token_identifier,
+                    managed_token_id!(STAKING_FARM_TOKEN_ID)
+                );
+                assert_eq!(unbond_tokens.amount, managed_biguint!(compounded_rewards));
+            },
+        )
+        .assert_ok();
+}
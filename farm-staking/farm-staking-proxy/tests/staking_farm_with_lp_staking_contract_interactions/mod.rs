@@ -1,3 +1,4 @@
+use elrond_wasm::elrond_codec::multi_types::OptionalValue;
 use elrond_wasm::types::Address;
 use elrond_wasm_debug::{
     managed_biguint, rust_biguint,
@@ -141,6 +142,7 @@ where
                 lp_farm_token_amount: managed_biguint!(lp_farm_token_stake_amount),
                 staking_farm_token_nonce: expected_staking_farm_token_nonce,
                 staking_farm_token_amount: managed_biguint!(expected_staking_token_amount),
+                lp_farm_entry_id: 1,
             };
 
             self.b_mock.check_nft_balance(
@@ -229,6 +231,39 @@ where
         dual_yield_nonce
     }
 
+    pub fn compound_rewards_proxy(
+        &mut self,
+        dual_yield_token_nonce: u64,
+        dual_yield_token_amount: u64,
+        expected_new_dual_yield_token_amount: u64,
+    ) -> u64 {
+        let mut dual_yield_nonce = 0;
+
+        self.b_mock
+            .execute_esdt_transfer(
+                &self.user_addr,
+                &self.proxy_wrapper,
+                DUAL_YIELD_TOKEN_ID,
+                dual_yield_token_nonce,
+                &rust_biguint!(dual_yield_token_amount),
+                |sc| {
+                    let received_tokens = sc.compound_dual_yield().to_vec();
+                    assert_eq!(received_tokens.len(), 1);
+
+                    let new_dual_yield_tokens = received_tokens.get(0);
+                    dual_yield_nonce = new_dual_yield_tokens.token_nonce;
+
+                    assert_eq!(
+                        new_dual_yield_tokens.amount,
+                        expected_new_dual_yield_token_amount
+                    );
+                },
+            )
+            .assert_ok();
+
+        dual_yield_nonce
+    }
+
     pub fn unstake_proxy(
         &mut self,
         dual_yield_token_nonce: u64,
@@ -250,7 +285,11 @@ where
                 &rust_biguint!(dual_yield_token_amount),
                 |sc| {
                     let received_tokens = sc
-                        .unstake_farm_tokens(managed_biguint!(1), managed_biguint!(1))
+                        .unstake_farm_tokens(
+                            managed_biguint!(1),
+                            managed_biguint!(1),
+                            OptionalValue::None,
+                        )
                         .to_vec();
                     let mut vec_index = 0;
 
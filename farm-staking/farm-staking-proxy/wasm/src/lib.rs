@@ -8,16 +8,22 @@ elrond_wasm_node::wasm_endpoints! {
     farm_staking_proxy
     (
         callBack
+        addLpFarmEntry
         claimDualYield
+        compoundDualYield
+        getActiveLpFarmEntries
         getDualYieldTokenId
         getFarmTokenId
         getLpFarmAddress
+        getLpFarmEntry
+        getLpFarmEntryIdForToken
         getLpFarmTokenId
         getLpTokenId
         getPairAddress
         getStakingFarmAddress
         getStakingTokenId
         registerDualYieldToken
+        removeLpFarmEntry
         stakeFarmTokens
         unstakeFarmTokens
     )
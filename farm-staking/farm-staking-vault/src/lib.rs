@@ -0,0 +1,233 @@
+#![no_std]
+
+elrond_wasm::imports!();
+
+pub mod share_token;
+pub mod staking_farm_interactions;
+
+use pausable::State;
+
+pub const MAX_PERCENT: u64 = 10_000;
+pub const MAX_COMPOUND_BOUNTY_PERCENT: u64 = 1_000;
+
+pub type DepositResultType<BigUint> = EsdtTokenPayment<BigUint>;
+pub type CompoundResultType<BigUint> = EsdtTokenPayment<BigUint>;
+pub type WithdrawResultType<BigUint> = MultiValueEncoded<BigUint, EsdtTokenPayment<BigUint>>;
+
+/// Holds a single farm staking position on behalf of its depositors,
+/// who receive fungible share tokens in exchange.
+#[elrond_wasm::contract]
+pub trait FarmStakingVault:
+    share_token::ShareTokenModule
+    + staking_farm_interactions::StakingFarmInteractionsModule
+    + pausable::PausableModule
+    + elrond_wasm_modules::default_issue_callbacks::DefaultIssueCallbacksModule
+{
+    #[init]
+    fn init(
+        &self,
+        staking_farm_address: ManagedAddress,
+        staking_token_id: TokenIdentifier,
+        compound_bounty_percent: u64,
+    ) {
+        require!(
+            self.blockchain().is_smart_contract(&staking_farm_address),
+            "Invalid Staking Farm address"
+        );
+        require!(
+            staking_token_id.is_valid_esdt_identifier(),
+            "Invalid Staking token ID"
+        );
+
+        self.staking_farm_address().set(&staking_farm_address);
+        self.staking_token_id().set(&staking_token_id);
+        self.set_compound_bounty_percent(compound_bounty_percent);
+
+        self.state().set(State::Inactive);
+
+        let caller = self.blockchain().get_caller();
+        self.pause_whitelist().add(&caller);
+    }
+
+    /// The part of the compounded rewards paid to the caller of compoundRewards.
+    #[only_owner]
+    #[endpoint(setCompoundBountyPercent)]
+    fn set_compound_bounty_percent(&self, compound_bounty_percent: u64) {
+        require!(
+            compound_bounty_percent <= MAX_COMPOUND_BOUNTY_PERCENT,
+            "Bounty percent too high"
+        );
+        self.compound_bounty_percent().set(compound_bounty_percent);
+    }
+
+    #[payable("*")]
+    #[endpoint]
+    fn deposit(&self) -> DepositResultType<Self::Api> {
+        self.require_active();
+
+        let (payment_token, payment_amount) = self.call_value().single_fungible_esdt();
+        require!(
+            payment_token == self.staking_token_id().get(),
+            "Invalid payment token"
+        );
+        require!(payment_amount > 0u32, "Payment amount cannot be zero");
+
+        // the pending rewards belong to the current depositors
+        self.staking_farm_compound();
+
+        let share_supply = self.share_supply().get();
+        let shares_amount = if share_supply == 0 {
+            payment_amount.clone()
+        } else {
+            let position_amount = self.staking_position().get().amount;
+            &payment_amount * &share_supply / position_amount
+        };
+        require!(shares_amount > 0u32, "Deposit amount too low");
+
+        let _ = self.staking_farm_enter(payment_amount);
+        self.share_supply().update(|x| *x += &shares_amount);
+
+        let caller = self.blockchain().get_caller();
+        self.share_token().mint_and_send(&caller, shares_amount)
+    }
+
+    /// Claims the rewards of the vault's position and stakes them again.
+    /// The caller receives a part of the rewards as a bounty.
+    #[endpoint(compoundRewards)]
+    fn compound_rewards(&self) -> CompoundResultType<Self::Api> {
+        self.require_active();
+        require!(!self.staking_position().is_empty(), "Nothing to compound");
+
+        let rewards = self.staking_farm_claim_rewards();
+        let bounty_amount = &rewards.amount * self.compound_bounty_percent().get() / MAX_PERCENT;
+        let compounded_amount = &rewards.amount - &bounty_amount;
+        if compounded_amount > 0u32 {
+            let _ = self.staking_farm_enter(compounded_amount);
+        }
+
+        let caller = self.blockchain().get_caller();
+        if bounty_amount > 0u32 {
+            self.send()
+                .direct_esdt(&caller, &rewards.token_identifier, 0, &bounty_amount);
+        }
+
+        EsdtTokenPayment::new(rewards.token_identifier, 0, bounty_amount)
+    }
+
+    /// Burns the received shares and unstakes the corresponding part of the vault's position.
+    /// The caller receives the unbond tokens of the staking farm,
+    /// which are then unbonded directly through the staking farm's `unbondFarm` endpoint.
+    #[payable("*")]
+    #[endpoint]
+    fn withdraw(&self) -> WithdrawResultType<Self::Api> {
+        self.require_active();
+
+        let (payment_token, shares_amount) = self.call_value().single_fungible_esdt();
+        self.share_token().require_same_token(&payment_token);
+        require!(shares_amount > 0u32, "Payment amount cannot be zero");
+
+        self.staking_farm_compound();
+
+        let share_supply = self.share_supply().get();
+        let position_amount = self.staking_position().get().amount;
+        let farm_token_amount = &shares_amount * &position_amount / &share_supply;
+        require!(farm_token_amount > 0u32, "Withdraw amount too low");
+
+        self.share_supply().update(|x| *x -= &shares_amount);
+        self.share_token().burn(&shares_amount);
+
+        let (unbond_tokens, rewards) = self.staking_farm_unstake(farm_token_amount).into_tuple();
+
+        let mut payments = ManagedVec::from_single_item(unbond_tokens);
+        if rewards.amount > 0u32 {
+            payments.push(rewards);
+        }
+
+        let caller = self.blockchain().get_caller();
+        self.send().direct_multi(&caller, &payments);
+
+        payments.into()
+    }
+
+    /// Exits the vault's whole position while the staking farm is paused,
+    /// since the shareholders cannot use the staking farm's `emergencyExit` themselves.
+    /// Afterwards, the vault only allows `emergencyWithdraw`.
+    #[endpoint(emergencyExit)]
+    fn emergency_exit(&self) -> BigUint {
+        require!(!self.emergency_exited().get(), "Already exited");
+        require!(!self.staking_position().is_empty(), "Nothing to exit");
+
+        let recovered_tokens = self.staking_farm_emergency_exit();
+        self.recovered_staking_amount()
+            .set(&recovered_tokens.amount);
+        self.emergency_exited().set(true);
+
+        recovered_tokens.amount
+    }
+
+    /// Burns the received shares and sends back their part of the staking tokens
+    /// recovered through `emergencyExit`.
+    #[payable("*")]
+    #[endpoint(emergencyWithdraw)]
+    fn emergency_withdraw(&self) -> EsdtTokenPayment<Self::Api> {
+        require!(self.emergency_exited().get(), "Not exited");
+
+        let (payment_token, shares_amount) = self.call_value().single_fungible_esdt();
+        self.share_token().require_same_token(&payment_token);
+        require!(shares_amount > 0u32, "Payment amount cannot be zero");
+
+        let share_supply = self.share_supply().get();
+        let recovered_amount = self.recovered_staking_amount().get();
+        let staking_token_amount = &shares_amount * &recovered_amount / &share_supply;
+
+        self.share_supply().update(|x| *x -= &shares_amount);
+        self.recovered_staking_amount()
+            .set(&(recovered_amount - &staking_token_amount));
+        self.share_token().burn(&shares_amount);
+
+        let staking_token_id = self.staking_token_id().get();
+        let caller = self.blockchain().get_caller();
+        if staking_token_amount > 0u32 {
+            self.send()
+                .direct_esdt(&caller, &staking_token_id, 0, &staking_token_amount);
+        }
+
+        EsdtTokenPayment::new(staking_token_id, 0, staking_token_amount)
+    }
+
+    /// Returns the amount of staking tokens the given shares are worth,
+    /// including the rewards not compounded yet.
+    #[view(getUnderlyingForShares)]
+    fn get_underlying_for_shares(&self, shares_amount: BigUint) -> BigUint {
+        let share_supply = self.share_supply().get();
+        if share_supply == 0 {
+            return shares_amount;
+        }
+        if self.emergency_exited().get() {
+            return shares_amount * self.recovered_staking_amount().get() / share_supply;
+        }
+
+        shares_amount * self.get_staking_position_value() / share_supply
+    }
+
+    fn require_active(&self) {
+        require!(self.state().get() == State::Active, "Not active");
+        require!(!self.emergency_exited().get(), "Vault exited");
+    }
+
+    #[view(getCompoundBountyPercent)]
+    #[storage_mapper("compoundBountyPercent")]
+    fn compound_bounty_percent(&self) -> SingleValueMapper<u64>;
+
+    #[view(isEmergencyExited)]
+    #[storage_mapper("emergencyExited")]
+    fn emergency_exited(&self) -> SingleValueMapper<bool>;
+
+    #[view(getRecoveredStakingAmount)]
+    #[storage_mapper("recoveredStakingAmount")]
+    fn recovered_staking_amount(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getShareSupply)]
+    #[storage_mapper("shareSupply")]
+    fn share_supply(&self) -> SingleValueMapper<BigUint>;
+}
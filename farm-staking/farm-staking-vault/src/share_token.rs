@@ -0,0 +1,29 @@
+elrond_wasm::imports!();
+
+#[elrond_wasm::module]
+pub trait ShareTokenModule:
+    elrond_wasm_modules::default_issue_callbacks::DefaultIssueCallbacksModule
+{
+    #[only_owner]
+    #[payable("EGLD")]
+    #[endpoint(registerShareToken)]
+    fn register_share_token(
+        &self,
+        token_display_name: ManagedBuffer,
+        token_ticker: ManagedBuffer,
+        num_decimals: usize,
+    ) {
+        let register_cost = self.call_value().egld_value();
+        self.share_token().issue_and_set_all_roles(
+            register_cost,
+            token_display_name,
+            token_ticker,
+            num_decimals,
+            None,
+        );
+    }
+
+    #[view(getShareTokenId)]
+    #[storage_mapper("shareTokenId")]
+    fn share_token(&self) -> FungibleTokenMapper<Self::Api>;
+}
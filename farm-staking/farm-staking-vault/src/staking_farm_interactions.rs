@@ -0,0 +1,161 @@
+elrond_wasm::imports!();
+
+use farm_staking::farm_token_merge::StakingFarmTokenAttributes;
+use farm_staking::{
+    ClaimRewardsResultType, CompoundRewardsResultType, EnterFarmResultType, ExitFarmResultType,
+};
+
+#[elrond_wasm::module]
+pub trait StakingFarmInteractionsModule {
+    /// Stakes the given tokens, merging them with the vault's position, if any.
+    fn staking_farm_enter(&self, staking_token_amount: BigUint) -> EsdtTokenPayment<Self::Api> {
+        let mut payments = ManagedVec::from_single_item(EsdtTokenPayment::new(
+            self.staking_token_id().get(),
+            0,
+            staking_token_amount,
+        ));
+        if !self.staking_position().is_empty() {
+            payments.push(self.staking_position().get());
+        }
+
+        let staking_farm_address = self.staking_farm_address().get();
+        let new_position: EnterFarmResultType<Self::Api> = self
+            .staking_farm_proxy_obj(staking_farm_address)
+            .stake_farm_endpoint()
+            .with_multi_token_transfer(payments)
+            .execute_on_dest_context();
+        self.staking_position().set(&new_position);
+
+        new_position
+    }
+
+    /// Compounds the pending rewards of the vault's position, if any.
+    fn staking_farm_compound(&self) {
+        if self.staking_position().is_empty() {
+            return;
+        }
+
+        let position = self.staking_position().get();
+        let staking_farm_address = self.staking_farm_address().get();
+        let new_position: CompoundRewardsResultType<Self::Api> = self
+            .staking_farm_proxy_obj(staking_farm_address)
+            .compound_rewards()
+            .add_esdt_token_transfer(
+                position.token_identifier,
+                position.token_nonce,
+                position.amount,
+            )
+            .execute_on_dest_context();
+        self.staking_position().set(&new_position);
+    }
+
+    /// Claims the rewards of the vault's whole position.
+    fn staking_farm_claim_rewards(&self) -> EsdtTokenPayment<Self::Api> {
+        let position = self.staking_position().get();
+        let staking_farm_address = self.staking_farm_address().get();
+        let claim_result: ClaimRewardsResultType<Self::Api> = self
+            .staking_farm_proxy_obj(staking_farm_address)
+            .claim_rewards()
+            .add_esdt_token_transfer(
+                position.token_identifier,
+                position.token_nonce,
+                position.amount,
+            )
+            .execute_on_dest_context();
+        let (new_position, rewards) = claim_result.into_tuple();
+        self.staking_position().set(&new_position);
+
+        rewards
+    }
+
+    /// Unstakes part of the vault's position.
+    /// Returns the unbond tokens and the rewards of the unstaked part.
+    fn staking_farm_unstake(
+        &self,
+        farm_token_amount: BigUint,
+    ) -> MultiValue2<EsdtTokenPayment<Self::Api>, EsdtTokenPayment<Self::Api>> {
+        let mut position = self.staking_position().get();
+        let staking_farm_address = self.staking_farm_address().get();
+        let unstake_result: ExitFarmResultType<Self::Api> = self
+            .staking_farm_proxy_obj(staking_farm_address)
+            .unstake_farm()
+            .add_esdt_token_transfer(
+                position.token_identifier.clone(),
+                position.token_nonce,
+                farm_token_amount.clone(),
+            )
+            .execute_on_dest_context();
+
+        position.amount -= farm_token_amount;
+        if position.amount == 0 {
+            self.staking_position().clear();
+        } else {
+            self.staking_position().set(&position);
+        }
+
+        unstake_result
+    }
+
+    /// Exits the vault's whole position through the staking farm's `emergencyExit`,
+    /// which forfeits the pending rewards. Returns the recovered staking tokens.
+    fn staking_farm_emergency_exit(&self) -> EsdtTokenPayment<Self::Api> {
+        let position = self.staking_position().get();
+        let staking_farm_address = self.staking_farm_address().get();
+        let recovered_tokens: EsdtTokenPayment<Self::Api> = self
+            .staking_farm_proxy_obj(staking_farm_address)
+            .emergency_exit()
+            .add_esdt_token_transfer(
+                position.token_identifier,
+                position.token_nonce,
+                position.amount,
+            )
+            .execute_on_dest_context();
+        self.staking_position().clear();
+
+        recovered_tokens
+    }
+
+    /// The vault's position, including the rewards not compounded yet.
+    fn get_staking_position_value(&self) -> BigUint {
+        if self.staking_position().is_empty() {
+            return BigUint::zero();
+        }
+
+        let position = self.staking_position().get();
+        let attributes: StakingFarmTokenAttributes<Self::Api> = self
+            .blockchain()
+            .get_esdt_token_data(
+                &self.blockchain().get_sc_address(),
+                &position.token_identifier,
+                position.token_nonce,
+            )
+            .decode_attributes();
+
+        let staking_farm_address = self.staking_farm_address().get();
+        let pending_rewards: BigUint = self
+            .staking_farm_proxy_obj(staking_farm_address)
+            .calculate_rewards_for_given_position(position.amount.clone(), attributes)
+            .execute_on_dest_context();
+
+        position.amount + pending_rewards
+    }
+
+    // proxies
+
+    #[proxy]
+    fn staking_farm_proxy_obj(&self, sc_address: ManagedAddress) -> farm_staking::Proxy<Self::Api>;
+
+    // storage
+
+    #[view(getStakingFarmAddress)]
+    #[storage_mapper("stakingFarmAddress")]
+    fn staking_farm_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getStakingTokenId)]
+    #[storage_mapper("stakingTokenId")]
+    fn staking_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[view(getStakingPosition)]
+    #[storage_mapper("stakingPosition")]
+    fn staking_position(&self) -> SingleValueMapper<EsdtTokenPayment<Self::Api>>;
+}
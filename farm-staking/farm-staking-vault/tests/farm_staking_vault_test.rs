@@ -0,0 +1,441 @@
+use elrond_wasm::storage::mappers::StorageTokenWrapper;
+use elrond_wasm::types::{Address, EsdtLocalRole};
+use elrond_wasm_debug::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint,
+    testing_framework::{BlockchainStateWrapper, ContractObjWrapper},
+    DebugApi,
+};
+
+use config::ConfigModule;
+use farm_staking::custom_rewards::CustomRewardsModule;
+use farm_staking::*;
+use farm_staking_vault::share_token::ShareTokenModule;
+use farm_staking_vault::staking_farm_interactions::StakingFarmInteractionsModule;
+use farm_staking_vault::*;
+use farm_token::FarmTokenModule;
+use pausable::{PausableModule, State};
+
+const STAKING_FARM_WASM_PATH: &'static str = "farm-staking/output/farm-staking.wasm";
+const VAULT_WASM_PATH: &'static str = "farm-staking-vault/output/farm-staking-vault.wasm";
+
+const STAKING_TOKEN_ID: &[u8] = b"RIDE-abcdef";
+const STAKING_FARM_TOKEN_ID: &[u8] = b"FARM-abcdef";
+const SHARE_TOKEN_ID: &[u8] = b"VRIDE-abcdef";
+const DIVISION_SAFETY_CONSTANT: u64 = 1_000_000_000_000;
+const MIN_UNBOND_EPOCHS: u64 = 5;
+const MAX_APR: u64 = 2_500; // 25%
+const PER_BLOCK_REWARD_AMOUNT: u64 = 5_000;
+const TOTAL_REWARDS_AMOUNT: u64 = 1_000_000_000_000;
+const COMPOUND_BOUNTY_PERCENT: u64 = 500; // 5%
+
+const USER_TOTAL_RIDE_TOKENS: u64 = 5_000_000_000;
+
+struct VaultSetup<StakingFarmObjBuilder, VaultObjBuilder>
+where
+    StakingFarmObjBuilder: 'static + Copy + Fn() -> farm_staking::ContractObj<DebugApi>,
+    VaultObjBuilder: 'static + Copy + Fn() -> farm_staking_vault::ContractObj<DebugApi>,
+{
+    pub b_mock: BlockchainStateWrapper,
+    pub first_user: Address,
+    pub second_user: Address,
+    pub staking_farm_wrapper:
+        ContractObjWrapper<farm_staking::ContractObj<DebugApi>, StakingFarmObjBuilder>,
+    pub vault_wrapper:
+        ContractObjWrapper<farm_staking_vault::ContractObj<DebugApi>, VaultObjBuilder>,
+}
+
+fn setup_vault<StakingFarmObjBuilder, VaultObjBuilder>(
+    staking_farm_builder: StakingFarmObjBuilder,
+    vault_builder: VaultObjBuilder,
+) -> VaultSetup<StakingFarmObjBuilder, VaultObjBuilder>
+where
+    StakingFarmObjBuilder: 'static + Copy + Fn() -> farm_staking::ContractObj<DebugApi>,
+    VaultObjBuilder: 'static + Copy + Fn() -> farm_staking_vault::ContractObj<DebugApi>,
+{
+    let rust_zero = rust_biguint!(0u64);
+    let mut b_mock = BlockchainStateWrapper::new();
+    let owner_addr = b_mock.create_user_account(&rust_zero);
+
+    let staking_farm_wrapper = b_mock.create_sc_account(
+        &rust_zero,
+        Some(&owner_addr),
+        staking_farm_builder,
+        STAKING_FARM_WASM_PATH,
+    );
+    b_mock
+        .execute_tx(&owner_addr, &staking_farm_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_token_id!(STAKING_TOKEN_ID),
+                managed_biguint!(DIVISION_SAFETY_CONSTANT),
+                managed_biguint!(MAX_APR),
+                MIN_UNBOND_EPOCHS,
+            );
+
+            sc.farm_token()
+                .set_token_id(&managed_token_id!(STAKING_FARM_TOKEN_ID));
+            sc.per_block_reward_amount()
+                .set(&managed_biguint!(PER_BLOCK_REWARD_AMOUNT));
+
+            sc.state().set(&State::Active);
+            sc.produce_rewards_enabled().set(&true);
+        })
+        .assert_ok();
+
+    b_mock.set_esdt_balance(
+        &owner_addr,
+        STAKING_TOKEN_ID,
+        &rust_biguint!(TOTAL_REWARDS_AMOUNT),
+    );
+    b_mock
+        .execute_esdt_transfer(
+            &owner_addr,
+            &staking_farm_wrapper,
+            STAKING_TOKEN_ID,
+            0,
+            &rust_biguint!(TOTAL_REWARDS_AMOUNT),
+            |sc| {
+                sc.top_up_rewards();
+            },
+        )
+        .assert_ok();
+
+    let farm_token_roles = [
+        EsdtLocalRole::NftCreate,
+        EsdtLocalRole::NftAddQuantity,
+        EsdtLocalRole::NftBurn,
+    ];
+    b_mock.set_esdt_local_roles(
+        staking_farm_wrapper.address_ref(),
+        STAKING_FARM_TOKEN_ID,
+        &farm_token_roles[..],
+    );
+
+    let vault_wrapper = b_mock.create_sc_account(
+        &rust_zero,
+        Some(&owner_addr),
+        vault_builder,
+        VAULT_WASM_PATH,
+    );
+    b_mock
+        .execute_tx(&owner_addr, &vault_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_address!(staking_farm_wrapper.address_ref()),
+                managed_token_id!(STAKING_TOKEN_ID),
+                COMPOUND_BOUNTY_PERCENT,
+            );
+
+            sc.share_token()
+                .set_token_id(&managed_token_id!(SHARE_TOKEN_ID));
+            sc.state().set(State::Active);
+        })
+        .assert_ok();
+
+    let share_token_roles = [EsdtLocalRole::Mint, EsdtLocalRole::Burn];
+    b_mock.set_esdt_local_roles(
+        vault_wrapper.address_ref(),
+        SHARE_TOKEN_ID,
+        &share_token_roles[..],
+    );
+
+    let first_user = b_mock.create_user_account(&rust_zero);
+    b_mock.set_esdt_balance(
+        &first_user,
+        STAKING_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_RIDE_TOKENS),
+    );
+    let second_user = b_mock.create_user_account(&rust_zero);
+    b_mock.set_esdt_balance(
+        &second_user,
+        STAKING_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_RIDE_TOKENS),
+    );
+
+    VaultSetup {
+        b_mock,
+        first_user,
+        second_user,
+        staking_farm_wrapper,
+        vault_wrapper,
+    }
+}
+
+fn deposit<StakingFarmObjBuilder, VaultObjBuilder>(
+    setup: &mut VaultSetup<StakingFarmObjBuilder, VaultObjBuilder>,
+    caller: &Address,
+    amount: u64,
+    expected_shares: u64,
+) where
+    StakingFarmObjBuilder: 'static + Copy + Fn() -> farm_staking::ContractObj<DebugApi>,
+    VaultObjBuilder: 'static + Copy + Fn() -> farm_staking_vault::ContractObj<DebugApi>,
+{
+    setup
+        .b_mock
+        .execute_esdt_transfer(
+            caller,
+            &setup.vault_wrapper,
+            STAKING_TOKEN_ID,
+            0,
+            &rust_biguint!(amount),
+            |sc| {
+                let shares = sc.deposit();
+                assert_eq!(shares.token_identifier, managed_token_id!(SHARE_TOKEN_ID));
+                assert_eq!(shares.amount, managed_biguint!(expected_shares));
+            },
+        )
+        .assert_ok();
+}
+
+fn check_staking_position_amount<StakingFarmObjBuilder, VaultObjBuilder>(
+    setup: &mut VaultSetup<StakingFarmObjBuilder, VaultObjBuilder>,
+    expected_amount: u64,
+) where
+    StakingFarmObjBuilder: 'static + Copy + Fn() -> farm_staking::ContractObj<DebugApi>,
+    VaultObjBuilder: 'static + Copy + Fn() -> farm_staking_vault::ContractObj<DebugApi>,
+{
+    setup
+        .b_mock
+        .execute_query(&setup.vault_wrapper, |sc| {
+            let position = sc.staking_position().get();
+            assert_eq!(
+                position.token_identifier,
+                managed_token_id!(STAKING_FARM_TOKEN_ID)
+            );
+            assert_eq!(position.amount, managed_biguint!(expected_amount));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_vault_deposit_compound_withdraw() {
+    let mut setup = setup_vault(farm_staking::contract_obj, farm_staking_vault::contract_obj);
+    let first_user = setup.first_user.clone();
+    let second_user = setup.second_user.clone();
+
+    let first_deposit = 100_000_000;
+    deposit(&mut setup, &first_user, first_deposit, first_deposit);
+    check_staking_position_amount(&mut setup, first_deposit);
+
+    // same rewards as in the farm staking unbond test, bounded by the max APR
+    setup.b_mock.set_block_nonce(10);
+    let expected_rewards = 40;
+    let expected_bounty = expected_rewards * COMPOUND_BOUNTY_PERCENT / MAX_PERCENT;
+    let bot = setup.b_mock.create_user_account(&rust_biguint!(0));
+    setup
+        .b_mock
+        .execute_tx(&bot, &setup.vault_wrapper, &rust_biguint!(0), |sc| {
+            let bounty = sc.compound_rewards();
+            assert_eq!(bounty.token_identifier, managed_token_id!(STAKING_TOKEN_ID));
+            assert_eq!(bounty.amount, managed_biguint!(expected_bounty));
+        })
+        .assert_ok();
+    setup
+        .b_mock
+        .check_esdt_balance(&bot, STAKING_TOKEN_ID, &rust_biguint!(expected_bounty));
+
+    let position_amount = first_deposit + expected_rewards - expected_bounty;
+    check_staking_position_amount(&mut setup, position_amount);
+    setup
+        .b_mock
+        .execute_query(&setup.vault_wrapper, |sc| {
+            let underlying = sc.get_underlying_for_shares(managed_biguint!(first_deposit));
+            assert_eq!(underlying, managed_biguint!(position_amount));
+        })
+        .assert_ok();
+
+    // the second user gets the same amount of shares for the same value
+    deposit(&mut setup, &second_user, position_amount, first_deposit);
+    check_staking_position_amount(&mut setup, 2 * position_amount);
+    setup
+        .b_mock
+        .execute_query(&setup.staking_farm_wrapper, |sc| {
+            assert_eq!(
+                sc.farm_token_supply().get(),
+                managed_biguint!(2 * position_amount)
+            );
+        })
+        .assert_ok();
+
+    // withdraw half of the first user's shares
+    let withdrawn_shares = first_deposit / 2;
+    let expected_unbond_amount = position_amount / 2;
+    let mut unbond_token_nonce = 0;
+    setup
+        .b_mock
+        .execute_esdt_transfer(
+            &first_user,
+            &setup.vault_wrapper,
+            SHARE_TOKEN_ID,
+            0,
+            &rust_biguint!(withdrawn_shares),
+            |sc| {
+                let payments = sc.withdraw().to_vec();
+                assert_eq!(payments.len(), 1);
+
+                let unbond_tokens = payments.get(0);
+                assert_eq!(
+                    unbond_tokens.token_identifier,
+                    managed_token_id!(STAKING_FARM_TOKEN_ID)
+                );
+                assert_eq!(
+                    unbond_tokens.amount,
+                    managed_biguint!(expected_unbond_amount)
+                );
+                unbond_token_nonce = unbond_tokens.token_nonce;
+            },
+        )
+        .assert_ok();
+
+    setup.b_mock.check_nft_balance(
+        &first_user,
+        STAKING_FARM_TOKEN_ID,
+        unbond_token_nonce,
+        &rust_biguint!(expected_unbond_amount),
+        Some(&UnbondSftAttributes {
+            unlock_epoch: MIN_UNBOND_EPOCHS,
+        }),
+    );
+    setup.b_mock.check_esdt_balance(
+        &first_user,
+        SHARE_TOKEN_ID,
+        &rust_biguint!(first_deposit - withdrawn_shares),
+    );
+    check_staking_position_amount(&mut setup, 2 * position_amount - expected_unbond_amount);
+
+    // the unbond tokens go through the staking farm's unbond flow
+    setup.b_mock.set_block_epoch(MIN_UNBOND_EPOCHS);
+    setup
+        .b_mock
+        .execute_esdt_transfer(
+            &first_user,
+            &setup.staking_farm_wrapper,
+            STAKING_FARM_TOKEN_ID,
+            unbond_token_nonce,
+            &rust_biguint!(expected_unbond_amount),
+            |sc| {
+                let payment = sc.unbond_farm();
+                assert_eq!(payment.amount, managed_biguint!(expected_unbond_amount));
+            },
+        )
+        .assert_ok();
+
+    setup.b_mock.check_esdt_balance(
+        &first_user,
+        STAKING_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_RIDE_TOKENS - first_deposit + expected_unbond_amount),
+    );
+}
+
+#[test]
+fn test_vault_emergency_exit() {
+    let mut setup = setup_vault(farm_staking::contract_obj, farm_staking_vault::contract_obj);
+    let first_user = setup.first_user.clone();
+    let second_user = setup.second_user.clone();
+
+    let first_deposit = 100_000_000;
+    let second_deposit = 300_000_000;
+    deposit(&mut setup, &first_user, first_deposit, first_deposit);
+    deposit(&mut setup, &second_user, second_deposit, second_deposit);
+    setup.b_mock.set_block_nonce(10);
+
+    // the staking farm must be paused first
+    let bot = setup.b_mock.create_user_account(&rust_biguint!(0));
+    setup
+        .b_mock
+        .execute_tx(&bot, &setup.vault_wrapper, &rust_biguint!(0), |sc| {
+            let _ = sc.emergency_exit();
+        })
+        .assert_user_error("Not paused");
+
+    setup
+        .b_mock
+        .execute_tx(&bot, &setup.staking_farm_wrapper, &rust_biguint!(0), |sc| {
+            sc.state().set(&State::Inactive);
+        })
+        .assert_ok();
+
+    let total_deposit = first_deposit + second_deposit;
+    setup
+        .b_mock
+        .execute_tx(&bot, &setup.vault_wrapper, &rust_biguint!(0), |sc| {
+            let recovered_amount = sc.emergency_exit();
+            assert_eq!(recovered_amount, managed_biguint!(total_deposit));
+            assert!(sc.staking_position().is_empty());
+        })
+        .assert_ok();
+    setup.b_mock.check_esdt_balance(
+        setup.vault_wrapper.address_ref(),
+        STAKING_TOKEN_ID,
+        &rust_biguint!(total_deposit),
+    );
+
+    // regular withdrawals are disabled
+    setup
+        .b_mock
+        .execute_esdt_transfer(
+            &first_user,
+            &setup.vault_wrapper,
+            SHARE_TOKEN_ID,
+            0,
+            &rust_biguint!(first_deposit),
+            |sc| {
+                let _ = sc.withdraw();
+            },
+        )
+        .assert_user_error("Vault exited");
+
+    // each shareholder gets a pro-rata part of the recovered tokens
+    setup
+        .b_mock
+        .execute_esdt_transfer(
+            &second_user,
+            &setup.vault_wrapper,
+            SHARE_TOKEN_ID,
+            0,
+            &rust_biguint!(second_deposit / 3),
+            |sc| {
+                let payment = sc.emergency_withdraw();
+                assert_eq!(
+                    payment.token_identifier,
+                    managed_token_id!(STAKING_TOKEN_ID)
+                );
+                assert_eq!(payment.amount, managed_biguint!(second_deposit / 3));
+            },
+        )
+        .assert_ok();
+    setup
+        .b_mock
+        .execute_esdt_transfer(
+            &first_user,
+            &setup.vault_wrapper,
+            SHARE_TOKEN_ID,
+            0,
+            &rust_biguint!(first_deposit),
+            |sc| {
+                let payment = sc.emergency_withdraw();
+                assert_eq!(payment.amount, managed_biguint!(first_deposit));
+            },
+        )
+        .assert_ok();
+
+    setup.b_mock.check_esdt_balance(
+        &first_user,
+        STAKING_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_RIDE_TOKENS),
+    );
+    setup.b_mock.check_esdt_balance(
+        &second_user,
+        STAKING_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_RIDE_TOKENS - second_deposit + second_deposit / 3),
+    );
+    setup
+        .b_mock
+        .execute_query(&setup.vault_wrapper, |sc| {
+            let remaining_shares = second_deposit - second_deposit / 3;
+            assert_eq!(sc.share_supply().get(), managed_biguint!(remaining_shares));
+            assert_eq!(
+                sc.get_underlying_for_shares(managed_biguint!(remaining_shares)),
+                managed_biguint!(remaining_shares)
+            );
+        })
+        .assert_ok();
+}
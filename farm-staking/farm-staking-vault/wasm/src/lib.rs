@@ -0,0 +1,33 @@
+////////////////////////////////////////////////////
+////////////////// AUTO-GENERATED //////////////////
+////////////////////////////////////////////////////
+
+#![no_std]
+
+elrond_wasm_node::wasm_endpoints! {
+    farm_staking_vault
+    (
+        callBack
+        addToPauseWhitelist
+        compoundRewards
+        deposit
+        emergencyExit
+        emergencyWithdraw
+        getCompoundBountyPercent
+        getRecoveredStakingAmount
+        getShareSupply
+        getShareTokenId
+        getStakingFarmAddress
+        getStakingPosition
+        getStakingTokenId
+        getState
+        getUnderlyingForShares
+        isEmergencyExited
+        pause
+        registerShareToken
+        removeFromPauseWhitelist
+        resume
+        setCompoundBountyPercent
+        withdraw
+    )
+}
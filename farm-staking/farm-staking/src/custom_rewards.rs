@@ -4,7 +4,35 @@ elrond_wasm::derive_imports!();
 use common_structs::Nonce;
 use config::MAX_PERCENT;
 
-pub const BLOCKS_IN_YEAR: u64 = 31_536_000 / 6; // seconds_in_year / 6_seconds_per_block
+use crate::farm_token_merge::StakingFarmTokenAttributes;
+
+pub use config::BLOCKS_IN_YEAR;
+
+/// Extra APR, on top of the max APR, for positions staked for at least `min_staking_epochs`.
+#[derive(
+    ManagedVecItem,
+    TopEncode,
+    TopDecode,
+    NestedEncode,
+    NestedDecode,
+    TypeAbi,
+    Clone,
+    PartialEq,
+    Debug,
+)]
+pub struct AprTier<M: ManagedTypeApi> {
+    pub min_staking_epochs: u64,
+    pub extra_apr: BigUint<M>,
+}
+
+/// The reward per share of the base APR and of each tier, recorded at the first
+/// reward aggregation of an epoch.
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct RewardPerShareCheckpoint<M: ManagedTypeApi> {
+    pub epoch: u64,
+    pub reward_per_share: BigUint<M>,
+    pub tier_reward_per_share: ManagedVec<M, BigUint<M>>,
+}
 
 #[elrond_wasm::module]
 pub trait CustomRewardsModule:
@@ -14,31 +42,60 @@ pub trait CustomRewardsModule:
     + pausable::PausableModule
     + elrond_wasm_modules::default_issue_callbacks::DefaultIssueCallbacksModule
 {
-    fn calculate_extra_rewards_since_last_allocation(&self) -> BigUint {
+    /// Returns the rewards generated since the last allocation at the base APR cap,
+    /// followed by the ones at the APR cap of each tier.
+    fn calculate_extra_rewards_since_last_allocation(&self) -> ManagedVec<BigUint> {
         let current_block_nonce = self.blockchain().get_block_nonce();
         let last_reward_nonce = self.last_reward_block_nonce().get();
 
+        let extra_rewards = self.calculate_level_rewards(current_block_nonce, last_reward_nonce);
         if current_block_nonce > last_reward_nonce {
-            let extra_rewards_unbounded =
-                self.calculate_per_block_rewards(current_block_nonce, last_reward_nonce);
-
-            let farm_token_supply = self.farm_token_supply().get();
-            let extra_rewards_apr_bounded_per_block =
-                self.get_amount_apr_bounded(&farm_token_supply);
+            self.last_reward_block_nonce().set(&current_block_nonce);
+        }
 
-            let block_nonce_diff = current_block_nonce - last_reward_nonce;
-            let extra_rewards_apr_bounded = extra_rewards_apr_bounded_per_block * block_nonce_diff;
+        extra_rewards
+    }
 
-            self.last_reward_block_nonce().set(&current_block_nonce);
+    /// The rewards generated between the two blocks at the base APR cap,
+    /// followed by the ones at the APR cap of each tier, which is raised by the tier's extra APR.
+    fn calculate_level_rewards(
+        &self,
+        current_block_nonce: Nonce,
+        last_reward_nonce: Nonce,
+    ) -> ManagedVec<BigUint> {
+        let mut level_rewards = ManagedVec::new();
+        if current_block_nonce <= last_reward_nonce {
+            return level_rewards;
+        }
 
-            core::cmp::min(extra_rewards_unbounded, extra_rewards_apr_bounded)
-        } else {
-            BigUint::zero()
+        let extra_rewards_unbounded =
+            self.calculate_per_block_rewards(current_block_nonce, last_reward_nonce);
+        let farm_token_supply = self.farm_token_supply().get();
+        let block_nonce_diff = current_block_nonce - last_reward_nonce;
+
+        let extra_rewards_apr_bounded =
+            self.get_amount_apr_bounded(&farm_token_supply, &BigUint::zero()) * block_nonce_diff;
+        level_rewards.push(core::cmp::min(
+            extra_rewards_unbounded.clone(),
+            extra_rewards_apr_bounded,
+        ));
+
+        for tier in self.apr_tiers().get().iter() {
+            let tier_rewards_apr_bounded =
+                self.get_amount_apr_bounded(&farm_token_supply, &tier.extra_apr) * block_nonce_diff;
+            level_rewards.push(core::cmp::min(
+                extra_rewards_unbounded.clone(),
+                tier_rewards_apr_bounded,
+            ));
         }
+
+        level_rewards
     }
 
     fn generate_aggregated_rewards(&self) {
-        let mut extra_rewards = self.calculate_extra_rewards_since_last_allocation();
+        let level_rewards = self.calculate_extra_rewards_since_last_allocation();
+        let base_rewards = level_rewards.try_get(0).unwrap_or_else(BigUint::zero);
+        let mut extra_rewards = base_rewards.clone();
 
         if extra_rewards > 0 {
             let mut accumulated_rewards = self.accumulated_rewards().get();
@@ -52,8 +109,22 @@ pub trait CustomRewardsModule:
             accumulated_rewards += &extra_rewards;
             self.accumulated_rewards().set(&accumulated_rewards);
 
+            // once the capacity is reached, the tiers do not accrue more than the base APR
+            let capacity_reached = extra_rewards < base_rewards;
+            let mut tier_rewards = ManagedVec::new();
+            for rewards in level_rewards.iter().skip(1) {
+                if capacity_reached {
+                    tier_rewards.push(extra_rewards.clone());
+                } else {
+                    tier_rewards.push(rewards);
+                }
+            }
+
+            self.update_tier_reward_per_share(&tier_rewards);
             self.update_reward_per_share(&extra_rewards);
         }
+
+        self.record_reward_per_share_checkpoint();
     }
 
     #[only_owner]
@@ -98,6 +169,283 @@ pub trait CustomRewardsModule:
         self.min_unbond_epochs().set(&min_unbond_epochs);
     }
 
+    /// Replaces the APR tiers. Each tier is given as a pair of
+    /// (min staking epochs, extra APR), in increasing order of both values.
+    /// Calling it without arguments removes all the tiers.
+    /// The new tiers apply to the rewards generated from now on.
+    #[only_owner]
+    #[endpoint(setAprTiers)]
+    fn set_apr_tiers(&self, tiers: MultiValueEncoded<MultiValue2<u64, BigUint>>) {
+        self.generate_aggregated_rewards();
+
+        let mut apr_tiers = ManagedVec::new();
+        let mut last_min_staking_epochs = 0u64;
+        let mut last_extra_apr = BigUint::zero();
+        for tier in tiers {
+            let (min_staking_epochs, extra_apr) = tier.into_tuple();
+            require!(
+                min_staking_epochs > last_min_staking_epochs,
+                "Tiers not in order"
+            );
+            require!(extra_apr > last_extra_apr, "Invalid tier APR");
+
+            last_min_staking_epochs = min_staking_epochs;
+            last_extra_apr = extra_apr.clone();
+            apr_tiers.push(AprTier {
+                min_staking_epochs,
+                extra_apr,
+            });
+        }
+
+        // a new tier starts from the reward per share of the tier below it
+        let reward_per_share = self.reward_per_share().get();
+        let old_tier_reward_per_share = self.tier_reward_per_share().get();
+        let mut tier_reward_per_share = ManagedVec::new();
+        for apr_tier in 1..=apr_tiers.len() {
+            tier_reward_per_share.push(self.get_level_reward_per_share(
+                &reward_per_share,
+                &old_tier_reward_per_share,
+                apr_tier,
+            ));
+        }
+
+        self.apr_tiers().set(&apr_tiers);
+        self.tier_reward_per_share().set(&tier_reward_per_share);
+        self.record_reward_per_share_checkpoint();
+    }
+
+    /// Positions created before the APR tiers were introduced have
+    /// an original entering epoch of 0, so their staking duration
+    /// is counted from the epoch of the upgrade instead.
+    fn get_staking_start_epoch(&self, original_entering_epoch: u64) -> u64 {
+        core::cmp::max(
+            original_entering_epoch,
+            self.staking_duration_start_epoch().get(),
+        )
+    }
+
+    fn get_staking_epochs(&self, original_entering_epoch: u64) -> u64 {
+        let start_epoch = self.get_staking_start_epoch(original_entering_epoch);
+        let current_epoch = self.blockchain().get_block_epoch();
+
+        current_epoch.saturating_sub(start_epoch)
+    }
+
+    /// The number of tiers reached by a position. 0 means the base APR.
+    fn get_apr_tier(&self, original_entering_epoch: u64) -> usize {
+        let staking_epochs = self.get_staking_epochs(original_entering_epoch);
+
+        let mut apr_tier = 0;
+        for tier in self.apr_tiers().get().iter() {
+            if staking_epochs < tier.min_staking_epochs {
+                break;
+            }
+
+            apr_tier += 1;
+        }
+
+        apr_tier
+    }
+
+    fn get_extra_apr(&self, original_entering_epoch: u64) -> BigUint {
+        match self.get_apr_tier(original_entering_epoch) {
+            0 => BigUint::zero(),
+            apr_tier => self.apr_tiers().get().get(apr_tier - 1).extra_apr,
+        }
+    }
+
+    /// Tier 0 is the base reward per share. A tier missing from `tier_reward_per_share`,
+    /// as it was added later, has the value of the highest tier below it.
+    fn get_level_reward_per_share(
+        &self,
+        reward_per_share: &BigUint,
+        tier_reward_per_share: &ManagedVec<BigUint>,
+        apr_tier: usize,
+    ) -> BigUint {
+        match core::cmp::min(apr_tier, tier_reward_per_share.len()) {
+            0 => reward_per_share.clone(),
+            index => tier_reward_per_share.get(index - 1),
+        }
+    }
+
+    fn get_tier_reward_per_share(&self, apr_tier: usize) -> BigUint {
+        self.get_level_reward_per_share(
+            &self.reward_per_share().get(),
+            &self.tier_reward_per_share().get(),
+            apr_tier,
+        )
+    }
+
+    /// Returns the first checkpoint recorded in or after the given epoch.
+    fn find_reward_per_share_checkpoint(
+        &self,
+        epoch: u64,
+    ) -> Option<RewardPerShareCheckpoint<Self::Api>> {
+        let checkpoints = self.reward_per_share_checkpoints();
+        let mut low = 1;
+        let mut high = checkpoints.len() + 1;
+        while low < high {
+            let mid = (low + high) / 2;
+            if checkpoints.get(mid).epoch < epoch {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        if low > checkpoints.len() {
+            None
+        } else {
+            Some(checkpoints.get(low))
+        }
+    }
+
+    fn record_reward_per_share_checkpoint(&self) {
+        if self.apr_tiers().get().is_empty() {
+            return;
+        }
+
+        let current_epoch = self.blockchain().get_block_epoch();
+        let mut checkpoints = self.reward_per_share_checkpoints();
+        if !checkpoints.is_empty() && checkpoints.get(checkpoints.len()).epoch >= current_epoch {
+            return;
+        }
+
+        checkpoints.push(&RewardPerShareCheckpoint {
+            epoch: current_epoch,
+            reward_per_share: self.reward_per_share().get(),
+            tier_reward_per_share: self.tier_reward_per_share().get(),
+        });
+    }
+
+    /// The rewards of a position since its last claim, each part accrued at the APR cap
+    /// of the tier the position was in. The accrual is split at the epochs in which the position
+    /// reached each tier, using the first checkpoint recorded in or after that epoch.
+    fn calculate_tiered_reward(
+        &self,
+        amount: &BigUint,
+        attributes: &StakingFarmTokenAttributes<Self::Api>,
+        current_reward_per_share: &BigUint,
+        current_tier_reward_per_share: &ManagedVec<BigUint>,
+    ) -> BigUint {
+        let apr_tiers = self.apr_tiers().get();
+        let start_epoch = self.get_staking_start_epoch(attributes.original_entering_epoch);
+        let current_apr_tier = self.get_apr_tier(attributes.original_entering_epoch);
+
+        let mut reward = BigUint::zero();
+        let mut apr_tier = attributes.apr_tier;
+        let mut tier_start_reward_per_share = attributes.tier_reward_per_share.clone();
+        while apr_tier < current_apr_tier {
+            let next_tier_epoch = start_epoch + apr_tiers.get(apr_tier).min_staking_epochs;
+            let (reward_per_share, tier_reward_per_share) =
+                match self.find_reward_per_share_checkpoint(next_tier_epoch) {
+                    Some(checkpoint) => (
+                        checkpoint.reward_per_share,
+                        checkpoint.tier_reward_per_share,
+                    ),
+                    None => (
+                        current_reward_per_share.clone(),
+                        current_tier_reward_per_share.clone(),
+                    ),
+                };
+
+            let tier_end_reward_per_share = self.get_level_reward_per_share(
+                &reward_per_share,
+                &tier_reward_per_share,
+                apr_tier,
+            );
+            reward += self.calculate_reward(
+                amount,
+                &tier_end_reward_per_share,
+                &tier_start_reward_per_share,
+            );
+
+            apr_tier += 1;
+            tier_start_reward_per_share = self.get_level_reward_per_share(
+                &reward_per_share,
+                &tier_reward_per_share,
+                apr_tier,
+            );
+        }
+
+        let current_tier_end_reward_per_share = self.get_level_reward_per_share(
+            current_reward_per_share,
+            current_tier_reward_per_share,
+            current_apr_tier,
+        );
+        reward += self.calculate_reward(
+            amount,
+            &current_tier_end_reward_per_share,
+            &tier_start_reward_per_share,
+        );
+
+        reward
+    }
+
+    /// The part of the rewards of a position above the base APR cap,
+    /// which is not included in the aggregated rewards.
+    /// It is limited by the remaining reward capacity.
+    fn calculate_tier_bonus(
+        &self,
+        amount: &BigUint,
+        attributes: &StakingFarmTokenAttributes<Self::Api>,
+        base_reward: &BigUint,
+        current_reward_per_share: &BigUint,
+        current_tier_reward_per_share: &ManagedVec<BigUint>,
+    ) -> BigUint {
+        if self.apr_tiers().get().is_empty() {
+            return BigUint::zero();
+        }
+
+        let tiered_reward = self.calculate_tiered_reward(
+            amount,
+            attributes,
+            current_reward_per_share,
+            current_tier_reward_per_share,
+        );
+        if &tiered_reward <= base_reward {
+            return BigUint::zero();
+        }
+
+        let bonus = tiered_reward - base_reward;
+        let accumulated_rewards = self.accumulated_rewards().get();
+        let reward_capacity = self.reward_capacity().get();
+        let remaining_capacity = if reward_capacity > accumulated_rewards {
+            reward_capacity - accumulated_rewards
+        } else {
+            BigUint::zero()
+        };
+
+        core::cmp::min(bonus, remaining_capacity)
+    }
+
+    /// Expects the rewards to be aggregated beforehand.
+    fn allocate_tier_bonus(
+        &self,
+        amount: &BigUint,
+        attributes: &StakingFarmTokenAttributes<Self::Api>,
+        base_reward: &BigUint,
+    ) -> BigUint {
+        let bonus = self.calculate_tier_bonus(
+            amount,
+            attributes,
+            base_reward,
+            &self.reward_per_share().get(),
+            &self.tier_reward_per_share().get(),
+        );
+        if bonus > 0 {
+            self.accumulated_rewards().update(|r| *r += &bonus);
+        }
+
+        bonus
+    }
+
+    /// The APR cap of a position staked since the given epoch, for the rewards generated from now on.
+    #[view(getMaxAprForPosition)]
+    fn get_max_apr_for_position(&self, original_entering_epoch: u64) -> BigUint {
+        self.max_annual_percentage_rewards().get() + self.get_extra_apr(original_entering_epoch)
+    }
+
     fn calculate_per_block_rewards(
         &self,
         current_block_nonce: Nonce,
@@ -113,6 +461,37 @@ pub trait CustomRewardsModule:
         per_block_reward * block_nonce_diff
     }
 
+    fn update_tier_reward_per_share(&self, tier_rewards: &ManagedVec<BigUint>) {
+        let farm_token_supply = self.farm_token_supply().get();
+        if farm_token_supply > 0 {
+            let tier_reward_per_share = self.increase_tier_reward_per_share(
+                &self.reward_per_share().get(),
+                &self.tier_reward_per_share().get(),
+                tier_rewards,
+                &farm_token_supply,
+            );
+            self.tier_reward_per_share().set(&tier_reward_per_share);
+        }
+    }
+
+    fn increase_tier_reward_per_share(
+        &self,
+        reward_per_share: &BigUint,
+        tier_reward_per_share: &ManagedVec<BigUint>,
+        tier_rewards: &ManagedVec<BigUint>,
+        farm_token_supply: &BigUint,
+    ) -> ManagedVec<BigUint> {
+        let mut new_tier_reward_per_share = ManagedVec::new();
+        for (index, rewards) in tier_rewards.iter().enumerate() {
+            let increase = self.calculate_reward_per_share_increase(&rewards, farm_token_supply);
+            let old_value =
+                self.get_level_reward_per_share(reward_per_share, tier_reward_per_share, index + 1);
+            new_tier_reward_per_share.push(old_value + increase);
+        }
+
+        new_tier_reward_per_share
+    }
+
     fn update_reward_per_share(&self, reward_increase: &BigUint) {
         let farm_token_supply = self.farm_token_supply().get();
         if farm_token_supply > 0 {
@@ -144,11 +523,52 @@ pub trait CustomRewardsModule:
         }
     }
 
-    fn get_amount_apr_bounded(&self, amount: &BigUint) -> BigUint {
-        let max_apr = self.max_annual_percentage_rewards().get();
+    fn get_amount_apr_bounded(&self, amount: &BigUint, extra_apr: &BigUint) -> BigUint {
+        let max_apr = self.max_annual_percentage_rewards().get() + extra_apr;
         amount * &max_apr / MAX_PERCENT / BLOCKS_IN_YEAR
     }
 
+    fn calculate_per_block_rewards_apr_bounded(&self, farm_token_supply: &BigUint) -> BigUint {
+        if !self.produces_per_block_rewards()
+            || self.accumulated_rewards().get() >= self.reward_capacity().get()
+        {
+            return BigUint::zero();
+        }
+
+        let per_block_reward = self.per_block_reward_amount().get();
+        let per_block_reward_apr_bounded =
+            self.get_amount_apr_bounded(farm_token_supply, &BigUint::zero());
+
+        core::cmp::min(per_block_reward, per_block_reward_apr_bounded)
+    }
+
+    /// Rewards generated in one block for each staked token,
+    /// multiplied by the division safety constant.
+    #[view(getRewardPerBlockPerFarmingToken)]
+    fn get_reward_per_block_per_farming_token(&self) -> BigUint {
+        let farm_token_supply = self.farm_token_supply().get();
+        if farm_token_supply == 0 {
+            return BigUint::zero();
+        }
+
+        let per_block_reward = self.calculate_per_block_rewards_apr_bounded(&farm_token_supply);
+        per_block_reward * self.division_safety_constant().get() / farm_token_supply
+    }
+
+    /// Rewards generated in one year for each staked token, at the current rate,
+    /// multiplied by the division safety constant.
+    #[view(getAnnualRewardPerFarmingToken)]
+    fn get_annual_reward_per_farming_token(&self) -> BigUint {
+        let farm_token_supply = self.farm_token_supply().get();
+        if farm_token_supply == 0 {
+            return BigUint::zero();
+        }
+
+        let per_block_reward = self.calculate_per_block_rewards_apr_bounded(&farm_token_supply);
+        per_block_reward * BLOCKS_IN_YEAR * self.division_safety_constant().get()
+            / farm_token_supply
+    }
+
     #[only_owner]
     #[endpoint(startProduceRewards)]
     fn start_produce_rewards(&self) {
@@ -186,6 +606,21 @@ pub trait CustomRewardsModule:
     #[storage_mapper("annualPercentageRewards")]
     fn max_annual_percentage_rewards(&self) -> SingleValueMapper<BigUint>;
 
+    #[view(getAprTiers)]
+    #[storage_mapper("aprTiers")]
+    fn apr_tiers(&self) -> SingleValueMapper<ManagedVec<AprTier<Self::Api>>>;
+
+    #[view(getTierRewardPerShare)]
+    #[storage_mapper("tierRewardPerShare")]
+    fn tier_reward_per_share(&self) -> SingleValueMapper<ManagedVec<BigUint>>;
+
+    #[storage_mapper("rewardPerShareCheckpoints")]
+    fn reward_per_share_checkpoints(&self) -> VecMapper<RewardPerShareCheckpoint<Self::Api>>;
+
+    #[view(getStakingDurationStartEpoch)]
+    #[storage_mapper("stakingDurationStartEpoch")]
+    fn staking_duration_start_epoch(&self) -> SingleValueMapper<u64>;
+
     #[view(getMinUnbondEpochs)]
     #[storage_mapper("minUnbondEpochs")]
     fn min_unbond_epochs(&self) -> SingleValueMapper<u64>;
@@ -0,0 +1,31 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use crate::farm_token_merge::StakingFarmTokenAttributes;
+
+#[derive(TypeAbi, TopEncode)]
+pub struct EmergencyExitEvent<M: ManagedTypeApi> {
+    pub caller: ManagedAddress<M>,
+    pub farming_token_id: TokenIdentifier<M>,
+    pub farming_token_amount: BigUint<M>,
+    pub farm_token_id: TokenIdentifier<M>,
+    pub farm_token_nonce: u64,
+    pub farm_token_amount: BigUint<M>,
+    pub farm_supply: BigUint<M>,
+    pub farm_attributes: StakingFarmTokenAttributes<M>,
+    pub block: u64,
+    pub epoch: u64,
+    pub timestamp: u64,
+}
+
+#[elrond_wasm::module]
+pub trait EventsModule {
+    #[event("emergency_exit")]
+    fn emergency_exit_event(
+        &self,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] farm_token: &TokenIdentifier,
+        #[indexed] epoch: u64,
+        emergency_exit_event: &EmergencyExitEvent<Self::Api>,
+    );
+}
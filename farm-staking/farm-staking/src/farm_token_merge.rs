@@ -1,23 +1,61 @@
 elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
+use elrond_wasm::elrond_codec::{NestedDecodeInput, TopDecodeInput};
 use token_merge::ValueWeight;
 
+use crate::custom_rewards;
+
 #[derive(
-    ManagedVecItem,
-    TopEncode,
-    TopDecode,
-    NestedEncode,
-    NestedDecode,
-    TypeAbi,
-    Clone,
-    PartialEq,
-    Debug,
+    ManagedVecItem, TopEncode, NestedEncode, NestedDecode, TypeAbi, Clone, PartialEq, Debug,
 )]
 pub struct StakingFarmTokenAttributes<M: ManagedTypeApi> {
     pub reward_per_share: BigUint<M>,
     pub compounded_reward: BigUint<M>,
     pub current_farm_amount: BigUint<M>,
+    pub original_entering_epoch: u64,
+    pub apr_tier: usize,
+    pub tier_reward_per_share: BigUint<M>,
+}
+
+/// Farm tokens created before the APR tiers do not have the original entering epoch,
+/// in which case it is decoded as 0, and are in the base APR tier.
+impl<M: ManagedTypeApi> TopDecode for StakingFarmTokenAttributes<M> {
+    fn top_decode<I>(input: I) -> Result<Self, DecodeError>
+    where
+        I: TopDecodeInput,
+    {
+        let mut buffer = input.into_nested_buffer();
+        let reward_per_share = BigUint::dep_decode(&mut buffer)?;
+        let compounded_reward = BigUint::dep_decode(&mut buffer)?;
+        let current_farm_amount = BigUint::dep_decode(&mut buffer)?;
+        let original_entering_epoch = if buffer.is_depleted() {
+            0
+        } else {
+            u64::dep_decode(&mut buffer)?
+        };
+        let (apr_tier, tier_reward_per_share) = if buffer.is_depleted() {
+            (0, reward_per_share.clone())
+        } else {
+            (
+                usize::dep_decode(&mut buffer)?,
+                BigUint::dep_decode(&mut buffer)?,
+            )
+        };
+
+        if !buffer.is_depleted() {
+            return Err(DecodeError::INPUT_TOO_LONG);
+        }
+
+        Ok(StakingFarmTokenAttributes {
+            reward_per_share,
+            compounded_reward,
+            current_farm_amount,
+            original_entering_epoch,
+            apr_tier,
+            tier_reward_per_share,
+        })
+    }
 }
 
 #[derive(ManagedVecItem, Clone)]
@@ -28,7 +66,8 @@ pub struct StakingFarmToken<M: ManagedTypeApi> {
 
 #[elrond_wasm::module]
 pub trait FarmTokenMergeModule:
-    token_send::TokenSendModule
+    custom_rewards::CustomRewardsModule
+    + token_send::TokenSendModule
     + farm_token::FarmTokenModule
     + config::ConfigModule
     + token_merge::TokenMergeModule
@@ -41,6 +80,7 @@ pub trait FarmTokenMergeModule:
         let caller = self.blockchain().get_caller();
         let payments = self.call_value().all_esdt_transfers();
 
+        self.generate_aggregated_rewards();
         let attrs = self.get_merged_farm_token_attributes(&payments, None);
 
         self.burn_farm_tokens_from_payments(&payments);
@@ -49,6 +89,74 @@ pub trait FarmTokenMergeModule:
             .nft_create_and_send(&caller, attrs.current_farm_amount.clone(), &attrs)
     }
 
+    /// Splits a farm position into several positions with the given amounts.
+    /// The compounded reward is divided proportionally, while the reward per share is kept.
+    #[payable("*")]
+    #[endpoint(splitFarmToken)]
+    fn split_farm_token(
+        &self,
+        amounts: MultiValueEncoded<BigUint>,
+    ) -> MultiValueEncoded<EsdtTokenPayment<Self::Api>> {
+        let caller = self.blockchain().get_caller();
+        let payment = self.call_value().single_esdt();
+        let farm_token_id = self.farm_token().get_token_id();
+        require!(
+            payment.token_identifier == farm_token_id,
+            "Not a farm token"
+        );
+
+        let amounts = amounts.to_vec();
+        require!(amounts.len() > 1, "Bad split amounts");
+
+        let mut total_amount = BigUint::zero();
+        for amount in amounts.iter() {
+            require!(amount != 0u64, "zero entry amount");
+            total_amount += &amount;
+        }
+        require!(total_amount == payment.amount, "Bad split amounts");
+
+        let attributes: StakingFarmTokenAttributes<Self::Api> =
+            self.get_attributes(&payment.token_identifier, payment.token_nonce);
+        let total_compounded_reward = self.rule_of_three(
+            &payment.amount,
+            &attributes.current_farm_amount,
+            &attributes.compounded_reward,
+        );
+        let mut remaining_compounded_reward = total_compounded_reward.clone();
+
+        self.burn_farm_tokens(
+            &payment.token_identifier,
+            payment.token_nonce,
+            &payment.amount,
+        );
+
+        let last_index = amounts.len() - 1;
+        let mut new_tokens = ManagedVec::new();
+        for (i, amount) in amounts.iter().enumerate() {
+            // the last position gets the remainder, so nothing is lost to rounding
+            let compounded_reward = if i == last_index {
+                remaining_compounded_reward.clone()
+            } else {
+                self.rule_of_three(&amount, &payment.amount, &total_compounded_reward)
+            };
+            remaining_compounded_reward -= &compounded_reward;
+
+            let new_attributes = StakingFarmTokenAttributes {
+                reward_per_share: attributes.reward_per_share.clone(),
+                compounded_reward,
+                current_farm_amount: amount.clone(),
+                original_entering_epoch: attributes.original_entering_epoch,
+                apr_tier: attributes.apr_tier,
+                tier_reward_per_share: attributes.tier_reward_per_share.clone(),
+            };
+            new_tokens.push(self.mint_farm_tokens(farm_token_id.clone(), amount, &new_attributes));
+        }
+
+        self.send().direct_multi(&caller, &new_tokens);
+
+        new_tokens.into()
+    }
+
     fn get_merged_farm_token_attributes(
         &self,
         payments: &ManagedVec<EsdtTokenPayment<Self::Api>>,
@@ -88,10 +196,15 @@ pub trait FarmTokenMergeModule:
             }
         }
 
+        let original_entering_epoch = self.aggregated_original_entering_epoch(&tokens);
+        let apr_tier = self.get_apr_tier(original_entering_epoch);
         StakingFarmTokenAttributes {
             reward_per_share: self.aggregated_reward_per_share(&tokens),
             compounded_reward: self.aggregated_compounded_reward(&tokens),
             current_farm_amount: self.aggregated_current_farm_amount(&tokens),
+            original_entering_epoch,
+            apr_tier,
+            tier_reward_per_share: self.aggregated_tier_reward_per_share(&tokens, apr_tier),
         }
     }
 
@@ -109,6 +222,62 @@ pub trait FarmTokenMergeModule:
         self.weighted_average_ceil(dataset)
     }
 
+    /// The reward per share of the merged position's tier is set so that the pending rewards
+    /// of the merged positions, each accrued at the APR cap of its own tiers, are kept.
+    /// Expects the rewards to be aggregated beforehand.
+    fn aggregated_tier_reward_per_share(
+        &self,
+        tokens: &ManagedVec<StakingFarmToken<Self::Api>>,
+        merged_apr_tier: usize,
+    ) -> BigUint {
+        let current_reward_per_share = self.reward_per_share().get();
+        let current_tier_reward_per_share = self.tier_reward_per_share().get();
+
+        let mut total_amount = BigUint::zero();
+        let mut pending_rewards = BigUint::zero();
+        for token in tokens.iter() {
+            pending_rewards += self.calculate_tiered_reward(
+                &token.payment.amount,
+                &token.attributes,
+                &current_reward_per_share,
+                &current_tier_reward_per_share,
+            );
+            total_amount += &token.payment.amount;
+        }
+
+        let merged_tier_reward_per_share = self.get_level_reward_per_share(
+            &current_reward_per_share,
+            &current_tier_reward_per_share,
+            merged_apr_tier,
+        );
+        let reward_per_share_diff =
+            pending_rewards * self.division_safety_constant().get() / total_amount;
+        if merged_tier_reward_per_share > reward_per_share_diff {
+            merged_tier_reward_per_share - reward_per_share_diff
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    /// Positions created before the APR tiers count from the staking duration start epoch.
+    fn aggregated_original_entering_epoch(
+        &self,
+        tokens: &ManagedVec<StakingFarmToken<Self::Api>>,
+    ) -> u64 {
+        let start_epoch = self.staking_duration_start_epoch().get();
+        let mut dataset = ManagedVec::new();
+        tokens.iter().for_each(|x| {
+            let original_entering_epoch =
+                core::cmp::max(x.attributes.original_entering_epoch, start_epoch);
+            dataset.push(ValueWeight {
+                value: BigUint::from(original_entering_epoch),
+                weight: x.payment.amount,
+            })
+        });
+
+        self.weighted_average_ceil(dataset).to_u64().unwrap()
+    }
+
     fn aggregated_compounded_reward(
         &self,
         tokens: &ManagedVec<StakingFarmToken<Self::Api>>,
@@ -4,6 +4,7 @@
 #![allow(clippy::too_many_arguments)]
 
 pub mod custom_rewards;
+pub mod events;
 pub mod farm_token_merge;
 pub mod whitelist;
 
@@ -13,6 +14,7 @@ use pausable::State;
 elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
+use crate::events::EmergencyExitEvent;
 use crate::farm_token_merge::StakingFarmTokenAttributes;
 use config::{DEFAULT_BURN_GAS_LIMIT, DEFAULT_MINUMUM_FARMING_EPOCHS};
 use farm_token_merge::StakingFarmToken;
@@ -39,6 +41,7 @@ pub trait Farm:
     + farm_token::FarmTokenModule
     + farm_token_merge::FarmTokenMergeModule
     + whitelist::WhitelistModule
+    + events::EventsModule
     + pausable::PausableModule
     + elrond_wasm_modules::default_issue_callbacks::DefaultIssueCallbacksModule
 {
@@ -81,6 +84,8 @@ pub trait Farm:
         self.farming_token_id().set(&farming_token_id);
         self.max_annual_percentage_rewards().set(&max_apr);
         self.min_unbond_epochs().set(min_unbond_epochs);
+        self.staking_duration_start_epoch()
+            .set_if_empty(self.blockchain().get_block_epoch());
 
         let caller = self.blockchain().get_caller();
         self.pause_whitelist().add(&caller);
@@ -139,6 +144,9 @@ pub trait Farm:
             reward_per_share: self.reward_per_share().get(),
             compounded_reward: BigUint::zero(),
             current_farm_amount: enter_amount.clone(),
+            original_entering_epoch: self.blockchain().get_block_epoch(),
+            apr_tier: 0,
+            tier_reward_per_share: self.reward_per_share().get(),
         };
 
         let caller = self.blockchain().get_caller();
@@ -175,30 +183,51 @@ pub trait Farm:
         self.require_whitelisted(&caller);
 
         let payments = self.call_value().all_esdt_transfers();
-        require!(payments.len() == 2, "Invalid payments amount");
+        require!(
+            payments.len() == 1 || payments.len() == 2,
+            "Invalid payments amount"
+        );
 
         // first payment are the staking tokens, taken from the liquidity pool
         // they will be sent to the user on unbond
-        let first_payment = payments.get(0);
-        let staking_token_id = self.farming_token_id().get();
-        require!(
-            first_payment.token_identifier == staking_token_id,
-            "Invalid first payment"
-        );
+        // they are missing if the proxy keeps the LP farm position
+        let staking_token_amount = if payments.len() == 2 {
+            let first_payment = payments.get(0);
+            let staking_token_id = self.farming_token_id().get();
+            require!(
+                first_payment.token_identifier == staking_token_id,
+                "Invalid first payment"
+            );
+
+            first_payment.amount
+        } else {
+            BigUint::zero()
+        };
 
-        let second_payment = payments.get(1);
+        let farm_token_payment = payments.get(payments.len() - 1);
         let farm_token_id = self.farm_token().get_token_id();
         require!(
-            second_payment.token_identifier == farm_token_id,
+            farm_token_payment.token_identifier == farm_token_id,
             "Invalid second payment"
         );
 
+        // the compounded rewards are real tokens held by this contract
+        let farm_attributes = self.get_attributes::<StakingFarmTokenAttributes<Self::Api>>(
+            &farm_token_id,
+            farm_token_payment.token_nonce,
+        );
+        let compounded_reward = self.rule_of_three(
+            &farm_token_payment.amount,
+            &farm_attributes.current_farm_amount,
+            &farm_attributes.compounded_reward,
+        );
+
         self.unstake_farm_common(
             farm_token_id,
-            second_payment.token_identifier,
-            second_payment.token_nonce,
-            second_payment.amount,
-            Some(first_payment.amount),
+            farm_token_payment.token_identifier,
+            farm_token_payment.token_nonce,
+            farm_token_payment.amount,
+            Some(staking_token_amount + compounded_reward),
         )
     }
 
@@ -223,11 +252,12 @@ pub trait Farm:
         let reward_token_id = self.reward_token_id().get();
         self.generate_aggregated_rewards();
 
-        let reward = self.calculate_reward(
+        let mut reward = self.calculate_reward(
             &payment_amount,
             &self.reward_per_share().get(),
             &farm_attributes.reward_per_share,
         );
+        reward += self.allocate_tier_bonus(&payment_amount, &farm_attributes, &reward);
 
         let caller = self.blockchain().get_caller();
         self.burn_farm_tokens(&payment_token_id, token_nonce, &payment_amount);
@@ -236,8 +266,11 @@ pub trait Farm:
             Some(amt) => amt,
             None => payment_amount, // payment_amount = initial_farming + compounded_rewards
         };
-        let farm_token_payment =
-            self.create_and_send_unbond_tokens(&caller, farm_token_id, unbond_token_amount);
+        let farm_token_payment = if unbond_token_amount > 0u32 {
+            self.create_and_send_unbond_tokens(&caller, farm_token_id, unbond_token_amount)
+        } else {
+            EsdtTokenPayment::new(farm_token_id, 0, unbond_token_amount)
+        };
 
         self.send_rewards(&reward_token_id, &reward, &caller);
 
@@ -268,28 +301,17 @@ pub trait Farm:
         EsdtTokenPayment::new(farm_token_id, nft_nonce, amount)
     }
 
+    /// Accepts any number of unbond tokens, of one or several nonces,
+    /// each of them fully or partially, and sends back the staked tokens.
     #[payable("*")]
     #[endpoint(unbondFarm)]
     fn unbond_farm(&self) -> UnbondFarmResultType<Self::Api> {
         require!(self.is_active(), "Not active");
         require!(!self.farm_token().is_empty(), "No farm token");
 
-        let (payment_token_id, token_nonce, amount) = self.call_value().single_esdt().into_tuple();
-
+        let payments = self.call_value().all_esdt_transfers();
         let farm_token_id = self.farm_token().get_token_id();
-        require!(payment_token_id == farm_token_id, "Bad input token");
-        require!(amount > 0, "Payment amount cannot be zero");
-
-        let attributes: UnbondSftAttributes =
-            self.get_farm_token_attributes(&farm_token_id, token_nonce);
-        let current_epoch = self.blockchain().get_block_epoch();
-        require!(
-            current_epoch >= attributes.unlock_epoch,
-            "Unbond period not over"
-        );
-
-        self.send()
-            .esdt_local_burn(&farm_token_id, token_nonce, &amount);
+        let amount = self.burn_unbond_tokens(&farm_token_id, &payments, true);
 
         let caller = self.blockchain().get_caller();
         let farming_token_id = self.farming_token_id().get();
@@ -299,6 +321,77 @@ pub trait Farm:
         EsdtTokenPayment::new(farming_token_id, 0, amount)
     }
 
+    /// Turns unbond tokens back into a staking position,
+    /// which starts earning rewards from the current reward per share.
+    #[payable("*")]
+    #[endpoint(cancelUnbond)]
+    fn cancel_unbond(&self) -> EnterFarmResultType<Self::Api> {
+        require!(self.is_active(), "Not active");
+        require!(!self.farm_token().is_empty(), "No farm token");
+
+        let payments = self.call_value().all_esdt_transfers();
+        let farm_token_id = self.farm_token().get_token_id();
+        let amount = self.burn_unbond_tokens(&farm_token_id, &payments, false);
+
+        self.generate_aggregated_rewards();
+
+        let attributes = StakingFarmTokenAttributes {
+            reward_per_share: self.reward_per_share().get(),
+            compounded_reward: BigUint::zero(),
+            current_farm_amount: amount.clone(),
+            original_entering_epoch: self.blockchain().get_block_epoch(),
+            apr_tier: 0,
+            tier_reward_per_share: self.reward_per_share().get(),
+        };
+        let new_tokens = self.mint_farm_tokens(farm_token_id, amount, &attributes);
+
+        let caller = self.blockchain().get_caller();
+        self.send().direct_esdt(
+            &caller,
+            &new_tokens.token_identifier,
+            new_tokens.token_nonce,
+            &new_tokens.amount,
+        );
+
+        new_tokens
+    }
+
+    /// Burns the given unbond tokens and returns their total amount.
+    /// Unbond tokens are not part of the farm token supply.
+    fn burn_unbond_tokens(
+        &self,
+        farm_token_id: &TokenIdentifier,
+        payments: &ManagedVec<EsdtTokenPayment<Self::Api>>,
+        require_unbond_period_over: bool,
+    ) -> BigUint {
+        require!(!payments.is_empty(), "empty payments");
+
+        let current_epoch = self.blockchain().get_block_epoch();
+        let mut total_amount = BigUint::zero();
+        for payment in payments.iter() {
+            require!(
+                &payment.token_identifier == farm_token_id,
+                "Bad input token"
+            );
+            require!(payment.amount > 0, "Payment amount cannot be zero");
+
+            let attributes: UnbondSftAttributes =
+                self.get_farm_token_attributes(farm_token_id, payment.token_nonce);
+            if require_unbond_period_over {
+                require!(
+                    current_epoch >= attributes.unlock_epoch,
+                    "Unbond period not over"
+                );
+            }
+
+            self.send()
+                .esdt_local_burn(farm_token_id, payment.token_nonce, &payment.amount);
+            total_amount += payment.amount;
+        }
+
+        total_amount
+    }
+
     #[payable("*")]
     #[endpoint(claimRewards)]
     fn claim_rewards(&self) -> ClaimRewardsResultType<Self::Api> {
@@ -341,12 +434,21 @@ pub trait Farm:
         let farm_token_id = self.farm_token().get_token_id();
         require!(payment_token_id == farm_token_id, "Unknown farm token");
 
+        // the new value only covers the LP position, the compounded rewards are kept
+        let farm_attributes = self
+            .get_attributes::<StakingFarmTokenAttributes<Self::Api>>(&farm_token_id, token_nonce);
+        let compounded_reward = self.rule_of_three(
+            &old_farming_amount,
+            &farm_attributes.current_farm_amount,
+            &farm_attributes.compounded_reward,
+        );
+
         self.claim_rewards_common(
             caller,
             farm_token_id,
             token_nonce,
             old_farming_amount,
-            new_farming_amount,
+            new_farming_amount + compounded_reward,
         )
     }
 
@@ -366,20 +468,25 @@ pub trait Farm:
         self.generate_aggregated_rewards();
 
         let current_reward_per_share = self.reward_per_share().get();
-        let reward = self.calculate_reward(
+        let mut reward = self.calculate_reward(
             &old_farming_amount,
             &current_reward_per_share,
             &farm_attributes.reward_per_share,
         );
+        reward += self.allocate_tier_bonus(&old_farming_amount, &farm_attributes, &reward);
         let new_compound_reward_amount = self.rule_of_three(
             &old_farming_amount,
             &farm_attributes.current_farm_amount,
             &farm_attributes.compounded_reward,
         );
+        let apr_tier = self.get_apr_tier(farm_attributes.original_entering_epoch);
         let new_attributes = StakingFarmTokenAttributes {
             reward_per_share: current_reward_per_share,
             compounded_reward: new_compound_reward_amount,
             current_farm_amount: new_farming_amount.clone(),
+            original_entering_epoch: farm_attributes.original_entering_epoch,
+            apr_tier,
+            tier_reward_per_share: self.get_tier_reward_per_share(apr_tier),
         };
 
         self.burn_farm_tokens(&farm_token_id, farm_token_nonce, &old_farming_amount);
@@ -442,11 +549,12 @@ pub trait Farm:
             &payment_token_id,
             payment_token_nonce,
         );
-        let reward = self.calculate_reward(
+        let mut reward = self.calculate_reward(
             &payment_amount,
             &current_rps,
             &farm_attributes.reward_per_share,
         );
+        reward += self.allocate_tier_bonus(&payment_amount, &farm_attributes, &reward);
 
         let new_farm_contribution = &payment_amount + &reward;
         let new_compound_reward_amount = &self.rule_of_three(
@@ -455,10 +563,14 @@ pub trait Farm:
             &farm_attributes.compounded_reward,
         ) + &reward;
 
+        let apr_tier = self.get_apr_tier(farm_attributes.original_entering_epoch);
         let new_attributes = StakingFarmTokenAttributes {
             reward_per_share: current_rps,
             compounded_reward: new_compound_reward_amount,
             current_farm_amount: new_farm_contribution.clone(),
+            original_entering_epoch: farm_attributes.original_entering_epoch,
+            apr_tier,
+            tier_reward_per_share: self.get_tier_reward_per_share(apr_tier),
         };
 
         self.burn_farm_tokens(&farm_token_id, payment_token_nonce, &payment_amount);
@@ -479,6 +591,155 @@ pub trait Farm:
         new_farm_token.payment
     }
 
+    /// Compounds the rewards of a position created through the proxy.
+    /// The first payment is the farm token. It can be followed by staking tokens,
+    /// which are added to the position together with the rewards.
+    /// The new farming amount is the value of the LP position, without the compounded rewards.
+    #[payable("*")]
+    #[endpoint(compoundRewardsThroughProxy)]
+    fn compound_rewards_through_proxy(
+        &self,
+        new_farming_amount: BigUint,
+    ) -> CompoundRewardsResultType<Self::Api> {
+        require!(self.is_active(), "Not active");
+        require!(!self.farm_token().is_empty(), "No farm token");
+
+        let caller = self.blockchain().get_caller();
+        self.require_whitelisted(&caller);
+
+        let payments = self.call_value().all_esdt_transfers();
+        require!(
+            payments.len() == 1 || payments.len() == 2,
+            "Invalid payments amount"
+        );
+
+        let farm_token_payment = payments.get(0);
+        let farm_token_id = self.farm_token().get_token_id();
+        require!(
+            farm_token_payment.token_identifier == farm_token_id,
+            "Unknown farm token"
+        );
+        require!(farm_token_payment.amount > 0u32, "Zero amount");
+
+        let farming_token = self.farming_token_id().get();
+        let reward_token = self.reward_token_id().get();
+        require!(
+            farming_token == reward_token,
+            "Farming token differ from reward token"
+        );
+
+        let staking_token_amount = if payments.len() == 2 {
+            let staking_token_payment = payments.get(1);
+            require!(
+                staking_token_payment.token_identifier == farming_token,
+                "Invalid second payment"
+            );
+
+            staking_token_payment.amount
+        } else {
+            BigUint::zero()
+        };
+
+        self.generate_aggregated_rewards();
+
+        let current_rps = self.reward_per_share().get();
+        let farm_attributes = self.get_attributes::<StakingFarmTokenAttributes<Self::Api>>(
+            &farm_token_id,
+            farm_token_payment.token_nonce,
+        );
+        let mut reward = self.calculate_reward(
+            &farm_token_payment.amount,
+            &current_rps,
+            &farm_attributes.reward_per_share,
+        );
+        reward += self.allocate_tier_bonus(&farm_token_payment.amount, &farm_attributes, &reward);
+
+        let new_compound_reward_amount = self.rule_of_three(
+            &farm_token_payment.amount,
+            &farm_attributes.current_farm_amount,
+            &farm_attributes.compounded_reward,
+        ) + reward
+            + staking_token_amount;
+        let new_farm_contribution = new_farming_amount + &new_compound_reward_amount;
+
+        let apr_tier = self.get_apr_tier(farm_attributes.original_entering_epoch);
+        let new_attributes = StakingFarmTokenAttributes {
+            reward_per_share: current_rps,
+            compounded_reward: new_compound_reward_amount,
+            current_farm_amount: new_farm_contribution.clone(),
+            original_entering_epoch: farm_attributes.original_entering_epoch,
+            apr_tier,
+            tier_reward_per_share: self.get_tier_reward_per_share(apr_tier),
+        };
+
+        self.burn_farm_tokens(
+            &farm_token_id,
+            farm_token_payment.token_nonce,
+            &farm_token_payment.amount,
+        );
+        let new_tokens =
+            self.mint_farm_tokens(farm_token_id, new_farm_contribution, &new_attributes);
+        self.send().direct_esdt(
+            &caller,
+            &new_tokens.token_identifier,
+            new_tokens.token_nonce,
+            &new_tokens.amount,
+        );
+
+        new_tokens
+    }
+
+    /// Allows users to withdraw their staked tokens while the farm is paused,
+    /// without going through the unbond period.
+    /// The compounded rewards are returned with the stake, only the pending rewards are forfeited.
+    #[payable("*")]
+    #[endpoint(emergencyExit)]
+    fn emergency_exit(&self) -> EsdtTokenPayment<Self::Api> {
+        require!(self.state().get() == State::Inactive, "Not paused");
+        require!(!self.farm_token().is_empty(), "No farm token");
+
+        let (payment_token_id, token_nonce, payment_amount) =
+            self.call_value().single_esdt().into_tuple();
+        let farm_token_id = self.farm_token().get_token_id();
+        require!(payment_token_id == farm_token_id, "Bad input token");
+        require!(payment_amount > 0u32, "Payment amount cannot be zero");
+
+        let farm_attributes = self.get_attributes::<StakingFarmTokenAttributes<Self::Api>>(
+            &payment_token_id,
+            token_nonce,
+        );
+        self.generate_aggregated_rewards();
+
+        let farming_token_amount = payment_amount.clone();
+        self.burn_farm_tokens(&payment_token_id, token_nonce, &payment_amount);
+
+        let caller = self.blockchain().get_caller();
+        let farming_token_id = self.farming_token_id().get();
+        self.send_tokens_non_zero(&caller, &farming_token_id, 0, &farming_token_amount);
+
+        let epoch = self.blockchain().get_block_epoch();
+        self.emergency_exit_event(
+            &caller,
+            &farm_token_id,
+            epoch,
+            &EmergencyExitEvent {
+                caller: caller.clone(),
+                farming_token_id: farming_token_id.clone(),
+                farming_token_amount: farming_token_amount.clone(),
+                farm_token_id: farm_token_id.clone(),
+                farm_token_nonce: token_nonce,
+                farm_token_amount: payment_amount,
+                farm_supply: self.farm_token_supply().get(),
+                farm_attributes,
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        );
+
+        EsdtTokenPayment::new(farming_token_id, 0, farming_token_amount)
+    }
+
     fn create_farm_tokens_by_merging(
         &self,
         farm_token_id: TokenIdentifier,
@@ -531,17 +792,37 @@ pub trait Farm:
 
         let last_reward_nonce = self.last_reward_block_nonce().get();
         let current_block_nonce = self.blockchain().get_block_nonce();
-        let reward_increase =
-            self.calculate_per_block_rewards(current_block_nonce, last_reward_nonce);
-        let reward_per_share_increase =
-            self.calculate_reward_per_share_increase(&reward_increase, &farm_token_supply);
+        let level_rewards = self.calculate_level_rewards(current_block_nonce, last_reward_nonce);
+
+        let mut future_reward_per_share = self.reward_per_share().get();
+        let mut future_tier_reward_per_share = self.tier_reward_per_share().get();
+        if let Some(reward_increase) = level_rewards.try_get(0) {
+            let tier_rewards = level_rewards
+                .slice(1, level_rewards.len())
+                .unwrap_or_default();
+            future_tier_reward_per_share = self.increase_tier_reward_per_share(
+                &future_reward_per_share,
+                &future_tier_reward_per_share,
+                &tier_rewards,
+                &farm_token_supply,
+            );
+            future_reward_per_share +=
+                self.calculate_reward_per_share_increase(&reward_increase, &farm_token_supply);
+        }
 
-        let future_reward_per_share = self.reward_per_share().get() + reward_per_share_increase;
-
-        self.calculate_reward(
+        let reward = self.calculate_reward(
             &amount,
             &future_reward_per_share,
             &attributes.reward_per_share,
-        )
+        );
+        let tier_bonus = self.calculate_tier_bonus(
+            &amount,
+            &attributes,
+            &reward,
+            &future_reward_per_share,
+            &future_tier_reward_per_share,
+        );
+
+        reward + tier_bonus
     }
 }
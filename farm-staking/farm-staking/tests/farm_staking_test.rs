@@ -1,5 +1,5 @@
 use elrond_wasm::storage::mappers::StorageTokenWrapper;
-use elrond_wasm::types::{Address, EsdtLocalRole};
+use elrond_wasm::types::{Address, EsdtLocalRole, MultiValueEncoded};
 use elrond_wasm_debug::tx_mock::{TxContextStack, TxInputESDT};
 use elrond_wasm_debug::{
     managed_biguint, managed_token_id, rust_biguint, testing_framework::*, DebugApi,
@@ -29,7 +29,6 @@ const TOTAL_REWARDS_AMOUNT: u64 = 1_000_000_000_000;
 
 const USER_TOTAL_RIDE_TOKENS: u64 = 5_000_000_000;
 
-#[allow(dead_code)] // owner_address is unused, at least for now
 struct FarmSetup<FarmObjBuilder>
 where
     FarmObjBuilder: 'static + Copy + Fn() -> farm_staking::ContractObj<DebugApi>,
@@ -135,6 +134,7 @@ fn stake_farm<FarmObjBuilder>(
     expected_farm_token_nonce: u64,
     expected_reward_per_share: u64,
     expected_compounded_reward: u64,
+    expected_original_entering_epoch: u64,
 ) where
     FarmObjBuilder: 'static + Copy + Fn() -> farm_staking::ContractObj<DebugApi>,
 {
@@ -171,6 +171,9 @@ fn stake_farm<FarmObjBuilder>(
         reward_per_share: managed_biguint!(expected_reward_per_share),
         compounded_reward: managed_biguint!(expected_compounded_reward),
         current_farm_amount: managed_biguint!(expected_total_out_amount),
+        original_entering_epoch: expected_original_entering_epoch,
+        apr_tier: 0,
+        tier_reward_per_share: managed_biguint!(expected_reward_per_share),
     };
     b_mock.check_nft_balance(
         &farm_setup.user_address,
@@ -294,6 +297,7 @@ fn claim_rewards<FarmObjBuilder>(
     expected_user_farming_token_balance: &RustBigUint,
     expected_farm_token_nonce_out: u64,
     expected_reward_per_share: u64,
+    expected_original_entering_epoch: u64,
 ) where
     FarmObjBuilder: 'static + Copy + Fn() -> farm_staking::ContractObj<DebugApi>,
 {
@@ -334,6 +338,9 @@ fn claim_rewards<FarmObjBuilder>(
         reward_per_share: managed_biguint!(expected_reward_per_share),
         compounded_reward: managed_biguint!(0),
         current_farm_amount: managed_biguint!(farm_token_amount),
+        original_entering_epoch: expected_original_entering_epoch,
+        apr_tier: 0,
+        tier_reward_per_share: managed_biguint!(expected_reward_per_share),
     };
 
     b_mock.check_nft_balance(
@@ -407,10 +414,55 @@ fn test_enter_farm() {
         expected_farm_token_nonce,
         0,
         0,
+        0,
     );
     check_farm_token_supply(&mut farm_setup, farm_in_amount);
 }
 
+#[test]
+fn test_reward_rate_views() {
+    let mut farm_setup = setup_farm(farm_staking::contract_obj);
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            assert_eq!(
+                sc.get_reward_per_block_per_farming_token(),
+                managed_biguint!(0)
+            );
+            assert_eq!(
+                sc.get_annual_reward_per_farming_token(),
+                managed_biguint!(0)
+            );
+        })
+        .assert_ok();
+
+    let farm_in_amount = 100_000_000;
+    stake_farm(&mut farm_setup, farm_in_amount, &[], 1, 0, 0, 0);
+
+    // the per block rewards are bounded by the max APR, ~= 4 per block
+    let per_block_rewards = core::cmp::min(
+        PER_BLOCK_REWARD_AMOUNT,
+        farm_in_amount * MAX_APR / MAX_PERCENT / BLOCKS_IN_YEAR,
+    );
+    assert_eq!(per_block_rewards, 4);
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            let expected_per_block = per_block_rewards * DIVISION_SAFETY_CONSTANT / farm_in_amount;
+            assert_eq!(
+                sc.get_reward_per_block_per_farming_token(),
+                managed_biguint!(expected_per_block)
+            );
+            assert_eq!(
+                sc.get_annual_reward_per_farming_token(),
+                managed_biguint!(expected_per_block * BLOCKS_IN_YEAR)
+            );
+        })
+        .assert_ok();
+}
+
 #[test]
 fn test_unstake_farm() {
     let mut farm_setup = setup_farm(farm_staking::contract_obj);
@@ -424,6 +476,7 @@ fn test_unstake_farm() {
         expected_farm_token_nonce,
         0,
         0,
+        0,
     );
     check_farm_token_supply(&mut farm_setup, farm_in_amount);
 
@@ -472,6 +525,7 @@ fn test_claim_rewards() {
         expected_farm_token_nonce,
         0,
         0,
+        0,
     );
     check_farm_token_supply(&mut farm_setup, farm_in_amount);
 
@@ -492,6 +546,7 @@ fn test_claim_rewards() {
         &expected_farming_token_balance,
         expected_farm_token_nonce + 1,
         expected_reward_per_share,
+        0,
     );
     check_farm_token_supply(&mut farm_setup, farm_in_amount);
 }
@@ -511,6 +566,7 @@ where
         expected_farm_token_nonce,
         0,
         0,
+        0,
     );
     check_farm_token_supply(&mut farm_setup, farm_in_amount);
 
@@ -540,6 +596,7 @@ where
         expected_farm_token_nonce + 1,
         expected_reward_per_share,
         0,
+        4, // weighted average of epochs 0 and 5, rounded up
     );
     check_farm_token_supply(&mut farm_setup, total_amount);
 
@@ -595,6 +652,7 @@ fn test_unbond() {
         expected_farm_token_nonce,
         0,
         0,
+        0,
     );
     check_farm_token_supply(&mut farm_setup, farm_in_amount);
 
@@ -639,3 +697,561 @@ fn test_unbond() {
         USER_TOTAL_RIDE_TOKENS + expected_rewards,
     );
 }
+
+#[test]
+fn test_cancel_unbond_and_partial_unbond() {
+    let mut farm_setup = setup_farm(farm_staking::contract_obj);
+
+    let farm_in_amount = 100_000_000;
+    let expected_farm_token_nonce = 1;
+    stake_farm(
+        &mut farm_setup,
+        farm_in_amount,
+        &[],
+        expected_farm_token_nonce,
+        0,
+        0,
+        0,
+    );
+
+    let current_block = 10;
+    let current_epoch = 5;
+    set_block_epoch(&mut farm_setup, current_epoch);
+    set_block_nonce(&mut farm_setup, current_block);
+
+    // same rewards as in test_unbond
+    let expected_rewards = 40;
+    let expected_ride_token_balance =
+        rust_biguint!(USER_TOTAL_RIDE_TOKENS) - farm_in_amount + expected_rewards;
+    let unbond_token_nonce = expected_farm_token_nonce + 1;
+    unstake_farm(
+        &mut farm_setup,
+        farm_in_amount,
+        expected_farm_token_nonce,
+        expected_rewards,
+        &expected_ride_token_balance,
+        &expected_ride_token_balance,
+        unbond_token_nonce,
+        farm_in_amount,
+        &UnbondSftAttributes {
+            unlock_epoch: current_epoch + MIN_UNBOND_EPOCHS,
+        },
+    );
+    check_farm_token_supply(&mut farm_setup, 0);
+
+    // cancel the unbond for part of the tokens
+    let restaked_amount = 40_000_000;
+    let restaked_farm_token_nonce = unbond_token_nonce + 1;
+    let expected_reward_per_share = 400_000;
+    farm_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &farm_setup.user_address,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            unbond_token_nonce,
+            &rust_biguint!(restaked_amount),
+            |sc| {
+                let payment = sc.cancel_unbond();
+                assert_eq!(payment.token_identifier, managed_token_id!(FARM_TOKEN_ID));
+                assert_eq!(payment.token_nonce, restaked_farm_token_nonce);
+                assert_eq!(payment.amount, managed_biguint!(restaked_amount));
+            },
+        )
+        .assert_ok();
+
+    let _ = DebugApi::dummy();
+    farm_setup.blockchain_wrapper.check_nft_balance(
+        &farm_setup.user_address,
+        FARM_TOKEN_ID,
+        restaked_farm_token_nonce,
+        &rust_biguint!(restaked_amount),
+        Some(&StakingFarmTokenAttributes::<DebugApi> {
+            reward_per_share: managed_biguint!(expected_reward_per_share),
+            compounded_reward: managed_biguint!(0),
+            current_farm_amount: managed_biguint!(restaked_amount),
+            original_entering_epoch: current_epoch,
+            apr_tier: 0,
+            tier_reward_per_share: managed_biguint!(expected_reward_per_share),
+        }),
+    );
+    let _ = TxContextStack::static_pop();
+    check_farm_token_supply(&mut farm_setup, restaked_amount);
+
+    // unstake again later, to get a second unbond nonce
+    let second_unstake_epoch = current_epoch + 1;
+    set_block_epoch(&mut farm_setup, second_unstake_epoch);
+    let second_unbond_amount = 10_000_000;
+    let second_unbond_token_nonce = restaked_farm_token_nonce + 1;
+    unstake_farm(
+        &mut farm_setup,
+        second_unbond_amount,
+        restaked_farm_token_nonce,
+        0,
+        &expected_ride_token_balance,
+        &expected_ride_token_balance,
+        second_unbond_token_nonce,
+        second_unbond_amount,
+        &UnbondSftAttributes {
+            unlock_epoch: second_unstake_epoch + MIN_UNBOND_EPOCHS,
+        },
+    );
+    check_farm_token_supply(&mut farm_setup, restaked_amount - second_unbond_amount);
+
+    // the second unbond period is not over yet
+    set_block_epoch(&mut farm_setup, current_epoch + MIN_UNBOND_EPOCHS);
+    let first_unbond_amount = 30_000_000;
+    let payments = [
+        TxInputESDT {
+            token_identifier: FARM_TOKEN_ID.to_vec(),
+            nonce: unbond_token_nonce,
+            value: rust_biguint!(first_unbond_amount),
+        },
+        TxInputESDT {
+            token_identifier: FARM_TOKEN_ID.to_vec(),
+            nonce: second_unbond_token_nonce,
+            value: rust_biguint!(second_unbond_amount),
+        },
+    ];
+    farm_setup
+        .blockchain_wrapper
+        .execute_esdt_multi_transfer(
+            &farm_setup.user_address,
+            &farm_setup.farm_wrapper,
+            &payments,
+            |sc| {
+                let _ = sc.unbond_farm();
+            },
+        )
+        .assert_user_error("Unbond period not over");
+
+    set_block_epoch(&mut farm_setup, second_unstake_epoch + MIN_UNBOND_EPOCHS);
+    let total_unbond_amount = first_unbond_amount + second_unbond_amount;
+    farm_setup
+        .blockchain_wrapper
+        .execute_esdt_multi_transfer(
+            &farm_setup.user_address,
+            &farm_setup.farm_wrapper,
+            &payments,
+            |sc| {
+                let payment = sc.unbond_farm();
+                assert_eq!(
+                    payment.token_identifier,
+                    managed_token_id!(FARMING_TOKEN_ID)
+                );
+                assert_eq!(payment.amount, managed_biguint!(total_unbond_amount));
+            },
+        )
+        .assert_ok();
+
+    farm_setup.blockchain_wrapper.check_esdt_balance(
+        &farm_setup.user_address,
+        FARMING_TOKEN_ID,
+        &(expected_ride_token_balance + total_unbond_amount),
+    );
+    farm_setup
+        .blockchain_wrapper
+        .check_nft_balance::<UnbondSftAttributes>(
+            &farm_setup.user_address,
+            FARM_TOKEN_ID,
+            unbond_token_nonce,
+            &rust_biguint!(farm_in_amount - restaked_amount - first_unbond_amount),
+            None,
+        );
+}
+
+#[test]
+fn test_apr_tiers() {
+    let mut farm_setup = setup_farm(farm_staking::contract_obj);
+
+    // +25% APR after 30 epochs, +50% APR after 90 epochs
+    let owner_address = farm_setup.owner_address.clone();
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut tiers = MultiValueEncoded::new();
+                tiers.push((90u64, managed_biguint!(5_000)).into());
+                tiers.push((30u64, managed_biguint!(2_500)).into());
+                sc.set_apr_tiers(tiers);
+            },
+        )
+        .assert_user_error("Tiers not in order");
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut tiers = MultiValueEncoded::new();
+                tiers.push((30u64, managed_biguint!(2_500)).into());
+                tiers.push((90u64, managed_biguint!(5_000)).into());
+                sc.set_apr_tiers(tiers);
+            },
+        )
+        .assert_ok();
+
+    let farm_in_amount = 100_000_000;
+    let expected_farm_token_nonce = 1;
+    stake_farm(
+        &mut farm_setup,
+        farm_in_amount,
+        &[],
+        expected_farm_token_nonce,
+        0,
+        0,
+        0,
+    );
+
+    set_block_epoch(&mut farm_setup, 29);
+    farm_setup
+        .blockchain_wrapper
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            assert_eq!(sc.get_max_apr_for_position(0), managed_biguint!(MAX_APR));
+        })
+        .assert_ok();
+
+    set_block_epoch(&mut farm_setup, 30);
+    set_block_nonce(&mut farm_setup, 10);
+    farm_setup
+        .blockchain_wrapper
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            assert_eq!(
+                sc.get_max_apr_for_position(0),
+                managed_biguint!(MAX_APR + 2_500)
+            );
+        })
+        .assert_ok();
+
+    // the checkpoint of the epoch in which the position reaches the first tier
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_per_block_rewards(managed_biguint!(PER_BLOCK_REWARD_AMOUNT));
+            },
+        )
+        .assert_ok();
+
+    // per block, the base APR cap allows 4 tokens and the first tier's cap allows 9
+    set_block_epoch(&mut farm_setup, 40);
+    set_block_nonce(&mut farm_setup, 20);
+
+    // 40 for the blocks before the tier epoch, 90 for the ones after it
+    let expected_reward_token_out = 40 + 90;
+    claim_rewards_in_tier(
+        &mut farm_setup,
+        farm_in_amount,
+        expected_farm_token_nonce,
+        expected_reward_token_out,
+        expected_farm_token_nonce + 1,
+        800_000,
+        1,
+        1_800_000,
+    );
+    farm_setup
+        .blockchain_wrapper
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            assert_eq!(
+                sc.accumulated_rewards().get(),
+                managed_biguint!(expected_reward_token_out)
+            );
+        })
+        .assert_ok();
+
+    // below the base APR cap, the tiers do not give more rewards
+    farm_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_per_block_rewards(managed_biguint!(3));
+            },
+        )
+        .assert_ok();
+
+    set_block_epoch(&mut farm_setup, 50);
+    set_block_nonce(&mut farm_setup, 30);
+
+    claim_rewards_in_tier(
+        &mut farm_setup,
+        farm_in_amount,
+        expected_farm_token_nonce + 1,
+        30,
+        expected_farm_token_nonce + 2,
+        1_100_000,
+        1,
+        2_100_000,
+    );
+    farm_setup
+        .blockchain_wrapper
+        .execute_query(&farm_setup.farm_wrapper, |sc| {
+            assert_eq!(
+                sc.accumulated_rewards().get(),
+                managed_biguint!(expected_reward_token_out + 30)
+            );
+        })
+        .assert_ok();
+}
+
+fn claim_rewards_in_tier<FarmObjBuilder>(
+    farm_setup: &mut FarmSetup<FarmObjBuilder>,
+    farm_token_amount: u64,
+    farm_token_nonce: u64,
+    expected_reward_token_out: u64,
+    expected_farm_token_nonce_out: u64,
+    expected_reward_per_share: u64,
+    expected_apr_tier: usize,
+    expected_tier_reward_per_share: u64,
+) where
+    FarmObjBuilder: 'static + Copy + Fn() -> farm_staking::ContractObj<DebugApi>,
+{
+    let b_mock = &mut farm_setup.blockchain_wrapper;
+    b_mock
+        .execute_esdt_transfer(
+            &farm_setup.user_address,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            farm_token_nonce,
+            &rust_biguint!(farm_token_amount),
+            |sc| {
+                let (_, reward_payment) = sc.claim_rewards().into_tuple();
+                assert_eq!(
+                    reward_payment.amount,
+                    managed_biguint!(expected_reward_token_out)
+                );
+            },
+        )
+        .assert_ok();
+
+    let _ = DebugApi::dummy();
+    b_mock.check_nft_balance(
+        &farm_setup.user_address,
+        FARM_TOKEN_ID,
+        expected_farm_token_nonce_out,
+        &rust_biguint!(farm_token_amount),
+        Some(&StakingFarmTokenAttributes::<DebugApi> {
+            reward_per_share: managed_biguint!(expected_reward_per_share),
+            compounded_reward: managed_biguint!(0),
+            current_farm_amount: managed_biguint!(farm_token_amount),
+            original_entering_epoch: 0,
+            apr_tier: expected_apr_tier,
+            tier_reward_per_share: managed_biguint!(expected_tier_reward_per_share),
+        }),
+    );
+    let _ = TxContextStack::static_pop();
+}
+
+#[test]
+fn test_emergency_exit() {
+    let mut farm_setup = setup_farm(farm_staking::contract_obj);
+
+    let farm_in_amount = 100_000_000;
+    let expected_farm_token_nonce = 1;
+    stake_farm(
+        &mut farm_setup,
+        farm_in_amount,
+        &[],
+        expected_farm_token_nonce,
+        0,
+        0,
+        0,
+    );
+
+    set_block_epoch(&mut farm_setup, 5);
+    set_block_nonce(&mut farm_setup, 10);
+
+    // value taken from the "test_claim_rewards" test
+    let expected_compounded_reward = 40;
+    let position_amount = farm_in_amount + expected_compounded_reward;
+    let b_mock = &mut farm_setup.blockchain_wrapper;
+    b_mock
+        .execute_esdt_transfer(
+            &farm_setup.user_address,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            expected_farm_token_nonce,
+            &rust_biguint!(farm_in_amount),
+            |sc| {
+                let payment = sc.compound_rewards();
+                assert_eq!(payment.token_nonce, expected_farm_token_nonce + 1);
+                assert_eq!(payment.amount, managed_biguint!(position_amount));
+            },
+        )
+        .assert_ok();
+
+    set_block_nonce(&mut farm_setup, 20);
+
+    let b_mock = &mut farm_setup.blockchain_wrapper;
+    b_mock
+        .execute_esdt_transfer(
+            &farm_setup.user_address,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            expected_farm_token_nonce + 1,
+            &rust_biguint!(position_amount),
+            |sc| {
+                let _ = sc.emergency_exit();
+            },
+        )
+        .assert_user_error("Not paused");
+
+    b_mock
+        .execute_tx(
+            &farm_setup.owner_address,
+            &farm_setup.farm_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.pause();
+            },
+        )
+        .assert_ok();
+
+    // the compounded rewards are returned with the stake, the pending rewards are forfeited
+    b_mock
+        .execute_esdt_transfer(
+            &farm_setup.user_address,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            expected_farm_token_nonce + 1,
+            &rust_biguint!(position_amount),
+            |sc| {
+                let payment = sc.emergency_exit();
+                assert_eq!(
+                    payment.token_identifier,
+                    managed_token_id!(FARMING_TOKEN_ID)
+                );
+                assert_eq!(payment.amount, managed_biguint!(position_amount));
+            },
+        )
+        .assert_ok();
+
+    b_mock.check_esdt_balance(
+        &farm_setup.user_address,
+        FARMING_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_RIDE_TOKENS + expected_compounded_reward),
+    );
+    check_farm_token_supply(&mut farm_setup, 0);
+}
+
+#[test]
+fn test_split_farm_token() {
+    use farm_staking::farm_token_merge::FarmTokenMergeModule;
+
+    let mut farm_setup = setup_farm(farm_staking::contract_obj);
+
+    let original_entering_epoch = 3;
+    set_block_epoch(&mut farm_setup, original_entering_epoch);
+
+    let farm_in_amount = 100_000_000;
+    stake_farm(
+        &mut farm_setup,
+        farm_in_amount,
+        &[],
+        1,
+        0,
+        0,
+        original_entering_epoch,
+    );
+
+    set_block_epoch(&mut farm_setup, 5);
+    set_block_nonce(&mut farm_setup, 10);
+
+    // value taken from the "test_claim_rewards" test
+    let compounded_reward = 40;
+    let position_amount = farm_in_amount + compounded_reward;
+    let expected_reward_per_share = 400_000;
+    let user = farm_setup.user_address.clone();
+    farm_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &user,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            1,
+            &rust_biguint!(farm_in_amount),
+            |sc| {
+                let payment = sc.compound_rewards();
+                assert_eq!(payment.token_nonce, 2);
+                assert_eq!(payment.amount, managed_biguint!(position_amount));
+            },
+        )
+        .assert_ok();
+
+    farm_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &user,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            2,
+            &rust_biguint!(position_amount),
+            |sc| {
+                let mut amounts = MultiValueEncoded::new();
+                amounts.push(managed_biguint!(33_333_347));
+                amounts.push(managed_biguint!(33_333_347));
+                amounts.push(managed_biguint!(33_333_346));
+
+                let new_tokens = sc.split_farm_token(amounts).to_vec();
+                assert_eq!(new_tokens.len(), 3);
+                assert_eq!(new_tokens.get(0).token_nonce, 3);
+                assert_eq!(new_tokens.get(2).token_nonce, 5);
+            },
+        )
+        .assert_ok();
+
+    // the farm token supply is not changed by splitting
+    check_farm_token_supply(&mut farm_setup, position_amount);
+
+    // 40 * 33_333_347 / 100_000_040 rounds down to 13, the last position gets the remainder
+    let _ = DebugApi::dummy();
+    for (nonce, amount, compounded_reward) in [
+        (3u64, 33_333_347u64, 13u64),
+        (4, 33_333_347, 13),
+        (5, 33_333_346, 14),
+    ] {
+        farm_setup.blockchain_wrapper.check_nft_balance(
+            &user,
+            FARM_TOKEN_ID,
+            nonce,
+            &rust_biguint!(amount),
+            Some(&StakingFarmTokenAttributes::<DebugApi> {
+                reward_per_share: managed_biguint!(expected_reward_per_share),
+                compounded_reward: managed_biguint!(compounded_reward),
+                current_farm_amount: managed_biguint!(amount),
+                original_entering_epoch,
+                apr_tier: 0,
+                tier_reward_per_share: managed_biguint!(expected_reward_per_share),
+            }),
+        );
+    }
+    let _ = TxContextStack::static_pop();
+
+    // the amounts must add up to the payment
+    farm_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &user,
+            &farm_setup.farm_wrapper,
+            FARM_TOKEN_ID,
+            3,
+            &rust_biguint!(33_333_347),
+            |sc| {
+                let mut amounts = MultiValueEncoded::new();
+                amounts.push(managed_biguint!(1));
+                amounts.push(managed_biguint!(1));
+
+                let _ = sc.split_farm_token(amounts);
+            },
+        )
+        .assert_user_error("Bad split amounts");
+}
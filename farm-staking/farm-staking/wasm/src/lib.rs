@@ -11,12 +11,17 @@ elrond_wasm_node::wasm_endpoints! {
         addAddressToWhitelist
         addToPauseWhitelist
         calculateRewardsForGivenPosition
+        cancelUnbond
         claimRewards
         claimRewardsWithNewValue
         compoundRewards
+        compoundRewardsThroughProxy
+        emergencyExit
         end_produce_rewards
         getAccumulatedRewards
         getAnnualPercentageRewards
+        getAnnualRewardPerFarmingToken
+        getAprTiers
         getBurnGasLimit
         getDivisionSafetyConstant
         getFarmTokenId
@@ -24,15 +29,19 @@ elrond_wasm_node::wasm_endpoints! {
         getFarmingTokenId
         getLastRewardBlockNonce
         getLockedAssetFactoryManagedAddress
+        getMaxAprForPosition
         getMinUnbondEpochs
         getMinimumFarmingEpoch
         getPairContractManagedAddress
         getPenaltyPercent
         getPerBlockRewardAmount
         getRewardCapacity
+        getRewardPerBlockPerFarmingToken
         getRewardPerShare
         getRewardTokenId
+        getStakingDurationStartEpoch
         getState
+        getTierRewardPerShare
         isWhitelisted
         mergeFarmTokens
         pause
@@ -40,12 +49,14 @@ elrond_wasm_node::wasm_endpoints! {
         removeAddressFromWhitelist
         removeFromPauseWhitelist
         resume
+        setAprTiers
         setMaxApr
         setMinUnbondEpochs
         setPerBlockRewardAmount
         set_burn_gas_limit
         set_minimum_farming_epochs
         set_penalty_percent
+        splitFarmToken
         stakeFarm
         stakeFarmThroughProxy
         startProduceRewards
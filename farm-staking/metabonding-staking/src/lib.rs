@@ -4,6 +4,8 @@ elrond_wasm::imports!();
 
 pub mod events;
 pub mod locked_asset_token;
+pub mod stake_checkpoints;
+pub mod weighted_stake;
 
 use locked_asset_token::UserEntry;
 
@@ -12,7 +14,12 @@ pub const UNBOND_EPOCHS: u64 = 3;
 
 #[elrond_wasm::contract]
 pub trait MetabondingStaking:
-    locked_asset_token::LockedAssetTokenModule + events::EventsModule
+    locked_asset_token::LockedAssetTokenModule
+    + stake_checkpoints::StakeCheckpointsModule
+    + weighted_stake::WeightedStakeModule
+    + factory::attr_ex_helper::AttrExHelper
+    + factory::lock_time_weight::LockTimeWeightModule
+    + events::EventsModule
 {
     #[init]
     fn init(
@@ -23,6 +30,7 @@ pub trait MetabondingStaking:
         self.locked_asset_token_id().set(&locked_asset_token_id);
         self.locked_asset_factory_address()
             .set(&locked_asset_factory_address);
+        self.sync_extended_attributes_activation_nonce();
     }
 
     #[payable("*")]
@@ -33,14 +41,17 @@ pub trait MetabondingStaking:
 
         let caller = self.blockchain().get_caller();
         let entry_mapper = self.entry_for_user(&caller);
+        let prev_stake_amount = self.get_staked_amount_for_user(caller.clone());
         let new_entry = self.create_new_entry_by_merging_tokens(&entry_mapper, payments);
 
         self.total_locked_asset_supply()
             .update(|total_supply| *total_supply += new_entry.get_total_amount());
 
+        self.update_stake_checkpoint(&caller, &prev_stake_amount, &new_entry.stake_amount);
         self.stake_event(&caller, &new_entry);
 
         entry_mapper.set(&new_entry);
+        self.update_weighted_stake(&caller);
         let _ = self.user_list().insert(caller);
     }
 
@@ -56,14 +67,17 @@ pub trait MetabondingStaking:
             "Trying to unstake too much"
         );
 
+        let prev_stake_amount = user_entry.stake_amount.clone();
         let current_epoch = self.blockchain().get_block_epoch();
         user_entry.unbond_epoch = current_epoch + UNBOND_EPOCHS;
         user_entry.stake_amount -= &amount;
         user_entry.unstake_amount += amount;
 
+        self.update_stake_checkpoint(&caller, &prev_stake_amount, &user_entry.stake_amount);
         self.unstake_event(&caller, &user_entry);
 
         entry_mapper.set(&user_entry);
+        self.update_weighted_stake(&caller);
     }
 
     #[endpoint]
@@ -85,6 +99,9 @@ pub trait MetabondingStaking:
         self.total_locked_asset_supply()
             .update(|total_supply| *total_supply -= &unstake_amount);
 
+        // the stake amount does not change, but legacy users get their first checkpoint
+        self.update_stake_checkpoint(&caller, &user_entry.stake_amount, &user_entry.stake_amount);
+
         let opt_entry_after_action = if user_entry.stake_amount == 0 {
             entry_mapper.clear();
             self.user_list().swap_remove(&caller);
@@ -107,6 +124,7 @@ pub trait MetabondingStaking:
         );
 
         self.unbond_event(&caller, opt_entry_after_action);
+        self.update_weighted_stake(&caller);
     }
 
     #[view(getStakedAmountForUser)]
@@ -0,0 +1,161 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use crate::locked_asset_token::UserEntry;
+use crate::SnapshotEntry;
+
+/// Checkpoints created for the users who staked before the checkpoints existed
+/// use this epoch, as their stake is not known before the migration.
+pub const LEGACY_CHECKPOINT_EPOCH: u64 = 0;
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Debug)]
+pub struct StakeCheckpoint<M: ManagedTypeApi> {
+    pub epoch: u64,
+    pub stake_amount: BigUint<M>,
+}
+
+#[elrond_wasm::module]
+pub trait StakeCheckpointsModule: crate::locked_asset_token::LockedAssetTokenModule {
+    /// Adds the users who staked before the checkpoints existed to the epoch snapshots.
+    /// Their current stake is recorded as their stake since the legacy checkpoint epoch.
+    #[only_owner]
+    #[endpoint(addLegacyCheckpointUsers)]
+    fn add_legacy_checkpoint_users(&self, users: MultiValueEncoded<ManagedAddress>) {
+        for user_address in users {
+            require!(
+                self.user_list().contains(&user_address),
+                "User has no stake"
+            );
+
+            let checkpoints_mapper = self.stake_checkpoints(&user_address);
+            if !checkpoints_mapper.is_empty() {
+                continue;
+            }
+
+            let entry: UserEntry<Self::Api> = self.entry_for_user(&user_address).get();
+            self.push_legacy_checkpoint(&user_address, entry.stake_amount);
+        }
+    }
+
+    /// Records the user's stake for the current epoch.
+    /// `prev_stake_amount` is used to create the legacy checkpoint
+    /// of users who staked before the checkpoints existed.
+    fn update_stake_checkpoint(
+        &self,
+        user_address: &ManagedAddress,
+        prev_stake_amount: &BigUint,
+        new_stake_amount: &BigUint,
+    ) {
+        let mut checkpoints_mapper = self.stake_checkpoints(user_address);
+        if checkpoints_mapper.is_empty() && prev_stake_amount > &0 {
+            self.push_legacy_checkpoint(user_address, prev_stake_amount.clone());
+        }
+
+        let current_epoch = self.blockchain().get_block_epoch();
+        let checkpoints_len = checkpoints_mapper.len();
+        if checkpoints_len > 0 {
+            let last_checkpoint = checkpoints_mapper.get(checkpoints_len);
+            if &last_checkpoint.stake_amount == new_stake_amount {
+                return;
+            }
+
+            if last_checkpoint.epoch == current_epoch {
+                checkpoints_mapper.set(
+                    checkpoints_len,
+                    &StakeCheckpoint {
+                        epoch: current_epoch,
+                        stake_amount: new_stake_amount.clone(),
+                    },
+                );
+                return;
+            }
+        } else {
+            let _ = self.checkpoint_users().insert(user_address.clone());
+        }
+
+        let _ = checkpoints_mapper.push(&StakeCheckpoint {
+            epoch: current_epoch,
+            stake_amount: new_stake_amount.clone(),
+        });
+    }
+
+    fn push_legacy_checkpoint(&self, user_address: &ManagedAddress, stake_amount: BigUint) {
+        let _ = self.checkpoint_users().insert(user_address.clone());
+        let _ = self.stake_checkpoints(user_address).push(&StakeCheckpoint {
+            epoch: LEGACY_CHECKPOINT_EPOCH,
+            stake_amount,
+        });
+    }
+
+    /// Returns the user's stake at the end of the given epoch.
+    #[view(getStakeAtEpoch)]
+    fn get_stake_at_epoch(&self, user_address: ManagedAddress, epoch: u64) -> BigUint {
+        let checkpoints_mapper = self.stake_checkpoints(&user_address);
+        if checkpoints_mapper.is_empty() {
+            // the stake of a legacy user did not change since the checkpoints exist
+            let entry_mapper = self.entry_for_user(&user_address);
+            return if entry_mapper.is_empty() {
+                BigUint::zero()
+            } else {
+                entry_mapper.get().stake_amount
+            };
+        }
+
+        // binary search for the last checkpoint with checkpoint.epoch <= epoch
+        let mut low = 1;
+        let mut high = checkpoints_mapper.len();
+        let mut found_index = 0;
+        while low <= high {
+            let mid = (low + high) / 2;
+            let checkpoint = checkpoints_mapper.get(mid);
+            if checkpoint.epoch <= epoch {
+                found_index = mid;
+                low = mid + 1;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        if found_index == 0 {
+            BigUint::zero()
+        } else {
+            checkpoints_mapper.get(found_index).stake_amount
+        }
+    }
+
+    /// Returns the users with a non-zero stake at the end of the given epoch.
+    /// `start` and `count` paginate over the users with checkpoints, in the order
+    /// they were added, which does not change between calls.
+    #[view(getSnapshotAtEpoch)]
+    fn get_snapshot_at_epoch(
+        &self,
+        epoch: u64,
+        start: usize,
+        count: usize,
+    ) -> MultiValueEncoded<SnapshotEntry<Self::Api>> {
+        let mut result = MultiValueEncoded::new();
+
+        let users_mapper = self.checkpoint_users();
+        let end = core::cmp::min(start.saturating_add(count), users_mapper.len());
+        for index in start..end {
+            let user_address = users_mapper.get_by_index(index + 1);
+            let stake_amount = self.get_stake_at_epoch(user_address.clone(), epoch);
+            if stake_amount > 0 {
+                result.push((user_address, stake_amount).into());
+            }
+        }
+
+        result
+    }
+
+    #[view(getStakeCheckpoints)]
+    #[storage_mapper("stakeCheckpoints")]
+    fn stake_checkpoints(
+        &self,
+        user_address: &ManagedAddress,
+    ) -> VecMapper<StakeCheckpoint<Self::Api>>;
+
+    #[view(getCheckpointUsers)]
+    #[storage_mapper("checkpointUsers")]
+    fn checkpoint_users(&self) -> UnorderedSetMapper<ManagedAddress>;
+}
@@ -0,0 +1,101 @@
+elrond_wasm::imports!();
+
+use common_structs::LockedAssetTokenAttributesEx;
+use factory::attr_ex_helper::ProxyTrait as _;
+
+use crate::locked_asset_token::UserEntry;
+use crate::SnapshotEntry;
+
+#[elrond_wasm::module]
+pub trait WeightedStakeModule:
+    crate::locked_asset_token::LockedAssetTokenModule
+    + factory::attr_ex_helper::AttrExHelper
+    + factory::lock_time_weight::LockTimeWeightModule
+{
+    /// Weights decay with time, so the stored values and the total are only
+    /// recomputed on user actions. Anyone can refresh them for the given users.
+    #[endpoint(refreshWeightedStake)]
+    fn refresh_weighted_stake(&self, users: MultiValueEncoded<ManagedAddress>) {
+        for user_address in users {
+            self.update_weighted_stake(&user_address);
+        }
+    }
+
+    fn update_weighted_stake(&self, user_address: &ManagedAddress) {
+        let new_weighted_stake = self.get_weighted_stake_for_user(user_address.clone());
+        let user_weight_mapper = self.weighted_stake_for_user(user_address);
+        let old_weighted_stake = user_weight_mapper.get();
+
+        self.total_weighted_stake().update(|total| {
+            *total -= &old_weighted_stake;
+            *total += &new_weighted_stake;
+        });
+
+        if new_weighted_stake == 0 {
+            user_weight_mapper.clear();
+        } else {
+            user_weight_mapper.set(&new_weighted_stake);
+        }
+    }
+
+    /// The staked amount, weighted by the remaining lock duration of each milestone.
+    #[view(getWeightedStakeForUser)]
+    fn get_weighted_stake_for_user(&self, user_address: ManagedAddress) -> BigUint {
+        let entry_mapper = self.entry_for_user(&user_address);
+        if entry_mapper.is_empty() {
+            return BigUint::zero();
+        }
+
+        let entry: UserEntry<Self::Api> = entry_mapper.get();
+        if entry.stake_amount == 0 {
+            return BigUint::zero();
+        }
+
+        let attributes = self.get_locked_asset_attributes(entry.token_nonce);
+        self.get_lock_time_weighted_amount(
+            &entry.stake_amount,
+            &attributes.unlock_schedule.unlock_milestones,
+        )
+    }
+
+    #[view(getWeightedSnapshot)]
+    fn get_weighted_snapshot(&self) -> MultiValueEncoded<SnapshotEntry<Self::Api>> {
+        let mut result = MultiValueEncoded::new();
+
+        for user_address in self.user_list().iter() {
+            let weighted_stake = self.get_weighted_stake_for_user(user_address.clone());
+            if weighted_stake > 0 {
+                result.push((user_address, weighted_stake).into());
+            }
+        }
+
+        result
+    }
+
+    /// Tokens created before the extended attributes were activated in the factory
+    /// have the legacy encoding, so the factory's activation nonce is copied locally.
+    fn sync_extended_attributes_activation_nonce(&self) {
+        let locked_asset_factory_address = self.locked_asset_factory_address().get();
+        let activation_nonce: u64 = self
+            .locked_asset_factory_proxy(locked_asset_factory_address)
+            .extended_attributes_activation_nonce()
+            .execute_on_dest_context();
+
+        self.extended_attributes_activation_nonce()
+            .set(activation_nonce);
+    }
+
+    fn get_locked_asset_attributes(
+        &self,
+        token_nonce: u64,
+    ) -> LockedAssetTokenAttributesEx<Self::Api> {
+        self.get_attributes_ex(&self.locked_asset_token_id().get(), token_nonce)
+    }
+
+    #[storage_mapper("weightedStakeForUser")]
+    fn weighted_stake_for_user(&self, user_address: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[view(getTotalWeightedStake)]
+    #[storage_mapper("totalWeightedStake")]
+    fn total_weighted_stake(&self) -> SingleValueMapper<BigUint>;
+}
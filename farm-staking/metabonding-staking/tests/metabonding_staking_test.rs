@@ -1,14 +1,26 @@
 pub mod metabonding_staking_setup;
+use elrond_wasm::types::MultiValueEncoded;
 use elrond_wasm_debug::{managed_address, managed_biguint, rust_biguint, tx_mock::TxInputESDT};
+use factory::attr_ex_helper::AttrExHelper;
 use metabonding_staking::{
     locked_asset_token::{LockedAssetTokenModule, UserEntry},
+    stake_checkpoints::StakeCheckpointsModule,
+    weighted_stake::WeightedStakeModule,
     UNBOND_EPOCHS,
 };
 use metabonding_staking_setup::*;
 
 #[test]
 fn test_init() {
-    let _ = MetabondingStakingSetup::new(metabonding_staking::contract_obj, factory::contract_obj);
+    let mut setup =
+        MetabondingStakingSetup::new(metabonding_staking::contract_obj, factory::contract_obj);
+
+    setup
+        .b_mock
+        .execute_query(&setup.mbs_wrapper, |sc| {
+            assert_eq!(sc.extended_attributes_activation_nonce().get(), 1);
+        })
+        .assert_ok();
 }
 
 #[test]
@@ -336,3 +348,138 @@ fn test_unbond() {
         })
         .assert_ok();
 }
+
+#[test]
+fn test_stake_checkpoints() {
+    let mut setup =
+        MetabondingStakingSetup::new(metabonding_staking::contract_obj, factory::contract_obj);
+
+    setup.b_mock.set_block_epoch(1);
+    setup.call_stake_locked_asset(3, 100_000_000).assert_ok();
+
+    setup.b_mock.set_block_epoch(3);
+    setup.call_stake_locked_asset(4, 1_000_000).assert_ok();
+
+    // the second unstake in the same epoch overwrites the checkpoint
+    setup.b_mock.set_block_epoch(5);
+    setup.call_unstake(51_000_000).assert_ok();
+    setup.call_unstake(10_000_000).assert_ok();
+
+    let user_addr = setup.user_address.clone();
+    setup
+        .b_mock
+        .execute_query(&setup.mbs_wrapper, |sc| {
+            let user = managed_address!(&user_addr);
+            assert_eq!(sc.stake_checkpoints(&user).len(), 3);
+
+            assert_eq!(sc.get_stake_at_epoch(user.clone(), 0), managed_biguint!(0));
+            assert_eq!(
+                sc.get_stake_at_epoch(user.clone(), 1),
+                managed_biguint!(100_000_000)
+            );
+            assert_eq!(
+                sc.get_stake_at_epoch(user.clone(), 2),
+                managed_biguint!(100_000_000)
+            );
+            assert_eq!(
+                sc.get_stake_at_epoch(user.clone(), 3),
+                managed_biguint!(101_000_000)
+            );
+            assert_eq!(
+                sc.get_stake_at_epoch(user.clone(), 5),
+                managed_biguint!(40_000_000)
+            );
+            assert_eq!(
+                sc.get_stake_at_epoch(user.clone(), 10),
+                managed_biguint!(40_000_000)
+            );
+
+            let mut snapshot = sc.get_snapshot_at_epoch(3, 0, 10).into_iter();
+            let (snapshot_user, snapshot_amount) = snapshot.next().unwrap().into_tuple();
+            assert!(snapshot.next().is_none());
+            assert_eq!(snapshot_user, user);
+            assert_eq!(snapshot_amount, managed_biguint!(101_000_000));
+
+            assert_eq!(sc.get_snapshot_at_epoch(0, 0, 10).into_iter().count(), 0);
+            assert_eq!(sc.get_snapshot_at_epoch(3, 1, 10).into_iter().count(), 0);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_weighted_stake() {
+    let mut setup =
+        MetabondingStakingSetup::new(metabonding_staking::contract_obj, factory::contract_obj);
+    setup.call_stake_locked_asset(3, 100_000_000).assert_ok();
+
+    // 100_000_000 * 15_000 * (360 + 390 + 420 + 450 + 480 + 510) / (100_000 * 730)
+    // the milestone unlocked at epoch 0 has no weight
+    let user_addr = setup.user_address.clone();
+    setup
+        .b_mock
+        .execute_query(&setup.mbs_wrapper, |sc| {
+            let user = managed_address!(&user_addr);
+            assert_eq!(
+                sc.get_weighted_stake_for_user(user),
+                managed_biguint!(53_630_136)
+            );
+            assert_eq!(
+                sc.total_weighted_stake().get(),
+                managed_biguint!(53_630_136)
+            );
+        })
+        .assert_ok();
+
+    // the weight decays as the milestones approach,
+    // the total is only updated on refresh
+    setup.b_mock.set_block_epoch(100);
+    setup
+        .b_mock
+        .execute_query(&setup.mbs_wrapper, |sc| {
+            let user = managed_address!(&user_addr);
+            assert_eq!(
+                sc.get_weighted_stake_for_user(user.clone()),
+                managed_biguint!(41_301_369)
+            );
+            assert_eq!(
+                sc.total_weighted_stake().get(),
+                managed_biguint!(53_630_136)
+            );
+
+            let mut snapshot = sc.get_weighted_snapshot().into_iter();
+            let (snapshot_user, snapshot_amount) = snapshot.next().unwrap().into_tuple();
+            assert!(snapshot.next().is_none());
+            assert_eq!(snapshot_user, user);
+            assert_eq!(snapshot_amount, managed_biguint!(41_301_369));
+        })
+        .assert_ok();
+
+    setup
+        .b_mock
+        .execute_tx(
+            &setup.owner_address,
+            &setup.mbs_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut users = MultiValueEncoded::new();
+                users.push(managed_address!(&user_addr));
+                sc.refresh_weighted_stake(users);
+
+                assert_eq!(
+                    sc.total_weighted_stake().get(),
+                    managed_biguint!(41_301_369)
+                );
+            },
+        )
+        .assert_ok();
+
+    setup.call_unstake(100_000_000).assert_ok();
+    setup
+        .b_mock
+        .execute_query(&setup.mbs_wrapper, |sc| {
+            let user = managed_address!(&user_addr);
+            assert_eq!(sc.get_weighted_stake_for_user(user), managed_biguint!(0));
+            assert_eq!(sc.total_weighted_stake().get(), managed_biguint!(0));
+        })
+        .assert_ok();
+}
@@ -7,13 +7,23 @@
 elrond_wasm_node::wasm_endpoints! {
     metabonding_staking
     (
+        addLegacyCheckpointUsers
+        getCheckpointUsers
+        getExtendedAttributesActivationNonce
         getLockedAssetFactoryAddress
         getLockedAssetTokenId
         getSnapshot
+        getSnapshotAtEpoch
+        getStakeAtEpoch
+        getStakeCheckpoints
         getStakedAmountForUser
         getTotalLockedAssetSupply
+        getTotalWeightedStake
         getUserEntry
         getUserList
+        getWeightedSnapshot
+        getWeightedStakeForUser
+        refreshWeightedStake
         stakeLockedAsset
         unbond
         unstake
@@ -2,9 +2,10 @@
 #![feature(generic_associated_types)]
 #![feature(exact_size_is_empty)]
 
-mod attr_ex_helper;
+pub mod attr_ex_helper;
 mod cache;
 mod events;
+pub mod lock_time_weight;
 pub mod locked_asset;
 pub mod locked_asset_token_merge;
 
@@ -0,0 +1,30 @@
+elrond_wasm::imports!();
+
+use common_structs::UnlockMilestoneEx;
+
+use crate::locked_asset::PERCENTAGE_TOTAL_EX;
+
+/// Milestones unlocking this many epochs from now or later have the full weight.
+pub const MAX_LOCK_WEIGHT_EPOCHS: u64 = 730;
+
+#[elrond_wasm::module]
+pub trait LockTimeWeightModule {
+    /// The amount, weighted by the remaining lock duration of each milestone.
+    /// A milestone unlocking in MAX_LOCK_WEIGHT_EPOCHS epochs or later counts fully,
+    /// and its weight decreases linearly to 0 at its unlock epoch.
+    fn get_lock_time_weighted_amount(
+        &self,
+        amount: &BigUint,
+        unlock_milestones: &ManagedVec<UnlockMilestoneEx>,
+    ) -> BigUint {
+        let current_epoch = self.blockchain().get_block_epoch();
+        let mut weight = 0u64;
+        for milestone in unlock_milestones.iter() {
+            let remaining_epochs = milestone.unlock_epoch.saturating_sub(current_epoch);
+            let weight_epochs = core::cmp::min(remaining_epochs, MAX_LOCK_WEIGHT_EPOCHS);
+            weight += milestone.unlock_percent * weight_epochs;
+        }
+
+        amount * weight / (PERCENTAGE_TOTAL_EX * MAX_LOCK_WEIGHT_EPOCHS)
+    }
+}
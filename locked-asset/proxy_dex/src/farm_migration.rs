@@ -0,0 +1,140 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use common_structs::{Nonce, WrappedFarmTokenAttributes};
+
+use super::events;
+use super::proxy_common;
+use super::proxy_pair;
+use super::wrapped_farm_token_merge;
+use super::wrapped_lp_token_merge;
+use crate::proxy_farm;
+
+mod source_farm_proxy {
+    elrond_wasm::imports!();
+
+    #[elrond_wasm::proxy]
+    pub trait Farm {
+        #[payable("*")]
+        #[endpoint(migrateToNextFarm)]
+        fn migrate_to_next_farm(
+            &self,
+            opt_orig_caller: OptionalValue<ManagedAddress>,
+        ) -> MultiValue2<EsdtTokenPayment<Self::Api>, EsdtTokenPayment<Self::Api>>;
+    }
+}
+
+#[elrond_wasm::module]
+pub trait FarmMigrationModule:
+    proxy_farm::ProxyFarmModule
+    + proxy_common::ProxyCommonModule
+    + proxy_pair::ProxyPairModule
+    + token_merge::TokenMergeModule
+    + token_send::TokenSendModule
+    + wrapped_farm_token_merge::WrappedFarmTokenMerge
+    + wrapped_lp_token_merge::WrappedLpTokenMerge
+    + events::EventsModule
+{
+    /// Migrates the farm position behind a wrapped farm token
+    /// to the migration destination of its farm.
+    #[payable("*")]
+    #[endpoint(migrateFarmPosition)]
+    fn migrate_farm_position(&self, farm_address: ManagedAddress) {
+        let (payment_token_id, payment_token_nonce, payment_amount) =
+            self.call_value().single_esdt().into_tuple();
+
+        self.require_is_intermediated_farm(&farm_address);
+        let wrapped_farm_token_attrs = self.get_wrapped_farm_token_attributes_for_migration(
+            &payment_token_id,
+            payment_token_nonce,
+            &payment_amount,
+        );
+
+        let call_result: MultiValue2<EsdtTokenPayment<Self::Api>, EsdtTokenPayment<Self::Api>> =
+            self.source_farm_proxy(farm_address.clone())
+                .migrate_to_next_farm(self.get_orig_caller_arg(&farm_address))
+                .add_esdt_token_transfer(
+                    wrapped_farm_token_attrs.farm_token_id.clone(),
+                    wrapped_farm_token_attrs.farm_token_nonce,
+                    payment_amount.clone(),
+                )
+                .execute_on_dest_context();
+
+        let (new_pos, reward) = call_result.into_tuple();
+        self.rewrap_migrated_farm_position(
+            payment_token_id,
+            payment_token_nonce,
+            payment_amount,
+            wrapped_farm_token_attrs,
+            new_pos,
+            reward,
+        );
+    }
+
+    fn get_wrapped_farm_token_attributes_for_migration(
+        &self,
+        payment_token_id: &TokenIdentifier,
+        payment_token_nonce: Nonce,
+        payment_amount: &BigUint,
+    ) -> WrappedFarmTokenAttributes<Self::Api> {
+        self.require_wrapped_farm_token_id_not_empty();
+        self.require_wrapped_lp_token_id_not_empty();
+
+        let wrapped_farm_token = self.wrapped_farm_token().get_token_id();
+        require!(
+            payment_token_id == &wrapped_farm_token,
+            "Should only be used with wrapped farm tokens"
+        );
+        require!(payment_amount != &0u64, "Payment amount cannot be zero");
+
+        self.get_wrapped_farm_token_attributes(payment_token_id, payment_token_nonce)
+    }
+
+    /// Burns the old wrapped farm token and sends the caller a new one
+    /// for the migrated farm position, together with the rewards, if any.
+    fn rewrap_migrated_farm_position(
+        &self,
+        payment_token_id: TokenIdentifier,
+        payment_token_nonce: Nonce,
+        payment_amount: BigUint,
+        wrapped_farm_token_attrs: WrappedFarmTokenAttributes<Self::Api>,
+        new_pos: EsdtTokenPayment<Self::Api>,
+        reward: EsdtTokenPayment<Self::Api>,
+    ) {
+        self.send()
+            .esdt_local_burn(&payment_token_id, payment_token_nonce, &payment_amount);
+
+        let new_attrs = WrappedFarmTokenAttributes {
+            farm_token_id: new_pos.token_identifier.clone(),
+            farm_token_nonce: new_pos.token_nonce,
+            farm_token_amount: new_pos.amount.clone(),
+            farming_token_id: wrapped_farm_token_attrs.farming_token_id,
+            farming_token_nonce: wrapped_farm_token_attrs.farming_token_nonce,
+            farming_token_amount: self.rule_of_three_non_zero_result(
+                &payment_amount,
+                &wrapped_farm_token_attrs.farm_token_amount,
+                &wrapped_farm_token_attrs.farming_token_amount,
+            ),
+        };
+        let new_nonce =
+            self.send()
+                .esdt_nft_create_compact(&payment_token_id, &new_pos.amount, &new_attrs);
+
+        let mut payments = ManagedVec::new();
+        payments.push(EsdtTokenPayment::new(
+            payment_token_id,
+            new_nonce,
+            new_pos.amount,
+        ));
+
+        if reward.amount != 0u64 {
+            payments.push(reward);
+        }
+
+        let caller = self.blockchain().get_caller();
+        self.send().direct_multi(&caller, &payments);
+    }
+
+    #[proxy]
+    fn source_farm_proxy(&self, to: ManagedAddress) -> source_farm_proxy::Proxy<Self::Api>;
+}
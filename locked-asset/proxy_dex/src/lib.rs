@@ -7,6 +7,7 @@ elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
 mod events;
+pub mod farm_migration;
 pub mod migration_from_v1_2;
 pub mod proxy_common;
 pub mod proxy_farm;
@@ -30,6 +31,7 @@ pub trait ProxyDexImpl:
     + wrapped_farm_token_merge::WrappedFarmTokenMerge
     + wrapped_lp_token_merge::WrappedLpTokenMerge
     + events::EventsModule
+    + farm_migration::FarmMigrationModule
     + migration_from_v1_2::MigrationModule
     + elrond_wasm_modules::default_issue_callbacks::DefaultIssueCallbacksModule
 {
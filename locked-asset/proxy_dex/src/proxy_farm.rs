@@ -19,6 +19,27 @@ type ClaimRewardsResultType<BigUint> =
 type ExitFarmResultType<BigUint> =
     MultiValue2<EsdtTokenPayment<BigUint>, EsdtTokenPayment<BigUint>>;
 
+mod farm_with_lock_proxy {
+    elrond_wasm::imports!();
+
+    #[elrond_wasm::proxy]
+    pub trait FarmWithLock {
+        #[payable("*")]
+        #[endpoint(exitFarm)]
+        fn exit_farm(
+            &self,
+            opt_orig_caller: OptionalValue<ManagedAddress>,
+        ) -> MultiValue2<EsdtTokenPayment<Self::Api>, EsdtTokenPayment<Self::Api>>;
+
+        #[payable("*")]
+        #[endpoint(claimRewards)]
+        fn claim_rewards(
+            &self,
+            opt_orig_caller: OptionalValue<ManagedAddress>,
+        ) -> MultiValue2<EsdtTokenPayment<Self::Api>, EsdtTokenPayment<Self::Api>>;
+    }
+}
+
 #[derive(ManagedVecItem, Clone)]
 pub struct WrappedFarmToken<M: ManagedTypeApi> {
     pub token: EsdtTokenPayment<M>,
@@ -46,6 +67,24 @@ pub trait ProxyFarmModule:
     fn remove_intermediated_farm(&self, farm_address: ManagedAddress) {
         self.require_is_intermediated_farm(&farm_address);
         self.intermediated_farms().remove(&farm_address);
+        self.orig_caller_farms().remove(&farm_address);
+    }
+
+    /// Makes the proxy pass the original caller when exiting or claiming from the farm,
+    /// so farms with lock can apply the reward unlock option selected by the user.
+    /// The proxy must be a trusted proxy of the farm.
+    #[only_owner]
+    #[endpoint(addOrigCallerFarm)]
+    fn add_orig_caller_farm(&self, farm_address: ManagedAddress) {
+        self.require_is_intermediated_farm(&farm_address);
+        self.orig_caller_farms().insert(farm_address);
+    }
+
+    #[only_owner]
+    #[endpoint(removeOrigCallerFarm)]
+    fn remove_orig_caller_farm(&self, farm_address: ManagedAddress) {
+        let removed = self.orig_caller_farms().remove(&farm_address);
+        require!(removed, "Not an original caller farm");
     }
 
     #[payable("*")]
@@ -437,8 +476,8 @@ pub trait ProxyFarmModule:
         farm_token_nonce: Nonce,
         amount: &BigUint,
     ) -> ExitFarmResultType<Self::Api> {
-        self.farm_contract_proxy(farm_address.clone())
-            .exit_farm()
+        self.farm_with_lock_proxy(farm_address.clone())
+            .exit_farm(self.get_orig_caller_arg(farm_address))
             .add_esdt_token_transfer(farm_token_id.clone(), farm_token_nonce, amount.clone())
             .execute_on_dest_context()
     }
@@ -457,8 +496,8 @@ pub trait ProxyFarmModule:
             amount.clone(),
         ));
 
-        self.farm_contract_proxy(farm_address.clone())
-            .claim_rewards()
+        self.farm_with_lock_proxy(farm_address.clone())
+            .claim_rewards(self.get_orig_caller_arg(farm_address))
             .with_multi_token_transfer(payments)
             .execute_on_dest_context()
     }
@@ -483,6 +522,15 @@ pub trait ProxyFarmModule:
             .execute_on_dest_context()
     }
 
+    /// Passing no argument keeps the calls compatible with farms without lock.
+    fn get_orig_caller_arg(&self, farm_address: &ManagedAddress) -> OptionalValue<ManagedAddress> {
+        if self.orig_caller_farms().contains(farm_address) {
+            OptionalValue::Some(self.blockchain().get_caller())
+        } else {
+            OptionalValue::None
+        }
+    }
+
     fn require_is_intermediated_farm(&self, address: &ManagedAddress) {
         require!(
             self.intermediated_farms().contains(address),
@@ -493,4 +541,11 @@ pub trait ProxyFarmModule:
     fn require_wrapped_farm_token_id_not_empty(&self) {
         require!(!self.wrapped_farm_token().is_empty(), "Empty token id");
     }
+
+    #[view(getOrigCallerFarms)]
+    #[storage_mapper("orig_caller_farms")]
+    fn orig_caller_farms(&self) -> SetMapper<ManagedAddress>;
+
+    #[proxy]
+    fn farm_with_lock_proxy(&self, to: ManagedAddress) -> farm_with_lock_proxy::Proxy<Self::Api>;
 }
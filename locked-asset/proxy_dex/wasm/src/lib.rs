@@ -10,6 +10,7 @@ elrond_wasm_node::wasm_endpoints! {
         callBack
         addFarmToIntermediate
         addLiquidityProxy
+        addOrigCallerFarm
         addPairToIntermediate
         claimRewardsProxy
         compoundRewardsProxy
@@ -19,15 +20,18 @@ elrond_wasm_node::wasm_endpoints! {
         getIntermediatedFarms
         getIntermediatedPairs
         getLockedAssetTokenId
+        getOrigCallerFarms
         getWrappedFarmTokenId
         getWrappedLpTokenId
         mergeWrappedFarmTokens
         mergeWrappedLpTokens
+        migrateFarmPosition
         migrateV1_2Position
         registerProxyFarm
         registerProxyPair
         removeIntermediatedFarm
         removeIntermediatedPair
         removeLiquidityProxy
+        removeOrigCallerFarm
     )
 }